@@ -0,0 +1,173 @@
+//! Compute-unit benchmark of the hot scenarios
+//!
+//! Runs [`hot_scenarios`] through `solana-program-test` against the
+//! program's SBF build, so the units are those a cluster would charge.
+//! Build the program first and point `SBF_OUT_DIR` at it:
+//!
+//! ```text
+//! cargo build-sbf --manifest-path programs/x402_attestation/Cargo.toml
+//! SBF_OUT_DIR=$PWD/target/deploy cargo bench -p x402-attestation-client --bench cu
+//! ```
+//!
+//! By default the measurement is compared against `bench/cu_baseline.json`
+//! and the run fails on a regression above `CU_MAX_INCREASE_PERCENT`
+//! (default 10), or on a scenario the baseline doesn't cover yet. Pass
+//! `-- --write` to store the measurement as the new baseline instead.
+
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+use solana_program_test::{tokio, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+use x402_attestation_client::bench::{hot_scenarios, measure, CuBaseline};
+use x402_attestation_client::preflight::Simulation;
+use x402_attestation_client::ID;
+
+const BASELINE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/bench/cu_baseline.json");
+
+/// Cluster time the scenarios run at
+const NOW: i64 = 1_760_000_000;
+
+/// Lamports given to each signer
+const FUNDING: u64 = 100_000_000_000;
+
+fn main() -> ExitCode {
+    let write = std::env::args().any(|arg| arg == "--write");
+    let max_increase = match std::env::var("CU_MAX_INCREASE_PERCENT") {
+        Ok(value) => value
+            .parse()
+            .expect("CU_MAX_INCREASE_PERCENT is a whole percentage"),
+        Err(_) => 10,
+    };
+
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let [admin, attester, source] = &signers;
+    let (fixtures, scenarios) =
+        hot_scenarios(&admin.pubkey(), &attester.pubkey(), &source.pubkey(), NOW);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("tokio runtime");
+    let mut program_test = ProgramTest::new("x402_attestation", ID, None);
+    program_test.prefer_bpf(true);
+    let mut context = runtime.block_on(program_test.start_with_context());
+    for fixture in fixtures.accounts() {
+        let account = Account {
+            lamports: fixture.lamports,
+            data: fixture.data,
+            owner: fixture.owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        context.set_account(&fixture.address, &AccountSharedData::from(account));
+    }
+    for signer in &signers {
+        let account = Account::new(FUNDING, 0, &solana_sdk::system_program::ID);
+        context.set_account(&signer.pubkey(), &AccountSharedData::from(account));
+    }
+    let mut clock: Clock = runtime
+        .block_on(context.banks_client.get_sysvar())
+        .expect("clock sysvar");
+    clock.unix_timestamp = NOW;
+    context.set_sysvar(&clock);
+
+    let keypairs: HashMap<Pubkey, &Keypair> = signers
+        .iter()
+        .map(|signer| (signer.pubkey(), signer))
+        .collect();
+    let current = match measure(&scenarios, |instructions| {
+        runtime.block_on(simulate(&mut context, &keypairs, instructions))
+    }) {
+        Ok(current) => current,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for (name, units) in &current.0 {
+        println!("{name}: {units} CU");
+    }
+
+    if write {
+        std::fs::write(BASELINE_PATH, current.to_json() + "\n").expect("write the baseline");
+        println!("wrote {BASELINE_PATH}");
+        return ExitCode::SUCCESS;
+    }
+
+    let committed = std::fs::read_to_string(BASELINE_PATH).expect("read the baseline");
+    let committed = CuBaseline::from_json(&committed).expect("baseline is a JSON object");
+    let mut failed = false;
+    for regression in committed.regressions(&current, max_increase) {
+        eprintln!(
+            "{}: {} CU, up from {} (more than {max_increase}%)",
+            regression.name, regression.current, regression.baseline
+        );
+        failed = true;
+    }
+    for name in current
+        .0
+        .keys()
+        .filter(|name| !committed.0.contains_key(*name))
+    {
+        eprintln!("{name}: not in the baseline; run with -- --write and commit it");
+        failed = true;
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Simulate `instructions` against the seeded bank, signed by whichever
+/// of `keypairs` they need and paid for by the context's payer
+async fn simulate(
+    context: &mut ProgramTestContext,
+    keypairs: &HashMap<Pubkey, &Keypair>,
+    instructions: &[Instruction],
+) -> Result<Simulation, String> {
+    let mut signers: Vec<&Keypair> = vec![&context.payer];
+    for meta in instructions
+        .iter()
+        .flat_map(|instruction| &instruction.accounts)
+    {
+        if meta.is_signer && signers.iter().all(|signer| signer.pubkey() != meta.pubkey) {
+            let keypair = keypairs
+                .get(&meta.pubkey)
+                .ok_or_else(|| format!("no keypair for signer {}", meta.pubkey))?;
+            signers.push(keypair);
+        }
+    }
+    let blockhash = context
+        .banks_client
+        .get_latest_blockhash()
+        .await
+        .map_err(|e| e.to_string())?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &signers,
+        blockhash,
+    );
+
+    let result = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .map_err(|e| e.to_string())?;
+    let details = result.simulation_details;
+    Ok(Simulation {
+        err: match result.result {
+            Some(Err(e)) => Some(e.to_string()),
+            _ => None,
+        },
+        logs: details.as_ref().map(|d| d.logs.clone()).unwrap_or_default(),
+        units_consumed: details.map(|d| d.units_consumed),
+    })
+}
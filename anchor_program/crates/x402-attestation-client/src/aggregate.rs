@@ -0,0 +1,184 @@
+//! Aggregated proofs
+//!
+//! One recursive proof can cover a batch of claims: the batch is attested
+//! once with `attest_aggregated_proof`, committing to the Merkle root over
+//! the claims' `aggregate_leaf`s, and each claim's attestation is created
+//! later, by anyone, with `materialize_from_batch` and the claim's path.
+//! The tree is hashed like the historical import tree, so
+//! [`HistoricalTree`] builds it.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use crate::history::HistoricalTree;
+use crate::instructions::PublicInputs;
+use crate::{
+    aggregate_proof_address, attestation_address, billing_period_address, claim_verdicts_address,
+    config_address, maintenance_schedule_address, month_id, program_stats_address,
+    recent_attestations_address, schema_address, settlement_queue_address, AggregateProof,
+    DEFAULT_SCHEMA_ID, ID,
+};
+
+pub use x402_attestation::{aggregate_leaf, AGGREGATE_LEAF_DOMAIN};
+
+/// One claim covered by an aggregated proof
+#[derive(Debug, Clone)]
+pub struct AggregateClaim {
+    pub claim_id: [u8; 32],
+    pub public_inputs: PublicInputs,
+    pub evidence_slot: u64,
+    pub refund_signature: [u8; 64],
+    pub merchant_id: [u8; 32],
+}
+
+impl AggregateClaim {
+    pub fn leaf(&self) -> [u8; 32] {
+        aggregate_leaf(
+            &self.claim_id,
+            &self.public_inputs.to_array(),
+            self.evidence_slot,
+            &self.refund_signature,
+            &self.merchant_id,
+        )
+    }
+}
+
+/// Tree over a batch, in the given order; its root is the `batch_root`
+///
+/// # Panics
+///
+/// If `claims` is empty.
+pub fn batch_tree(claims: &[AggregateClaim]) -> HistoricalTree {
+    HistoricalTree::new(claims.iter().map(AggregateClaim::leaf).collect())
+}
+
+/// `attest_aggregated_proof` for `claims`, paid for and signed by `authority`
+///
+/// All of `claims` are proven by circuit `circuit_version` under schema
+/// `schema_id`.
+///
+/// # Panics
+///
+/// If `claims` is empty.
+pub fn attest_aggregated_proof(
+    authority: &Pubkey,
+    claims: &[AggregateClaim],
+    proof_hash: [u8; 32],
+    hash_algo: u8,
+    circuit_version: u16,
+    schema_id: u16,
+) -> Instruction {
+    let batch_root = batch_tree(claims).root();
+    let accounts = x402_attestation::accounts::AttestAggregatedProof {
+        config: config_address(),
+        aggregate_proof: aggregate_proof_address(&batch_root),
+        authority: *authority,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::AttestAggregatedProof {
+        batch_root,
+        proof_hash,
+        hash_algo,
+        count: claims.len() as u32,
+        total_payout: claims
+            .iter()
+            .map(|claim| claim.public_inputs.payout.0)
+            .sum(),
+        circuit_version,
+        schema_id,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `materialize_from_batch` for a claim of the `aggregate` batch, paid for
+/// and signed by `payer`
+///
+/// `inclusion_proof` comes from [`batch_tree`]. Pass `claim_verdicts` when
+/// the config sets `verdict_threshold`, `settlement_queue` when it sets
+/// `max_pending_settlements`, and `refund_claimant` when it sets
+/// `require_refund_transfer`, adding the
+/// [`crate::instructions::refund_transfer`] to the transaction.
+pub fn materialize_from_batch(
+    payer: &Pubkey,
+    aggregate: &AggregateProof,
+    claim: &AggregateClaim,
+    inclusion_proof: Vec<[u8; 32]>,
+    claim_verdicts: bool,
+    settlement_queue: bool,
+    refund_claimant: Option<Pubkey>,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::MaterializeFromBatch {
+        config: config_address(),
+        aggregate_proof: aggregate_proof_address(&aggregate.batch_root),
+        attestation: attestation_address(&claim.claim_id),
+        recent_attestations: recent_attestations_address(),
+        program_stats: program_stats_address(),
+        billing_period: billing_period_address(month_id(aggregate.attested_at)),
+        schema: (aggregate.schema_id != DEFAULT_SCHEMA_ID)
+            .then(|| schema_address(aggregate.schema_id)),
+        maintenance_schedule: maintenance_schedule_address(&claim.merchant_id),
+        claim_verdicts: claim_verdicts.then(|| claim_verdicts_address(&claim.claim_id)),
+        settlement_queue: settlement_queue.then(settlement_queue_address),
+        claimant: refund_claimant,
+        instructions: refund_claimant.map(|_| sysvar_instructions::ID),
+        payer: *payer,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::MaterializeFromBatch {
+        claim_id: claim.claim_id,
+        public_inputs: claim.public_inputs.to_array(),
+        evidence_slot: claim.evidence_slot,
+        refund_signature: claim.refund_signature,
+        merchant_id: claim.merchant_id,
+        inclusion_proof,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::merkle_root;
+    use crate::MicroUsdc;
+
+    #[test]
+    fn every_claim_path_leads_to_the_batch_root() {
+        let claims: Vec<AggregateClaim> = (0..5u8)
+            .map(|i| AggregateClaim {
+                claim_id: [i; 32],
+                public_inputs: PublicInputs {
+                    fraud_detected: true,
+                    http_status: 503,
+                    body_length: 0,
+                    payout: MicroUsdc(u64::from(i) * 1_000),
+                },
+                evidence_slot: 100 + u64::from(i),
+                refund_signature: [i; 64],
+                merchant_id: [7; 32],
+            })
+            .collect();
+        let tree = batch_tree(&claims);
+
+        for (index, claim) in claims.iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            assert_eq!(merkle_root(claim.leaf(), &proof), tree.root());
+
+            let mut forged = claim.clone();
+            forged.public_inputs.payout = MicroUsdc(1_000_000);
+            assert_ne!(merkle_root(forged.leaf(), &proof), tree.root());
+        }
+    }
+}
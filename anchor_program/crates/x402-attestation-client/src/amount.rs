@@ -0,0 +1,209 @@
+//! Decimal-safe USDC amounts
+//!
+//! The program stores every amount in micro-USDC (10^-6 USDC) as a bare
+//! `u64`. These types make the unit explicit at API boundaries so a
+//! micro-USDC value can't be passed where whole USDC was meant, or vice
+//! versa. No floating point is involved anywhere.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Micro-USDC per USDC (USDC has 6 decimals)
+pub const MICRO_USDC_PER_USDC: u64 = 1_000_000;
+
+const DECIMALS: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AmountError {
+    #[error("invalid USDC amount {0:?}")]
+    InvalidFormat(String),
+
+    #[error("USDC amount {0:?} has more than 6 decimal places")]
+    TooManyDecimals(String),
+
+    #[error("USDC amount overflows u64 micro-USDC")]
+    Overflow,
+}
+
+/// Amount in micro-USDC, the on-chain unit
+///
+/// Serializes as a plain integer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MicroUsdc(pub u64);
+
+impl MicroUsdc {
+    pub const ZERO: MicroUsdc = MicroUsdc(0);
+
+    pub fn checked_add(self, other: MicroUsdc) -> Option<MicroUsdc> {
+        self.0.checked_add(other.0).map(MicroUsdc)
+    }
+
+    pub fn checked_sub(self, other: MicroUsdc) -> Option<MicroUsdc> {
+        self.0.checked_sub(other.0).map(MicroUsdc)
+    }
+
+    pub fn checked_mul(self, factor: u64) -> Option<MicroUsdc> {
+        self.0.checked_mul(factor).map(MicroUsdc)
+    }
+
+    /// Convert to whole-USDC representation (lossless)
+    pub fn to_usdc(self) -> UsdcAmount {
+        UsdcAmount(self)
+    }
+}
+
+impl From<u64> for MicroUsdc {
+    fn from(value: u64) -> Self {
+        MicroUsdc(value)
+    }
+}
+
+impl From<MicroUsdc> for u64 {
+    fn from(value: MicroUsdc) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for MicroUsdc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} micro-USDC", self.0)
+    }
+}
+
+/// Amount in whole USDC with exactly 6 decimals of precision
+///
+/// Parses and serializes as a decimal string (`"0.01"`, `"1250.5"`) so JSON
+/// consumers never round-trip it through a float.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UsdcAmount(MicroUsdc);
+
+impl UsdcAmount {
+    /// Whole USDC, e.g. `UsdcAmount::from_whole(5)` is 5.000000 USDC
+    pub fn from_whole(usdc: u64) -> Result<UsdcAmount, AmountError> {
+        usdc.checked_mul(MICRO_USDC_PER_USDC)
+            .map(|micro| UsdcAmount(MicroUsdc(micro)))
+            .ok_or(AmountError::Overflow)
+    }
+
+    pub fn to_micro(self) -> MicroUsdc {
+        self.0
+    }
+
+    pub fn checked_add(self, other: UsdcAmount) -> Option<UsdcAmount> {
+        self.0.checked_add(other.0).map(UsdcAmount)
+    }
+
+    pub fn checked_sub(self, other: UsdcAmount) -> Option<UsdcAmount> {
+        self.0.checked_sub(other.0).map(UsdcAmount)
+    }
+
+    /// Format with locale-specific separators
+    ///
+    /// `decimal` replaces the `.`; `grouping`, if set, is inserted every
+    /// three integer digits (e.g. `('.', Some(','))` → `"1,234.50"`,
+    /// `(',', Some('.'))` → `"1.234,50"`). At least two decimals are kept.
+    pub fn to_localized_string(&self, decimal: char, grouping: Option<char>) -> String {
+        let whole = (self.0 .0 / MICRO_USDC_PER_USDC).to_string();
+        let fraction = self.fraction_digits(2);
+
+        let mut out = String::with_capacity(whole.len() * 4 / 3 + fraction.len() + 1);
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i).is_multiple_of(3) {
+                if let Some(separator) = grouping {
+                    out.push(separator);
+                }
+            }
+            out.push(digit);
+        }
+        if !fraction.is_empty() {
+            out.push(decimal);
+            out.push_str(&fraction);
+        }
+        out
+    }
+
+    /// Fractional digits with trailing zeros trimmed, keeping at least `min`
+    fn fraction_digits(&self, min: usize) -> String {
+        let fraction = format!("{:0width$}", self.0 .0 % MICRO_USDC_PER_USDC, width = DECIMALS);
+        let trimmed = fraction.trim_end_matches('0');
+        if trimmed.len() >= min {
+            trimmed.to_string()
+        } else {
+            fraction[..min].to_string()
+        }
+    }
+}
+
+impl From<MicroUsdc> for UsdcAmount {
+    fn from(value: MicroUsdc) -> Self {
+        UsdcAmount(value)
+    }
+}
+
+impl From<UsdcAmount> for MicroUsdc {
+    fn from(value: UsdcAmount) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for UsdcAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let whole = self.0 .0 / MICRO_USDC_PER_USDC;
+        let fraction = self.fraction_digits(0);
+        if fraction.is_empty() {
+            write!(f, "{whole}")
+        } else {
+            write!(f, "{whole}.{fraction}")
+        }
+    }
+}
+
+impl FromStr for UsdcAmount {
+    type Err = AmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AmountError::InvalidFormat(s.to_string());
+
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid());
+        }
+        if !whole.bytes().all(|b| b.is_ascii_digit()) || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        if fraction.len() > DECIMALS {
+            return Err(AmountError::TooManyDecimals(s.to_string()));
+        }
+
+        let whole: u64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| AmountError::Overflow)?
+        };
+        let fraction: u64 = format!("{fraction:0<width$}", width = DECIMALS)
+            .parse()
+            .map_err(|_| invalid())?;
+
+        whole
+            .checked_mul(MICRO_USDC_PER_USDC)
+            .and_then(|micro| micro.checked_add(fraction))
+            .map(|micro| UsdcAmount(MicroUsdc(micro)))
+            .ok_or(AmountError::Overflow)
+    }
+}
+
+impl Serialize for UsdcAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for UsdcAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
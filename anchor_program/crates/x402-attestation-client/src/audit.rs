@@ -0,0 +1,158 @@
+//! Attestation integrity audit
+//!
+//! Recomputes every attestation's digests from its raw account data and
+//! compares them with an indexer's copy, reporting each divergence with
+//! the offending account. Feed it the program's attestation accounts
+//! (`getProgramAccounts` with a memcmp on `ProofAttestation::DISCRIMINATOR`)
+//! and the `(address, canonical_digest)` pairs the indexer holds.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::{attestation_address, decode_attestation, ClientError};
+use x402_attestation::LEGACY_SEQUENCE;
+
+#[derive(Debug)]
+pub enum Divergence {
+    /// Account data does not decode as a current-layout attestation
+    Undecodable { address: Pubkey, error: ClientError },
+    /// The account is not the PDA of the claim_id it stores
+    WrongAddress { address: Pubkey, expected: Pubkey },
+    /// Stored `evm_digest` differs from the recomputed struct hash
+    EvmDigestMismatch { address: Pubkey },
+    /// Indexer digest differs from the on-chain canonical digest
+    DigestMismatch {
+        address: Pubkey,
+        indexed: [u8; 32],
+        on_chain: [u8; 32],
+    },
+    /// Indexed, but no such attestation on-chain (e.g., archived)
+    MissingOnChain { address: Pubkey },
+    /// On-chain, but absent from the index
+    MissingFromIndex { address: Pubkey },
+    /// Several attestations carry the same sequence number
+    DuplicateSequence { sequence: u64, addresses: Vec<Pubkey> },
+    /// Sequence numbers `from..to` are not held by any attestation
+    SequenceGap { from: u64, to: u64 },
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Divergence::Undecodable { address, error } => write!(f, "{address}: {error}"),
+            Divergence::WrongAddress { address, expected } => {
+                write!(f, "{address}: stored claim_id belongs to {expected}")
+            }
+            Divergence::EvmDigestMismatch { address } => {
+                write!(f, "{address}: evm_digest does not match the record")
+            }
+            Divergence::DigestMismatch { address, .. } => {
+                write!(f, "{address}: indexed digest differs from on-chain record")
+            }
+            Divergence::MissingOnChain { address } => {
+                write!(f, "{address}: indexed but not found on-chain")
+            }
+            Divergence::MissingFromIndex { address } => {
+                write!(f, "{address}: on-chain but missing from the index")
+            }
+            Divergence::DuplicateSequence { sequence, addresses } => {
+                write!(f, "sequence {sequence} used by {} attestations", addresses.len())
+            }
+            Divergence::SequenceGap { from, to } => {
+                write!(f, "sequences {from}..{to} missing (archived or never indexed)")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AuditReport {
+    /// On-chain attestations examined
+    pub checked: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl AuditReport {
+    pub fn is_clean(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+/// Audit raw on-chain attestations against indexed canonical digests
+///
+/// Sequence gaps are reported too; archived attestations leave gaps by
+/// design, so check those against the `ArchiveSummary` counts.
+/// Attestations migrated from layout 1 or imported (`LEGACY_SEQUENCE`)
+/// are exempt.
+pub fn audit_attestations<I, J>(on_chain: I, indexed: J) -> AuditReport
+where
+    I: IntoIterator<Item = (Pubkey, Vec<u8>)>,
+    J: IntoIterator<Item = (Pubkey, [u8; 32])>,
+{
+    let mut report = AuditReport::default();
+    let mut indexed: BTreeMap<Pubkey, [u8; 32]> = indexed.into_iter().collect();
+    let mut sequences: BTreeMap<u64, Vec<Pubkey>> = BTreeMap::new();
+
+    for (address, data) in on_chain {
+        report.checked += 1;
+        let attestation = match decode_attestation(&address, &data) {
+            Ok(attestation) => attestation,
+            Err(error) => {
+                indexed.remove(&address);
+                report.divergences.push(Divergence::Undecodable { address, error });
+                continue;
+            }
+        };
+
+        let expected = attestation_address(&attestation.claim_id);
+        if expected != address {
+            report.divergences.push(Divergence::WrongAddress { address, expected });
+        }
+        if attestation.evm_digest != attestation.evm_struct_hash() {
+            report.divergences.push(Divergence::EvmDigestMismatch { address });
+        }
+
+        let on_chain = attestation.canonical_digest();
+        match indexed.remove(&address) {
+            Some(digest) if digest != on_chain => {
+                report.divergences.push(Divergence::DigestMismatch {
+                    address,
+                    indexed: digest,
+                    on_chain,
+                });
+            }
+            Some(_) => {}
+            None => report.divergences.push(Divergence::MissingFromIndex { address }),
+        }
+
+        if attestation.sequence != LEGACY_SEQUENCE {
+            sequences.entry(attestation.sequence).or_default().push(address);
+        }
+    }
+
+    report.divergences.extend(
+        indexed
+            .into_keys()
+            .map(|address| Divergence::MissingOnChain { address }),
+    );
+
+    let mut next = 0;
+    for (sequence, addresses) in sequences {
+        if sequence > next {
+            report.divergences.push(Divergence::SequenceGap {
+                from: next,
+                to: sequence,
+            });
+        }
+        if addresses.len() > 1 {
+            report
+                .divergences
+                .push(Divergence::DuplicateSequence { sequence, addresses });
+        }
+        next = sequence + 1;
+    }
+
+    report
+}
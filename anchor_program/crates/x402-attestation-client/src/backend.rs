@@ -0,0 +1,471 @@
+//! Reference [`InsuranceBackend`] over the attestation program
+//!
+//! Filing a claim attests it, settling commits a settlement batch and a
+//! dispute records a fraud verdict. The program prices and binds no
+//! coverage, so `quote` and `bind` are unsupported. Claims are attested
+//! with the default config in mind: deployments that require verdicts,
+//! schemas, dual proofs or the refund transfer build the attestation
+//! with [`crate::instructions`] instead.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use x402_insurance_interface::{
+    ClaimId, ClaimRequest, ClaimStatus, Dispute, InsuranceBackend, InsuranceError, Operation,
+    Settlement,
+};
+
+use crate::amount::MicroUsdc;
+use crate::errors::{AttestationError, ErrorCode};
+use crate::instructions::{
+    attest_claim_proof, commit_settlement_batch, record_verdict, AttestArgs, PublicInputs,
+};
+use crate::settlement::settlement_root;
+use crate::{
+    attestation_address, decode_attestation, decode_settlement_batch, decode_settlement_ledger,
+    month_id, settlement_batch_address, settlement_ledger_address, settlement_queue_address,
+    ProofAttestation, SettlementBatch, SettlementLedger, DEFAULT_SCHEMA_ID,
+};
+use x402_attestation::{HASH_ALGO_BLAKE3, LEGACY_SEQUENCE};
+
+/// Chain access for [`ProgramBackend`], over the caller's RPC client
+///
+/// Errors are the transport's message. A failed transaction's message
+/// should carry its error, e.g. `custom program error: 0x1771`.
+pub trait ProgramTransport {
+    /// Sign `instructions` as one transaction, send it and wait until it
+    /// is confirmed
+    fn submit(&mut self, instructions: &[Instruction]) -> Result<(), String>;
+
+    /// Raw account data, `None` if the account doesn't exist
+    fn account(&mut self, address: &Pubkey) -> Result<Option<Vec<u8>>, String>;
+
+    /// Current cluster unix time
+    fn unix_timestamp(&mut self) -> Result<i64, String>;
+
+    /// Leaves of settlement batch `batch_index`, in order, as listed by
+    /// its `SettlementBatchCommitted` event
+    fn batch_leaves(&mut self, batch_index: u64) -> Result<Vec<[u8; 32]>, String>;
+}
+
+pub struct ProgramBackend<T> {
+    pub transport: T,
+    /// Registered attester signing claims
+    pub attester: Pubkey,
+    /// Config admin, to settle claims
+    pub admin: Option<Pubkey>,
+    /// Registered verdict source, to dispute claims
+    pub verdict_source: Option<Pubkey>,
+}
+
+impl<T: ProgramTransport> ProgramBackend<T> {
+    fn attestation(
+        &mut self,
+        claim_id: &ClaimId,
+    ) -> Result<Option<ProofAttestation>, InsuranceError> {
+        let address = attestation_address(claim_id);
+        match self
+            .transport
+            .account(&address)
+            .map_err(InsuranceError::Unavailable)?
+        {
+            Some(data) => decode_attestation(&address, &data)
+                .map(Some)
+                .map_err(|e| InsuranceError::Unavailable(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    fn settlement_ledger(&mut self) -> Result<Option<SettlementLedger>, InsuranceError> {
+        let address = settlement_ledger_address();
+        match self
+            .transport
+            .account(&address)
+            .map_err(InsuranceError::Unavailable)?
+        {
+            Some(data) => decode_settlement_ledger(&address, &data)
+                .map(Some)
+                .map_err(|e| InsuranceError::Unavailable(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    fn settlement_batch(&mut self, batch_index: u64) -> Result<SettlementBatch, InsuranceError> {
+        let address = settlement_batch_address(batch_index);
+        let data = self
+            .transport
+            .account(&address)
+            .map_err(InsuranceError::Unavailable)?
+            .ok_or_else(|| InsuranceError::Unavailable(format!("batch {address} not found")))?;
+        decode_settlement_batch(&address, &data)
+            .map_err(|e| InsuranceError::Unavailable(e.to_string()))
+    }
+
+    /// Whether a committed settlement batch holds `record`
+    ///
+    /// Batches cover consecutive sequence ranges in index order, but may
+    /// pass over claims in their range, so a batch with fewer leaves than
+    /// sequences is checked leaf by leaf.
+    fn is_settled(&mut self, record: &ProofAttestation) -> Result<bool, InsuranceError> {
+        if record.sequence == LEGACY_SEQUENCE {
+            return Ok(false);
+        }
+        let batch_count = self
+            .settlement_ledger()?
+            .map_or(0, |ledger| ledger.batch_count);
+
+        let (mut low, mut high) = (0, batch_count);
+        let batch = loop {
+            if low == high {
+                return Ok(false);
+            }
+            let batch = self.settlement_batch(low + (high - low) / 2)?;
+            if record.sequence < batch.first_sequence {
+                high = batch.batch_index;
+            } else if record.sequence > batch.last_sequence {
+                low = batch.batch_index + 1;
+            } else {
+                break batch;
+            }
+        };
+        if u64::from(batch.leaf_count) == batch.last_sequence - batch.first_sequence + 1 {
+            return Ok(true);
+        }
+
+        let leaves = self
+            .transport
+            .batch_leaves(batch.batch_index)
+            .map_err(InsuranceError::Unavailable)?;
+        if settlement_root(&leaves) != batch.root {
+            return Err(InsuranceError::Unavailable(format!(
+                "leaves of batch {} don't match its root",
+                batch.batch_index
+            )));
+        }
+        Ok(leaves.contains(&record.evm_struct_hash()))
+    }
+
+    fn submit(&mut self, instructions: &[Instruction]) -> Result<(), InsuranceError> {
+        self.transport.submit(instructions).map_err(submit_error)
+    }
+}
+
+impl<T: ProgramTransport> InsuranceBackend for ProgramBackend<T> {
+    fn name(&self) -> &str {
+        "solana"
+    }
+
+    fn claim(&mut self, claim: &ClaimRequest) -> Result<ClaimStatus, InsuranceError> {
+        let refund_signature: [u8; 64] =
+            claim.refund_reference.as_slice().try_into().map_err(|_| {
+                InsuranceError::Rejected("refund reference is not a signature".into())
+            })?;
+        // Archived claims leave a tombstone, which doesn't decode
+        let existing = self
+            .transport
+            .account(&attestation_address(&claim.claim_id))
+            .map_err(InsuranceError::Unavailable)?;
+        if existing.is_some() {
+            return Err(InsuranceError::DuplicateClaim);
+        }
+
+        let now = self
+            .transport
+            .unix_timestamp()
+            .map_err(InsuranceError::Unavailable)?;
+        let args = AttestArgs {
+            claim_id: claim.claim_id,
+            proof_hash: claim.evidence.proof_hash,
+            hash_algo: HASH_ALGO_BLAKE3,
+            public_inputs: PublicInputs {
+                fraud_detected: claim.evidence.fraud_detected,
+                http_status: claim.evidence.http_status,
+                body_length: claim.evidence.body_length,
+                payout: MicroUsdc(claim.payout),
+            },
+            evidence_slot: claim.evidence.captured_at,
+            refund_signature,
+            merchant_id: claim.merchant_id,
+            related_claim_id: None,
+            secondary_proof: None,
+            signer_regions: false,
+            circuit_version: 0,
+            schema_id: DEFAULT_SCHEMA_ID,
+            billing_period: month_id(now),
+            day_index: None,
+            proof_job: false,
+            merchant_category: None,
+            claim_bloom: false,
+            claim_verdicts: false,
+            merchant_dashboard: false,
+            settlement_queue: false,
+            refund_claimant: None,
+            durable_nonce: false,
+        };
+        self.submit(&[attest_claim_proof(&self.attester, args)])?;
+        self.claim_status(&claim.claim_id)
+    }
+
+    /// Claims count as settled once a committed settlement batch holds
+    /// their leaf
+    fn claim_status(&mut self, claim_id: &ClaimId) -> Result<ClaimStatus, InsuranceError> {
+        let Some(record) = self.attestation(claim_id)? else {
+            return Ok(ClaimStatus::Unknown);
+        };
+
+        let (payout, paid_at) = (record.public_inputs[3], record.attested_at);
+        if self.is_settled(&record)? {
+            Ok(ClaimStatus::Settled { payout, paid_at })
+        } else {
+            Ok(ClaimStatus::Paid { payout, paid_at })
+        }
+    }
+
+    fn settle(&mut self, claim_ids: &[ClaimId]) -> Result<Settlement, InsuranceError> {
+        let admin = self
+            .admin
+            .ok_or(InsuranceError::Unsupported(Operation::Settle))?;
+
+        let mut records = Vec::with_capacity(claim_ids.len());
+        for claim_id in claim_ids {
+            let record = self
+                .attestation(claim_id)?
+                .ok_or(InsuranceError::UnknownClaim)?;
+            records.push((record.sequence, *claim_id));
+        }
+        records.sort();
+        let claim_ids: Vec<ClaimId> = records.into_iter().map(|(_, claim_id)| claim_id).collect();
+
+        let batch_index = self
+            .settlement_ledger()?
+            .map_or(0, |ledger| ledger.batch_count);
+        let queue = self
+            .transport
+            .account(&settlement_queue_address())
+            .map_err(InsuranceError::Unavailable)?
+            .is_some();
+        self.submit(&[commit_settlement_batch(
+            &admin,
+            batch_index,
+            &claim_ids,
+            queue,
+        )])?;
+
+        let batch = self.settlement_batch(batch_index)?;
+        Ok(Settlement {
+            batch: batch_index,
+            root: batch.root,
+            claim_ids,
+        })
+    }
+
+    fn dispute(&mut self, dispute: &Dispute) -> Result<(), InsuranceError> {
+        let source = self
+            .verdict_source
+            .ok_or(InsuranceError::Unsupported(Operation::Dispute))?;
+        self.submit(&[record_verdict(
+            &source,
+            dispute.claim_id,
+            dispute.fraud_detected,
+            dispute.evidence_hash,
+        )])
+    }
+}
+
+/// A program rejection where the message names one, otherwise unavailable
+fn submit_error(message: String) -> InsuranceError {
+    match AttestationError::from_transaction_error(&message) {
+        Some(AttestationError::Program(ErrorCode::AttestationExists)) => {
+            InsuranceError::DuplicateClaim
+        }
+        Some(error) if !error.remediation().retryable => {
+            InsuranceError::Rejected(error.to_string())
+        }
+        _ => InsuranceError::Unavailable(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use anchor_lang::{AccountSerialize, AnchorDeserialize, Discriminator};
+    use x402_attestation::instruction::{AttestClaimProof, CommitSettlementBatch, RecordVerdict};
+    use x402_insurance_interface::conformance::check_backend;
+    use x402_insurance_interface::Evidence;
+
+    use super::*;
+    use crate::fixtures::sample_attestation;
+
+    /// Just enough of the program for the lifecycle: attestations,
+    /// settlement batches and verdicts, with no config checks
+    #[derive(Default)]
+    struct FakeChain {
+        now: i64,
+        sequence: u64,
+        accounts: HashMap<Pubkey, Vec<u8>>,
+        leaves: Vec<Vec<[u8; 32]>>,
+    }
+
+    impl FakeChain {
+        fn store<T: AccountSerialize>(&mut self, address: Pubkey, account: &T) {
+            let mut data = Vec::new();
+            account.try_serialize(&mut data).unwrap();
+            self.accounts.insert(address, data);
+        }
+
+        fn apply(&mut self, instruction: &Instruction) -> Result<(), String> {
+            let (discriminator, mut args) = instruction.data.split_at(8);
+            if discriminator == AttestClaimProof::DISCRIMINATOR {
+                let args = AttestClaimProof::deserialize(&mut args).map_err(|e| e.to_string())?;
+                let address = attestation_address(&args.claim_id);
+                if self.accounts.contains_key(&address) {
+                    return Err(format!("account {address} already in use"));
+                }
+                let attester = instruction.accounts.iter().find(|meta| meta.is_signer);
+                let mut record = sample_attestation(0, attester.unwrap().pubkey, self.now);
+                record.claim_id = args.claim_id;
+                record.proof_hash = args.proof_hash;
+                record.public_inputs = args.public_inputs;
+                record.refund_tx_sig = args.refund_signature;
+                record.merchant_id = args.merchant_id;
+                record.sequence = self.sequence;
+                record.evm_digest = record.evm_struct_hash();
+                self.sequence += 1;
+                self.store(address, &record);
+            } else if discriminator == CommitSettlementBatch::DISCRIMINATOR {
+                let args =
+                    CommitSettlementBatch::deserialize(&mut args).map_err(|e| e.to_string())?;
+                let records: Vec<ProofAttestation> = instruction
+                    .accounts
+                    .iter()
+                    .filter_map(|meta| {
+                        let data = self.accounts.get(&meta.pubkey)?;
+                        decode_attestation(&meta.pubkey, data).ok()
+                    })
+                    .collect();
+                let address = settlement_ledger_address();
+                let mut ledger = match self.accounts.get(&address) {
+                    Some(data) => decode_settlement_ledger(&address, data).unwrap(),
+                    None => SettlementLedger {
+                        batch_count: 0,
+                        next_sequence: 0,
+                        bump: 0,
+                    },
+                };
+                let (Some(first), Some(last)) = (records.first(), records.last()) else {
+                    return Err("empty batch".into());
+                };
+                if args.batch_index != ledger.batch_count || first.sequence < ledger.next_sequence {
+                    return Err("custom program error: 0x1771".into());
+                }
+
+                let leaves: Vec<[u8; 32]> =
+                    records.iter().map(|record| record.evm_digest).collect();
+                let batch = SettlementBatch {
+                    batch_index: args.batch_index,
+                    first_sequence: ledger.next_sequence,
+                    last_sequence: last.sequence,
+                    leaf_count: leaves.len() as u32,
+                    root: settlement_root(&leaves),
+                    committed_at: self.now,
+                    bump: 0,
+                };
+                ledger.batch_count += 1;
+                ledger.next_sequence = last.sequence + 1;
+                self.store(settlement_batch_address(args.batch_index), &batch);
+                self.store(address, &ledger);
+                self.leaves.push(leaves);
+            } else if discriminator == RecordVerdict::DISCRIMINATOR {
+                let args = RecordVerdict::deserialize(&mut args).map_err(|e| e.to_string())?;
+                if !self
+                    .accounts
+                    .contains_key(&attestation_address(&args.claim_id))
+                {
+                    return Err("account not initialized".into());
+                }
+            } else {
+                return Err("unexpected instruction".into());
+            }
+            Ok(())
+        }
+    }
+
+    impl ProgramTransport for FakeChain {
+        fn submit(&mut self, instructions: &[Instruction]) -> Result<(), String> {
+            instructions
+                .iter()
+                .try_for_each(|instruction| self.apply(instruction))
+        }
+
+        fn account(&mut self, address: &Pubkey) -> Result<Option<Vec<u8>>, String> {
+            Ok(self.accounts.get(address).cloned())
+        }
+
+        fn unix_timestamp(&mut self) -> Result<i64, String> {
+            Ok(self.now)
+        }
+
+        fn batch_leaves(&mut self, batch_index: u64) -> Result<Vec<[u8; 32]>, String> {
+            self.leaves
+                .get(batch_index as usize)
+                .cloned()
+                .ok_or_else(|| format!("batch {batch_index} not committed"))
+        }
+    }
+
+    fn claim(index: u8) -> ClaimRequest {
+        ClaimRequest {
+            claim_id: [index; 32],
+            merchant_id: [2; 32],
+            payout: 10_000,
+            evidence: Evidence {
+                fraud_detected: true,
+                http_status: 503,
+                body_length: 0,
+                proof_hash: [3; 32],
+                captured_at: 0,
+            },
+            refund_reference: vec![4; 64],
+        }
+    }
+
+    #[test]
+    fn program_backend_conforms_and_settles_by_membership() {
+        let mut backend = ProgramBackend {
+            transport: FakeChain {
+                now: 1_760_000_000,
+                ..FakeChain::default()
+            },
+            attester: Pubkey::new_unique(),
+            admin: Some(Pubkey::new_unique()),
+            verdict_source: Some(Pubkey::new_unique()),
+        };
+        assert_eq!(check_backend(&mut backend, &claim(1)), Ok(()));
+
+        // A batch passing over a claim in its sequence range doesn't settle it
+        backend.claim(&claim(2)).unwrap();
+        backend.claim(&claim(3)).unwrap();
+        backend.settle(&[[3; 32]]).unwrap();
+        assert!(matches!(
+            backend.claim_status(&[2; 32]),
+            Ok(ClaimStatus::Paid { .. })
+        ));
+        assert!(matches!(
+            backend.claim_status(&[3; 32]),
+            Ok(ClaimStatus::Settled { .. })
+        ));
+
+        backend.transport.leaves[1] = vec![[0; 32]];
+        assert!(matches!(
+            backend.claim_status(&[2; 32]),
+            Err(InsuranceError::Unavailable(_))
+        ));
+
+        let mut backend = ProgramBackend {
+            admin: None,
+            verdict_source: None,
+            ..backend
+        };
+        assert_eq!(check_backend(&mut backend, &claim(4)), Ok(()));
+    }
+}
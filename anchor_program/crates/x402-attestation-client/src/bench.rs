@@ -0,0 +1,349 @@
+//! Compute-unit benchmarks and regression gate
+//!
+//! [`measure`] runs named scenarios through the caller's
+//! `simulateTransaction` (the same closure [`crate::preflight`] takes),
+//! against a test validator seeded with [`crate::fixtures`], and records
+//! the units the program consumed in each. The resulting [`CuBaseline`]
+//! is committed as JSON; CI measures again and fails when
+//! [`CuBaseline::regressions`] is non-empty.
+//!
+//! [`hot_scenarios`] covers the instructions that run per claim, and
+//! `bench/cu_baseline.json` in this crate is their committed baseline.
+//! `benches/cu.rs` measures them with `solana-program-test` against the
+//! program's SBF build, and fails on any scenario missing from the
+//! baseline, so new scenarios land with their numbers.
+//!
+//! Units come from the program's own `consumed N of M compute units` log
+//! line, so compute budget instructions in the simulated transaction
+//! don't count against it.
+
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::Space;
+use serde::{Deserialize, Serialize};
+
+use crate::amount::MicroUsdc;
+use crate::fixtures::{sample_attestation, Fixtures};
+use crate::instructions::{
+    attest_claim_proof, commit_settlement_batch, record_response_fingerprint, record_verdict,
+    start_proof_job, AttestArgs, PublicInputs,
+};
+use crate::preflight::{program_units_consumed, Simulation};
+use crate::{
+    month_id, verdict_source_address, ProofAttestation, VerdictSource, DEFAULT_SCHEMA_ID, ID,
+    VERDICT_KIND_ORACLE, VERDICT_SOURCE_SEED,
+};
+
+/// Attestations [`hot_scenarios`] seeds, all settled by its batch scenario
+pub const SEEDED_ATTESTATIONS: u32 = 4;
+
+/// One representative input: a name and the instructions to simulate
+#[derive(Debug, Clone)]
+pub struct Scenario {
+    /// Baseline key, e.g. `attest_claim_proof/closes_proof_job`
+    pub name: String,
+    /// Transaction to simulate; the last program instruction in it is the
+    /// one measured
+    pub instructions: Vec<Instruction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BenchError {
+    #[error("simulating {0} failed: {1}")]
+    Simulate(String, String),
+
+    #[error("scenario {0} failed: {1}")]
+    Failed(String, String),
+
+    #[error("scenario {0} reported no compute units")]
+    NoUnits(String),
+}
+
+/// Compute units per scenario; serializes as a flat JSON object
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CuBaseline(pub BTreeMap<String, u64>);
+
+/// A scenario that got more expensive than the baseline allows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CuRegression {
+    pub name: String,
+    pub baseline: u64,
+    pub current: u64,
+}
+
+impl CuBaseline {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("a string-keyed map always serializes")
+    }
+
+    pub fn from_json(json: &str) -> Result<CuBaseline, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Scenarios in `current` that use more than `max_increase_percent`
+    /// above their baseline
+    ///
+    /// Scenarios missing from the baseline are new, not regressions; add
+    /// them by committing the current measurement.
+    pub fn regressions(
+        &self,
+        current: &CuBaseline,
+        max_increase_percent: u64,
+    ) -> Vec<CuRegression> {
+        current
+            .0
+            .iter()
+            .filter_map(|(name, &units)| {
+                let baseline = *self.0.get(name)?;
+                let allowed = u128::from(baseline) * u128::from(100 + max_increase_percent);
+                (u128::from(units) * 100 > allowed).then(|| CuRegression {
+                    name: name.clone(),
+                    baseline,
+                    current: units,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Simulate every scenario and record the units the program consumed
+///
+/// A scenario that fails is an error rather than a measurement: a
+/// program that rejects its input early would otherwise look cheap.
+pub fn measure<F>(scenarios: &[Scenario], mut simulate: F) -> Result<CuBaseline, BenchError>
+where
+    F: FnMut(&[Instruction]) -> Result<Simulation, String>,
+{
+    let mut baseline = CuBaseline::default();
+    for scenario in scenarios {
+        let name = &scenario.name;
+        let simulation =
+            simulate(&scenario.instructions).map_err(|e| BenchError::Simulate(name.clone(), e))?;
+        if let Some(err) = simulation.err {
+            return Err(BenchError::Failed(name.clone(), err));
+        }
+        let units = program_units_consumed(&simulation.logs)
+            .or(simulation.units_consumed)
+            .ok_or_else(|| BenchError::NoUnits(name.clone()))?;
+        baseline.0.insert(name.clone(), units);
+    }
+    Ok(baseline)
+}
+
+/// Scenarios for the per-claim instructions, and the state they run against
+///
+/// Seed the validator with the fixtures' accounts and fund `admin`,
+/// `attester` and `source` (the verdict source the fixtures register).
+/// Each scenario is independent, so simulate them against the same
+/// seeded state. `now` is the validator's clock, which picks the billing
+/// period the attestations are filed under.
+pub fn hot_scenarios(
+    admin: &Pubkey,
+    attester: &Pubkey,
+    source: &Pubkey,
+    now: i64,
+) -> (Fixtures, Vec<Scenario>) {
+    let mut fixtures = Fixtures::new(*admin, 0);
+    let seeded: Vec<[u8; 32]> = (0..SEEDED_ATTESTATIONS)
+        .map(|index| {
+            let record = sample_attestation(index, *attester, now - 60);
+            fixtures.attest(record, 0).claim_id
+        })
+        .collect();
+    let registration = VerdictSource {
+        source: *source,
+        kind: VERDICT_KIND_ORACLE,
+        weight: 1,
+        registered_at: now - 60,
+        bump: Pubkey::find_program_address(&[VERDICT_SOURCE_SEED, source.as_ref()], &ID).1,
+    };
+    fixtures.push_account(
+        verdict_source_address(source),
+        &registration,
+        VerdictSource::INIT_SPACE,
+    );
+
+    let attest = |index: u32| attest_args(&sample_attestation(index, *attester, now), now);
+    let scenario = |name: &str, instructions: Vec<Instruction>| Scenario {
+        name: name.to_string(),
+        instructions,
+    };
+
+    let minimal = attest(100);
+    let mut with_job = attest(102);
+    with_job.proof_job = true;
+    let job = start_proof_job(attester, with_job.claim_id);
+    let new_job = start_proof_job(attester, attest(103).claim_id);
+
+    let scenarios = vec![
+        scenario(
+            "attest_claim_proof/minimal",
+            vec![attest_claim_proof(attester, minimal)],
+        ),
+        scenario(
+            "record_response_fingerprint",
+            vec![record_response_fingerprint(attester, seeded[1], [0xf1; 32])],
+        ),
+        scenario(
+            "attest_claim_proof/closes_proof_job",
+            vec![job, attest_claim_proof(attester, with_job)],
+        ),
+        scenario("start_proof_job", vec![new_job]),
+        scenario(
+            "record_verdict",
+            vec![record_verdict(source, seeded[0], true, [0xe1; 32])],
+        ),
+        scenario(
+            "commit_settlement_batch/4_claims",
+            vec![commit_settlement_batch(admin, 0, &seeded, false)],
+        ),
+    ];
+    (fixtures, scenarios)
+}
+
+/// Attest arguments for a sample record, with no optional accounts
+fn attest_args(record: &ProofAttestation, now: i64) -> AttestArgs {
+    let [fraud_detected, http_status, body_length, payout] = record.public_inputs;
+    AttestArgs {
+        claim_id: record.claim_id,
+        proof_hash: record.proof_hash,
+        hash_algo: record.hash_algo,
+        public_inputs: PublicInputs {
+            fraud_detected: fraud_detected != 0,
+            http_status: http_status as u16,
+            body_length,
+            payout: MicroUsdc(payout),
+        },
+        evidence_slot: record.evidence_slot,
+        refund_signature: record.refund_tx_sig,
+        merchant_id: record.merchant_id,
+        related_claim_id: None,
+        secondary_proof: None,
+        signer_regions: false,
+        circuit_version: record.circuit_version,
+        schema_id: DEFAULT_SCHEMA_ID,
+        billing_period: month_id(now),
+        day_index: None,
+        proof_job: false,
+        merchant_category: None,
+        claim_bloom: false,
+        claim_verdicts: false,
+        merchant_dashboard: false,
+        settlement_queue: false,
+        refund_claimant: None,
+        durable_nonce: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::sender::recommended_percentile;
+
+    /// The committed baseline of [`hot_scenarios`]
+    const CU_BASELINE: &str = include_str!("../bench/cu_baseline.json");
+
+    fn baseline(entries: &[(&str, u64)]) -> CuBaseline {
+        CuBaseline(
+            entries
+                .iter()
+                .map(|&(name, units)| (name.to_string(), units))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn regressions_respect_threshold_and_skip_new_scenarios() {
+        let committed = baseline(&[("attest", 20_000), ("heartbeat", 3_000)]);
+        let current = baseline(&[("attest", 22_000), ("heartbeat", 3_301), ("new", 50_000)]);
+
+        assert_eq!(
+            committed.regressions(&current, 10),
+            vec![CuRegression {
+                name: "heartbeat".to_string(),
+                baseline: 3_000,
+                current: 3_301,
+            }]
+        );
+        assert_eq!(
+            CuBaseline::from_json(&committed.to_json()).unwrap(),
+            committed
+        );
+    }
+
+    #[test]
+    fn measure_reads_program_units_and_rejects_failures() {
+        let scenario = |name: &str| Scenario {
+            name: name.to_string(),
+            instructions: Vec::new(),
+        };
+        let logs = vec![
+            format!("Program {ID} invoke [1]"),
+            format!("Program {ID} consumed 18123 of 199850 compute units"),
+        ];
+
+        let measured = measure(&[scenario("attest")], |_| {
+            Ok(Simulation {
+                err: None,
+                logs: logs.clone(),
+                units_consumed: Some(18_273),
+            })
+        })
+        .unwrap();
+        assert_eq!(measured, baseline(&[("attest", 18_123)]));
+
+        let failed = measure(&[scenario("attest")], |_| {
+            Ok(Simulation {
+                err: Some("custom program error: 0x1770".to_string()),
+                ..Simulation::default()
+            })
+        });
+        assert!(matches!(failed, Err(BenchError::Failed(..))));
+    }
+
+    #[test]
+    fn hot_scenarios_measure_per_claim_instructions() {
+        let (admin, attester, source) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (fixtures, scenarios) = hot_scenarios(&admin, &attester, &source, 1_760_000_000);
+        // Config, feed, stats, one billing period, the attestations, the source
+        assert_eq!(
+            fixtures.accounts().len(),
+            4 + SEEDED_ATTESTATIONS as usize + 1
+        );
+
+        let mut names = HashSet::new();
+        for scenario in &scenarios {
+            assert!(
+                names.insert(scenario.name.as_str()),
+                "{} is repeated",
+                scenario.name
+            );
+            let measured = scenario
+                .instructions
+                .iter()
+                .rev()
+                .find(|instruction| instruction.program_id == ID)
+                .unwrap();
+            assert!(
+                recommended_percentile(measured) >= 75,
+                "{} is not hot",
+                scenario.name
+            );
+        }
+
+        // Every committed measurement belongs to a current scenario
+        let committed = CuBaseline::from_json(CU_BASELINE).unwrap();
+        for name in committed.0.keys() {
+            assert!(names.contains(name.as_str()), "stale baseline entry {name}");
+        }
+    }
+}
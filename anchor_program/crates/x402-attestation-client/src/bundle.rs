@@ -0,0 +1,401 @@
+//! Claimant proof bundles
+//!
+//! A bundle is one JSON document with everything a claimant needs to show
+//! a claim was attested and refunded, without trusting whoever hands it
+//! over: the raw attestation account, its canonical digest, the refund
+//! transaction, the `ProofAttested` event as it appears in the program's
+//! logs and, once the claim is in a settlement batch, its inclusion path.
+//! The attester signs the JCS (RFC 8785) form of the document.
+//!
+//! [`verify_claim_bundle`] rebuilds every derived field from the account
+//! data, so it only has to trust the attester's key, and the settlement
+//! root if a checkpoint is present; compare that with the `SettlementBatch`
+//! account or the EVM notary.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::Event;
+use base64::Engine;
+use serde_json::{json, Value};
+
+use crate::credential::{canonical_json, hex};
+use crate::receipt::{verify_ed25519, Ed25519Signer};
+use crate::settlement::{settlement_proof, verify_settlement_proof};
+use crate::{
+    attestation_address, decode_attestation, settlement_batch_address, ProofAttestation,
+    SettlementBatch,
+};
+use x402_attestation::{ProofAttested, LEGACY_SEQUENCE};
+
+/// Domain tag for the signed bundle message
+pub const BUNDLE_DOMAIN: &[u8] = b"x402-claim-bundle:v1";
+
+/// Bundle format version
+pub const BUNDLE_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BundleError {
+    #[error("bundle is not valid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("bundle is missing or has an invalid {0}")]
+    InvalidField(&'static str),
+
+    #[error("attestation data does not decode: {0}")]
+    Undecodable(String),
+
+    #[error("claim is not in the settlement batch")]
+    NotInBatch,
+
+    #[error("bundle does not match its attestation data")]
+    RecordMismatch,
+
+    #[error("bundle signature is invalid")]
+    InvalidSignature,
+}
+
+/// Settlement batch holding the claim, with the batch's leaves in order
+///
+/// The leaves are the `leaves` of the batch's `SettlementBatchCommitted`
+/// event.
+#[derive(Clone, Copy)]
+pub struct Checkpoint<'a> {
+    pub batch: &'a SettlementBatch,
+    pub leaves: &'a [[u8; 32]],
+}
+
+/// A verified bundle's attestation, and the settlement root it is under
+#[derive(Clone)]
+pub struct VerifiedBundle {
+    pub attestation: ProofAttestation,
+    pub settlement_root: Option<[u8; 32]>,
+}
+
+/// Bundle the attestation of `claim_id`, signed by its attester
+///
+/// `data` is the raw attestation account data.
+pub fn export_claim_bundle(
+    claim_id: &[u8; 32],
+    data: &[u8],
+    checkpoint: Option<Checkpoint<'_>>,
+    signer: impl Ed25519Signer,
+) -> Result<Value, BundleError> {
+    let address = attestation_address(claim_id);
+    let record =
+        decode_attestation(&address, data).map_err(|e| BundleError::Undecodable(e.to_string()))?;
+    if record.claim_id != *claim_id {
+        return Err(BundleError::RecordMismatch);
+    }
+
+    let checkpoint = match checkpoint {
+        Some(Checkpoint { batch, leaves }) => {
+            let leaf = record.evm_struct_hash();
+            let index = leaves
+                .iter()
+                .position(|candidate| *candidate == leaf)
+                .ok_or(BundleError::NotInBatch)?;
+            let proof = settlement_proof(leaves, index).ok_or(BundleError::NotInBatch)?;
+            Some(checkpoint_json(batch, index, leaves.len(), &proof))
+        }
+        None => None,
+    };
+
+    let mut bundle = unsigned_bundle(&record, data, checkpoint);
+    let signature = signer.sign(&bundle_message(&bundle));
+    bundle["signature"] = bs58::encode(signature).into_string().into();
+    Ok(bundle)
+}
+
+/// Verify a bundle's signature and that every field matches its account data
+pub fn verify_claim_bundle(document: &str) -> Result<VerifiedBundle, BundleError> {
+    let mut bundle: Value =
+        serde_json::from_str(document).map_err(|e| BundleError::InvalidJson(e.to_string()))?;
+    let signature: [u8; 64] = bundle
+        .as_object_mut()
+        .ok_or(BundleError::InvalidField("document"))?
+        .remove("signature")
+        .as_ref()
+        .and_then(Value::as_str)
+        .and_then(|value| bs58::decode(value).into_vec().ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(BundleError::InvalidField("signature"))?;
+
+    let data = bundle["attestation"]["data"]
+        .as_str()
+        .and_then(|value| base64::engine::general_purpose::STANDARD.decode(value).ok())
+        .ok_or(BundleError::InvalidField("attestation.data"))?;
+    let address = bundle["attestation"]["address"]
+        .as_str()
+        .and_then(|value| value.parse::<Pubkey>().ok())
+        .ok_or(BundleError::InvalidField("attestation.address"))?;
+    let record =
+        decode_attestation(&address, &data).map_err(|e| BundleError::Undecodable(e.to_string()))?;
+
+    let checkpoint = match bundle.get("checkpoint") {
+        Some(checkpoint) => Some(verify_checkpoint(&record, checkpoint)?),
+        None => None,
+    };
+    let settlement_root = checkpoint.as_ref().map(|(root, _)| *root);
+    if bundle != unsigned_bundle(&record, &data, checkpoint.map(|(_, json)| json)) {
+        return Err(BundleError::RecordMismatch);
+    }
+
+    if verify_ed25519(&record.attester, &bundle_message(&bundle), &signature) {
+        Ok(VerifiedBundle {
+            attestation: record,
+            settlement_root,
+        })
+    } else {
+        Err(BundleError::InvalidSignature)
+    }
+}
+
+/// The bundle without its signature
+fn unsigned_bundle(record: &ProofAttestation, data: &[u8], checkpoint: Option<Value>) -> Value {
+    let address = attestation_address(&record.claim_id);
+    let mut bundle = json!({
+        "version": BUNDLE_VERSION,
+        "claimId": hex(&record.claim_id),
+        "attestation": {
+            "address": address.to_string(),
+            "data": base64::engine::general_purpose::STANDARD.encode(data),
+        },
+        "canonicalDigest": hex(&record.canonical_digest()),
+        "evmDigest": hex(&record.evm_digest),
+        "refundTransaction": bs58::encode(record.refund_tx_sig).into_string(),
+        "attester": record.attester.to_string(),
+    });
+    let fields = bundle.as_object_mut().expect("bundle is an object");
+    // Imported records were never attested live, so no event was emitted
+    if record.sequence != LEGACY_SEQUENCE {
+        let event = ProofAttested {
+            claim_id: record.claim_id,
+            proof_hash: record.proof_hash,
+            hash_algo: record.hash_algo,
+            payout_amount: record.public_inputs[3],
+            attested_at: record.attested_at,
+            sequence: record.sequence,
+            evm_digest: record.evm_digest,
+        };
+        fields.insert(
+            "events".into(),
+            json!({
+                "ProofAttested": base64::engine::general_purpose::STANDARD.encode(event.data()),
+            }),
+        );
+    }
+    if let Some(checkpoint) = checkpoint {
+        fields.insert("checkpoint".into(), checkpoint);
+    }
+    bundle
+}
+
+fn checkpoint_json(
+    batch: &SettlementBatch,
+    leaf_index: usize,
+    leaf_count: usize,
+    proof: &[[u8; 32]],
+) -> Value {
+    json!({
+        "batch": settlement_batch_address(batch.batch_index).to_string(),
+        "batchIndex": batch.batch_index.to_string(),
+        "firstSequence": batch.first_sequence.to_string(),
+        "lastSequence": batch.last_sequence.to_string(),
+        "root": hex(&batch.root),
+        "leafIndex": leaf_index,
+        "leafCount": leaf_count,
+        "proof": proof.iter().map(|node| hex(node)).collect::<Vec<_>>(),
+    })
+}
+
+/// The checkpoint's root, and the checkpoint as rebuilt from its path
+fn verify_checkpoint(
+    record: &ProofAttestation,
+    checkpoint: &Value,
+) -> Result<([u8; 32], Value), BundleError> {
+    let u64_field = |name: &'static str| {
+        checkpoint[name]
+            .as_str()
+            .and_then(|value| value.parse::<u64>().ok())
+            .ok_or(BundleError::InvalidField(name))
+    };
+    let usize_field = |name: &'static str| {
+        checkpoint[name]
+            .as_u64()
+            .and_then(|value| usize::try_from(value).ok())
+            .ok_or(BundleError::InvalidField(name))
+    };
+    let root = checkpoint["root"]
+        .as_str()
+        .and_then(unhex)
+        .ok_or(BundleError::InvalidField("root"))?;
+    let proof = checkpoint["proof"]
+        .as_array()
+        .and_then(|nodes| {
+            nodes
+                .iter()
+                .map(|node| node.as_str().and_then(unhex))
+                .collect::<Option<Vec<_>>>()
+        })
+        .ok_or(BundleError::InvalidField("proof"))?;
+
+    let batch = SettlementBatch {
+        batch_index: u64_field("batchIndex")?,
+        first_sequence: u64_field("firstSequence")?,
+        last_sequence: u64_field("lastSequence")?,
+        leaf_count: u32::try_from(usize_field("leafCount")?)
+            .map_err(|_| BundleError::InvalidField("leafCount"))?,
+        root,
+        committed_at: 0,
+        bump: 0,
+    };
+    let leaf_index = usize_field("leafIndex")?;
+    if !(batch.first_sequence..=batch.last_sequence).contains(&record.sequence)
+        || !verify_settlement_proof(
+            &record.evm_struct_hash(),
+            leaf_index,
+            batch.leaf_count as usize,
+            &proof,
+            &root,
+        )
+    {
+        return Err(BundleError::NotInBatch);
+    }
+
+    let json = checkpoint_json(&batch, leaf_index, batch.leaf_count as usize, &proof);
+    Ok((root, json))
+}
+
+fn bundle_message(bundle: &Value) -> [u8; 32] {
+    hashv(&[BUNDLE_DOMAIN, canonical_json(bundle).as_bytes()]).to_bytes()
+}
+
+fn unhex(value: &str) -> Option<[u8; 32]> {
+    if value.len() != 64 || !value.is_ascii() {
+        return None;
+    }
+    let mut out = [0u8; 32];
+    for (byte, pair) in out.iter_mut().zip(value.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Space;
+
+    use super::*;
+    use crate::fixtures::{sample_attestation, AccountFixture};
+    use crate::receipt::tests::keypair;
+    use crate::settlement::settlement_root;
+
+    fn attested(index: u32, attester: Pubkey) -> (ProofAttestation, Vec<u8>) {
+        let mut record = sample_attestation(index, attester, 1_760_000_000);
+        record.sequence = u64::from(index);
+        record.evm_digest = record.evm_struct_hash();
+        let address = attestation_address(&record.claim_id);
+        let data =
+            AccountFixture::program_account(address, &record, ProofAttestation::INIT_SPACE).data;
+        (record, data)
+    }
+
+    #[test]
+    fn exported_bundles_verify_with_and_without_a_checkpoint() {
+        let (attester, sign) = keypair([4; 32]);
+        let records: Vec<_> = (10..13).map(|index| attested(index, attester)).collect();
+        let leaves: Vec<[u8; 32]> = records.iter().map(|(r, _)| r.evm_struct_hash()).collect();
+        let batch = SettlementBatch {
+            batch_index: 4,
+            first_sequence: 10,
+            last_sequence: 12,
+            leaf_count: 3,
+            root: settlement_root(&leaves),
+            committed_at: 1_760_000_600,
+            bump: 0,
+        };
+        let (record, data) = &records[1];
+
+        let bare = export_claim_bundle(&record.claim_id, data, None, &sign).unwrap();
+        let verified = verify_claim_bundle(&bare.to_string()).unwrap();
+        assert_eq!(verified.attestation.claim_id, record.claim_id);
+        assert_eq!(verified.settlement_root, None);
+        assert!(bare.get("events").is_some());
+
+        let checkpoint = Checkpoint {
+            batch: &batch,
+            leaves: &leaves,
+        };
+        let settled = export_claim_bundle(&record.claim_id, data, Some(checkpoint), &sign).unwrap();
+        let verified = verify_claim_bundle(&settled.to_string()).unwrap();
+        assert_eq!(verified.settlement_root, Some(batch.root));
+        assert_eq!(settled["checkpoint"]["leafIndex"], 1);
+
+        let (outside, outside_data) = attested(13, attester);
+        assert_eq!(
+            export_claim_bundle(&outside.claim_id, &outside_data, Some(checkpoint), &sign)
+                .unwrap_err(),
+            BundleError::NotInBatch
+        );
+        assert_eq!(
+            export_claim_bundle(&outside.claim_id, data, None, &sign).unwrap_err(),
+            BundleError::RecordMismatch
+        );
+    }
+
+    #[test]
+    fn tampered_bundles_are_rejected() {
+        let (attester, sign) = keypair([4; 32]);
+        let (record, data) = attested(10, attester);
+        let leaves = [record.evm_struct_hash(), [7; 32]];
+        let batch = SettlementBatch {
+            batch_index: 0,
+            first_sequence: 10,
+            last_sequence: 11,
+            leaf_count: 2,
+            root: settlement_root(&leaves),
+            committed_at: 0,
+            bump: 0,
+        };
+        let checkpoint = Checkpoint {
+            batch: &batch,
+            leaves: &leaves,
+        };
+        let bundle = export_claim_bundle(&record.claim_id, &data, Some(checkpoint), &sign).unwrap();
+
+        let mut refund = bundle.clone();
+        refund["refundTransaction"] = bs58::encode([1; 64]).into_string().into();
+        assert_eq!(
+            verify_claim_bundle(&refund.to_string()).err(),
+            Some(BundleError::RecordMismatch)
+        );
+
+        let mut root = bundle.clone();
+        root["checkpoint"]["root"] = hex(&[0; 32]).into();
+        assert_eq!(
+            verify_claim_bundle(&root.to_string()).err(),
+            Some(BundleError::NotInBatch)
+        );
+
+        let mut range = bundle.clone();
+        range["checkpoint"]["firstSequence"] = "11".into();
+        assert_eq!(
+            verify_claim_bundle(&range.to_string()).err(),
+            Some(BundleError::NotInBatch)
+        );
+
+        let (_, other_sign) = keypair([5; 32]);
+        let forged = export_claim_bundle(&record.claim_id, &data, None, other_sign).unwrap();
+        assert_eq!(
+            verify_claim_bundle(&forged.to_string()).err(),
+            Some(BundleError::InvalidSignature)
+        );
+
+        let mut unsigned = bundle;
+        unsigned.as_object_mut().unwrap().remove("signature");
+        assert_eq!(
+            verify_claim_bundle(&unsigned.to_string()).err(),
+            Some(BundleError::InvalidField("signature"))
+        );
+    }
+}
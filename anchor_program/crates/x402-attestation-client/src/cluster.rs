@@ -0,0 +1,136 @@
+//! Cluster identity
+//!
+//! The program is deployed at the same ID on every cluster, so nothing in
+//! a signed transaction says which cluster it was meant for. The admin
+//! records the cluster's genesis hash once with `initialize_cluster_info`;
+//! prepending [`assert_cluster`] to a transaction then makes it fail as a
+//! whole anywhere else. Worth it for anything signed ahead of time, like
+//! durable-nonce transactions.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use crate::{cluster_info_address, config_address, ClusterInfo, ID};
+
+const MAINNET_BETA_GENESIS: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+const DEVNET_GENESIS: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+const TESTNET_GENESIS: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cluster {
+    MainnetBeta,
+    Devnet,
+    Testnet,
+    /// A local validator; its genesis hash changes with every ledger reset
+    Localnet {
+        genesis_hash: [u8; 32],
+    },
+}
+
+impl Cluster {
+    /// The public cluster with `genesis_hash`, or a localnet
+    pub fn from_genesis_hash(genesis_hash: [u8; 32]) -> Cluster {
+        [Cluster::MainnetBeta, Cluster::Devnet, Cluster::Testnet]
+            .into_iter()
+            .find(|cluster| cluster.genesis_hash() == genesis_hash)
+            .unwrap_or(Cluster::Localnet { genesis_hash })
+    }
+
+    /// As returned by the `getGenesisHash` RPC method
+    pub fn genesis_hash(&self) -> [u8; 32] {
+        let encoded = match self {
+            Cluster::MainnetBeta => MAINNET_BETA_GENESIS,
+            Cluster::Devnet => DEVNET_GENESIS,
+            Cluster::Testnet => TESTNET_GENESIS,
+            Cluster::Localnet { genesis_hash } => return *genesis_hash,
+        };
+        Hash::from_str(encoded)
+            .expect("known genesis hashes are valid")
+            .to_bytes()
+    }
+
+    /// Default public RPC endpoint
+    pub fn rpc_url(&self) -> &'static str {
+        match self {
+            Cluster::MainnetBeta => "https://api.mainnet-beta.solana.com",
+            Cluster::Devnet => "https://api.devnet.solana.com",
+            Cluster::Testnet => "https://api.testnet.solana.com",
+            Cluster::Localnet { .. } => "http://127.0.0.1:8899",
+        }
+    }
+}
+
+/// Whether the deployment's recorded cluster is `expected`
+pub fn check_cluster(info: &ClusterInfo, expected: &Cluster) -> bool {
+    info.genesis_hash == expected.genesis_hash()
+}
+
+/// `initialize_cluster_info`, recording `cluster` for this deployment
+pub fn initialize_cluster_info(admin: &Pubkey, cluster: &Cluster) -> Instruction {
+    let accounts = x402_attestation::accounts::InitializeClusterInfo {
+        config: config_address(),
+        cluster_info: cluster_info_address(),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::InitializeClusterInfo {
+            genesis_hash: cluster.genesis_hash(),
+        }
+        .data(),
+    }
+}
+
+/// `assert_cluster` for `cluster`; prepend it to pin a transaction there
+pub fn assert_cluster(cluster: &Cluster) -> Instruction {
+    let accounts = x402_attestation::accounts::AssertCluster {
+        cluster_info: cluster_info_address(),
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::AssertCluster {
+            genesis_hash: cluster.genesis_hash(),
+        }
+        .data(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn genesis_hashes_round_trip_to_clusters() {
+        for cluster in [Cluster::MainnetBeta, Cluster::Devnet, Cluster::Testnet] {
+            assert_eq!(Cluster::from_genesis_hash(cluster.genesis_hash()), cluster);
+        }
+        assert_eq!(
+            Cluster::from_genesis_hash([7; 32]),
+            Cluster::Localnet {
+                genesis_hash: [7; 32]
+            }
+        );
+        assert_ne!(
+            Cluster::MainnetBeta.genesis_hash(),
+            Cluster::Devnet.genesis_hash()
+        );
+
+        let info = ClusterInfo {
+            genesis_hash: Cluster::Devnet.genesis_hash(),
+            recorded_at: 0,
+            bump: 0,
+        };
+        assert!(check_cluster(&info, &Cluster::Devnet));
+        assert!(!check_cluster(&info, &Cluster::MainnetBeta));
+    }
+}
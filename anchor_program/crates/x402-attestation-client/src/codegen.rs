@@ -0,0 +1,559 @@
+//! Typed bindings generated from a program's IDL
+//!
+//! For integrating against a fork or devnet deployment without this source
+//! tree: fetch the deployment's on-chain IDL account, inflate it, and
+//! generate Rust account types and instruction builders from it, typically
+//! in a build script:
+//!
+//! ```text
+//! // build.rs
+//! let idl = std::fs::read_to_string("idl.json")?;
+//! let bindings = x402_attestation_client::codegen::generate(&idl)?;
+//! std::fs::write(Path::new(&env::var("OUT_DIR")?).join("x402.rs"), bindings)?;
+//!
+//! // lib.rs
+//! pub mod x402 { include!(concat!(env!("OUT_DIR"), "/x402.rs")); }
+//! ```
+//!
+//! [`idl_address`] and [`decode_idl_account`] read the IDL account. Its
+//! payload is zlib-compressed JSON; inflate it with any zlib
+//! implementation (e.g., `flate2::read::ZlibDecoder`), or fetch it already
+//! inflated with `anchor idl fetch`. The generated code depends only on
+//! `anchor-lang`.
+//!
+//! Understands the IDL format of Anchor 0.29, the version this program is
+//! built with.
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+use anchor_lang::idl::IdlAccount;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::AccountDeserialize;
+use serde::Deserialize;
+
+use crate::ClientError;
+
+/// Address of the on-chain IDL account of `program_id`
+pub fn idl_address(program_id: &Pubkey) -> Pubkey {
+    IdlAccount::address(program_id)
+}
+
+/// IDL account contents: update authority and compressed IDL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdlAccountData {
+    pub authority: Pubkey,
+    /// zlib-compressed IDL JSON
+    pub compressed: Vec<u8>,
+}
+
+/// Decode raw IDL account data (including the discriminator)
+pub fn decode_idl_account(address: &Pubkey, data: &[u8]) -> Result<IdlAccountData, ClientError> {
+    let decode_error = |e| ClientError::Decode(*address, e);
+    let mut header = data;
+    let account = IdlAccount::try_deserialize(&mut header).map_err(decode_error)?;
+    // discriminator, authority, data_len
+    let start = 8 + 32 + 4;
+    let compressed = data
+        .get(start..start + account.data_len as usize)
+        .ok_or_else(|| {
+            decode_error(anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+        })?;
+
+    Ok(IdlAccountData {
+        authority: account.authority,
+        compressed: compressed.to_vec(),
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CodegenError {
+    #[error("invalid IDL: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("unsupported IDL type {0}")]
+    UnsupportedType(String),
+
+    #[error("invalid program address {0:?} in IDL metadata")]
+    InvalidAddress(String),
+}
+
+#[derive(Deserialize)]
+struct Idl {
+    name: String,
+    version: String,
+    #[serde(default)]
+    instructions: Vec<IdlInstruction>,
+    #[serde(default)]
+    accounts: Vec<IdlTypeDef>,
+    #[serde(default)]
+    types: Vec<IdlTypeDef>,
+    #[serde(default)]
+    errors: Vec<IdlError>,
+    metadata: Option<IdlMetadata>,
+}
+
+#[derive(Deserialize)]
+struct IdlMetadata {
+    address: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdlInstruction {
+    name: String,
+    #[serde(default)]
+    docs: Vec<String>,
+    accounts: Vec<IdlAccountItem>,
+    args: Vec<IdlField>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IdlAccountItem {
+    Account(IdlInstructionAccount),
+    Composite {
+        name: String,
+        accounts: Vec<IdlAccountItem>,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdlInstructionAccount {
+    name: String,
+    is_mut: bool,
+    is_signer: bool,
+    #[serde(default)]
+    is_optional: bool,
+}
+
+#[derive(Deserialize)]
+struct IdlField {
+    name: String,
+    #[serde(rename = "type")]
+    ty: IdlType,
+}
+
+#[derive(Deserialize)]
+struct IdlTypeDef {
+    name: String,
+    #[serde(default)]
+    docs: Vec<String>,
+    #[serde(rename = "type")]
+    ty: IdlTypeDefBody,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum IdlTypeDefBody {
+    Struct { fields: Vec<IdlField> },
+    Enum { variants: Vec<IdlVariant> },
+}
+
+#[derive(Deserialize)]
+struct IdlVariant {
+    name: String,
+    fields: Option<IdlVariantFields>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IdlVariantFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlType>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IdlType {
+    Primitive(String),
+    Vec { vec: Box<IdlType> },
+    Option { option: Box<IdlType> },
+    Defined { defined: String },
+    Array { array: (Box<IdlType>, usize) },
+}
+
+#[derive(Deserialize)]
+struct IdlError {
+    code: u32,
+    name: String,
+    msg: Option<String>,
+}
+
+/// Generate Rust bindings from IDL JSON
+///
+/// The output holds a `types` module with every type and account in the IDL
+/// (accounts with their `DISCRIMINATOR` and a `try_decode`), an `accounts`
+/// module with one address struct per instruction, an `instructions` module
+/// of builders, and an `errors` table; plus `ID` if the IDL records the
+/// program address.
+pub fn generate(idl_json: &str) -> Result<String, CodegenError> {
+    let idl: Idl = serde_json::from_str(idl_json)?;
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "// Generated from the {} {} IDL; do not edit.",
+        idl.name, idl.version
+    )
+    .unwrap();
+    out.push('\n');
+    out.push_str(
+        "use anchor_lang::prelude::{borsh, AnchorDeserialize, AnchorSerialize, Pubkey};\n",
+    );
+    out.push_str("use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};\n\n");
+
+    if let Some(address) = idl
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.address.as_ref())
+    {
+        let id: Pubkey = address
+            .parse()
+            .map_err(|_| CodegenError::InvalidAddress(address.clone()))?;
+        writeln!(
+            out,
+            "pub const ID: Pubkey = Pubkey::new_from_array({:?});\n",
+            id.to_bytes()
+        )
+        .unwrap();
+    }
+
+    out.push_str("pub mod types {\n    use super::*;\n");
+    let account_names: HashSet<&str> = idl.accounts.iter().map(|def| def.name.as_str()).collect();
+    for def in idl.types.iter().chain(&idl.accounts) {
+        out.push('\n');
+        type_def(&mut out, def)?;
+        if account_names.contains(def.name.as_str()) {
+            account_impl(&mut out, &def.name);
+        }
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("pub mod accounts {\n    use super::*;\n");
+    for instruction in &idl.instructions {
+        out.push('\n');
+        accounts_struct(&mut out, instruction);
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("pub mod instructions {\n    use super::*;\n");
+    for instruction in &idl.instructions {
+        out.push('\n');
+        instruction_builder(&mut out, instruction)?;
+    }
+    out.push_str("}\n\n");
+
+    out.push_str("/// `(code, name, message)` of each program error\n");
+    out.push_str("pub const ERRORS: &[(u32, &str, &str)] = &[\n");
+    for error in &idl.errors {
+        writeln!(
+            out,
+            "    ({}, {:?}, {:?}),",
+            error.code,
+            error.name,
+            error.msg.as_deref().unwrap_or("")
+        )
+        .unwrap();
+    }
+    out.push_str("];\n");
+
+    Ok(out)
+}
+
+fn type_def(out: &mut String, def: &IdlTypeDef) -> Result<(), CodegenError> {
+    docs(out, &def.docs, "    ");
+    out.push_str("    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]\n");
+    match &def.ty {
+        IdlTypeDefBody::Struct { fields } => {
+            writeln!(out, "    pub struct {} {{", def.name).unwrap();
+            named_fields(out, fields, "        pub ")?;
+            out.push_str("    }\n");
+        }
+        IdlTypeDefBody::Enum { variants } => {
+            writeln!(out, "    pub enum {} {{", def.name).unwrap();
+            for variant in variants {
+                match &variant.fields {
+                    None => writeln!(out, "        {},", variant.name).unwrap(),
+                    Some(IdlVariantFields::Named(fields)) => {
+                        writeln!(out, "        {} {{", variant.name).unwrap();
+                        named_fields(out, fields, "            ")?;
+                        out.push_str("        },\n");
+                    }
+                    Some(IdlVariantFields::Tuple(types)) => {
+                        let types = types.iter().map(rust_type).collect::<Result<Vec<_>, _>>()?;
+                        writeln!(out, "        {}({}),", variant.name, types.join(", ")).unwrap();
+                    }
+                }
+            }
+            out.push_str("    }\n");
+        }
+    }
+    Ok(())
+}
+
+fn account_impl(out: &mut String, name: &str) {
+    writeln!(
+        out,
+        "\n    impl {name} {{\n        pub const DISCRIMINATOR: [u8; 8] = {:?};\n\n        \
+         /// Decode raw account data (including the discriminator)\n        \
+         pub fn try_decode(data: &[u8]) -> Option<{name}> {{\n            \
+         let body = data.strip_prefix(&Self::DISCRIMINATOR[..])?;\n            \
+         AnchorDeserialize::deserialize(&mut &body[..]).ok()\n        }}\n    }}",
+        sighash("account", name)
+    )
+    .unwrap();
+}
+
+fn accounts_struct(out: &mut String, instruction: &IdlInstruction) {
+    let mut accounts = Vec::new();
+    flatten_accounts(&instruction.accounts, "", &mut accounts);
+    let name = pascal_case(&instruction.name);
+
+    writeln!(
+        out,
+        "    /// Accounts of `{}`",
+        snake_case(&instruction.name)
+    )
+    .unwrap();
+    out.push_str("    #[derive(Clone, Debug)]\n");
+    writeln!(out, "    pub struct {name} {{").unwrap();
+    for (field, account) in &accounts {
+        let ty = if account.is_optional {
+            "Option<Pubkey>"
+        } else {
+            "Pubkey"
+        };
+        writeln!(out, "        pub {}: {ty},", ident(field)).unwrap();
+    }
+    out.push_str("    }\n\n");
+
+    writeln!(out, "    impl {name} {{").unwrap();
+    out.push_str("        /// Omitted optional accounts are passed as the program ID\n");
+    let optional = accounts.iter().any(|(_, account)| account.is_optional);
+    writeln!(
+        out,
+        "        pub fn to_account_metas(&self, {}program_id: &Pubkey) -> Vec<AccountMeta> {{",
+        if optional { "" } else { "_" }
+    )
+    .unwrap();
+    out.push_str("            vec![\n");
+    for (field, account) in &accounts {
+        let constructor = if account.is_mut {
+            "new"
+        } else {
+            "new_readonly"
+        };
+        let key = if account.is_optional {
+            format!("self.{}.unwrap_or(*program_id)", ident(field))
+        } else {
+            format!("self.{}", ident(field))
+        };
+        let signer = if account.is_optional && account.is_signer {
+            format!("self.{}.is_some()", ident(field))
+        } else {
+            account.is_signer.to_string()
+        };
+        writeln!(
+            out,
+            "                AccountMeta::{constructor}({key}, {signer}),"
+        )
+        .unwrap();
+    }
+    out.push_str("            ]\n        }\n    }\n");
+}
+
+fn flatten_accounts<'a>(
+    items: &'a [IdlAccountItem],
+    prefix: &str,
+    out: &mut Vec<(String, &'a IdlInstructionAccount)>,
+) {
+    for item in items {
+        match item {
+            IdlAccountItem::Account(account) => {
+                out.push((format!("{prefix}{}", snake_case(&account.name)), account));
+            }
+            IdlAccountItem::Composite { name, accounts } => {
+                flatten_accounts(accounts, &format!("{prefix}{}_", snake_case(name)), out);
+            }
+        }
+    }
+}
+
+fn instruction_builder(out: &mut String, instruction: &IdlInstruction) -> Result<(), CodegenError> {
+    let name = snake_case(&instruction.name);
+    let accounts = pascal_case(&instruction.name);
+
+    out.push_str("    #[derive(AnchorSerialize)]\n");
+    writeln!(out, "    struct {accounts}Args {{").unwrap();
+    named_fields(out, &instruction.args, "        ")?;
+    out.push_str("    }\n\n");
+
+    docs(out, &instruction.docs, "    ");
+    out.push_str("    #[allow(clippy::too_many_arguments)]\n");
+    writeln!(out, "    pub fn {}(", ident(&name)).unwrap();
+    out.push_str("        program_id: &Pubkey,\n");
+    writeln!(out, "        accounts: &super::accounts::{accounts},").unwrap();
+    named_fields(out, &instruction.args, "        ")?;
+    out.push_str("    ) -> Instruction {\n");
+    writeln!(
+        out,
+        "        let mut data = {:?}.to_vec();",
+        sighash("global", &name)
+    )
+    .unwrap();
+    writeln!(out, "        {accounts}Args {{").unwrap();
+    for arg in &instruction.args {
+        writeln!(out, "            {},", ident(&snake_case(&arg.name))).unwrap();
+    }
+    out.push_str("        }\n");
+    out.push_str("        .serialize(&mut data)\n");
+    out.push_str("        .expect(\"serializing into a Vec cannot fail\");\n\n");
+    out.push_str("        Instruction {\n");
+    out.push_str("            program_id: *program_id,\n");
+    out.push_str("            accounts: accounts.to_account_metas(program_id),\n");
+    out.push_str("            data,\n");
+    out.push_str("        }\n    }\n");
+    Ok(())
+}
+
+/// One `name: Type,` line per field; `prefix` sets indentation and visibility
+fn named_fields(out: &mut String, fields: &[IdlField], prefix: &str) -> Result<(), CodegenError> {
+    for field in fields {
+        writeln!(
+            out,
+            "{prefix}{}: {},",
+            ident(&snake_case(&field.name)),
+            rust_type(&field.ty)?
+        )
+        .unwrap();
+    }
+    Ok(())
+}
+
+fn rust_type(ty: &IdlType) -> Result<String, CodegenError> {
+    Ok(match ty {
+        IdlType::Primitive(name) => match name.as_str() {
+            "bool" | "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "u128"
+            | "i128" | "f32" | "f64" => name.clone(),
+            "string" => "String".to_string(),
+            "bytes" => "Vec<u8>".to_string(),
+            "publicKey" => "Pubkey".to_string(),
+            other => return Err(CodegenError::UnsupportedType(other.to_string())),
+        },
+        IdlType::Vec { vec } => format!("Vec<{}>", rust_type(vec)?),
+        IdlType::Option { option } => format!("Option<{}>", rust_type(option)?),
+        IdlType::Defined { defined } => format!("super::types::{defined}"),
+        IdlType::Array { array: (ty, len) } => format!("[{}; {len}]", rust_type(ty)?),
+    })
+}
+
+fn docs(out: &mut String, docs: &[String], indent: &str) {
+    for line in docs {
+        if line.is_empty() {
+            writeln!(out, "{indent}///").unwrap();
+        } else {
+            writeln!(out, "{indent}/// {line}").unwrap();
+        }
+    }
+}
+
+/// First 8 bytes of `sha256("<namespace>:<name>")`, as Anchor derives them
+fn sighash(namespace: &str, name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("{namespace}:{name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// `attestClaimProof` → `attest_claim_proof`, undoing the IDL's camelCase
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if !out.is_empty() {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn pascal_case(name: &str) -> String {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "break", "const", "continue", "crate", "else", "enum", "extern", "fn", "for", "if",
+        "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+        "static", "struct", "trait", "type", "unsafe", "use", "where", "while", "async", "await",
+        "dyn", "box", "final", "override", "priv", "yield",
+    ];
+    if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Discriminator;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{ProofAttestation, ID};
+
+    /// This program's IDL, from `anchor idl parse -f programs/x402_attestation/src/lib.rs`
+    const PROGRAM_IDL: &str = include_str!("../idl/x402_attestation.json");
+
+    #[test]
+    fn generates_bindings_for_the_program_idl() {
+        let bindings = generate(PROGRAM_IDL).unwrap();
+        assert!(bindings.starts_with("// Generated from the x402_attestation 0.1.0 IDL"));
+        assert!(!bindings.contains("pub const ID"));
+
+        // Account types with their Anchor discriminators
+        assert!(bindings.contains("    pub struct ProofAttestation {\n"));
+        assert!(bindings.contains("        pub claim_id: [u8; 32],\n"));
+        assert!(bindings.contains("        pub proof_hash_secondary: Option<[u8; 32]>,\n"));
+        assert!(bindings.contains(&format!(
+            "pub const DISCRIMINATOR: [u8; 8] = {:?};",
+            ProofAttestation::DISCRIMINATOR
+        )));
+        assert!(bindings.contains("    pub struct Config {\n"));
+
+        // Account structs and builders with the instructions' discriminators
+        assert!(bindings.contains("    pub struct AttestClaimProof {\n"));
+        assert!(bindings.contains("    pub fn attest_claim_proof(\n"));
+        assert!(bindings.contains(&format!(
+            "let mut data = {:?}.to_vec();",
+            x402_attestation::instruction::AttestClaimProof::DISCRIMINATOR
+        )));
+        assert!(bindings.contains("    pub fn migrate_config(\n"));
+        assert!(bindings.contains("    (6000, \"InvalidProofHash\", \"Invalid proof hash\"),\n"));
+
+        let mut idl: Value = serde_json::from_str(PROGRAM_IDL).unwrap();
+        idl["metadata"] = serde_json::json!({ "address": ID.to_string() });
+        let bindings = generate(&idl.to_string()).unwrap();
+        assert!(bindings.contains(&format!(
+            "pub const ID: Pubkey = Pubkey::new_from_array({:?});",
+            ID.to_bytes()
+        )));
+
+        idl["metadata"]["address"] = "not-a-key".into();
+        assert!(matches!(
+            generate(&idl.to_string()),
+            Err(CodegenError::InvalidAddress(_))
+        ));
+    }
+}
@@ -0,0 +1,367 @@
+//! Attestations as W3C Verifiable Credentials
+//!
+//! Exports an attestation as a VC Data Model 2.0 JSON-LD document, issued
+//! by the attester's key as a `did:key`, so claimants can present refund
+//! proofs to systems that understand credentials but not Solana.
+//!
+//! The proof is a `DataIntegrityProof` with the `eddsa-jcs-2022`
+//! cryptosuite: an Ed25519 signature over the JCS (RFC 8785) canonical
+//! form of the document. It is the same key and signature scheme as
+//! `Ed25519Signature2020`, but canonicalizes the JSON directly instead of
+//! through RDF, so verifying needs no JSON-LD processor.
+//!
+//! Verifiers that only accept `Ed25519Signature2020` proofs reject these
+//! credentials: that suite signs the RDF Dataset Canonicalization of the
+//! document, which this crate has no processor for, so the two proofs
+//! can't be swapped. Such consumers need `eddsa-jcs-2022` support (W3C
+//! Data Integrity EdDSA Cryptosuites v1.0), or a converter that checks
+//! this proof and re-issues the credential under its own 2020 key.
+//! [`verify_credential`] likewise refuses 2020 proofs as
+//! [`CredentialError::UnsupportedProof`].
+
+use anchor_lang::prelude::Pubkey;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::amount::MicroUsdc;
+use crate::receipt::{verify_ed25519, Ed25519Signer};
+use crate::{attestation_address, ProofAttestation};
+
+/// VC Data Model 2.0 base context
+pub const CREDENTIALS_V2_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+
+/// Vocabulary for the credential subject's terms
+pub const X402_VOCAB: &str = "urn:x402:vocab#";
+
+/// Credential type after `VerifiableCredential`
+pub const CREDENTIAL_TYPE: &str = "X402RefundAttestation";
+
+/// Multicodec prefix of an Ed25519 public key in a `did:key`
+const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CredentialError {
+    #[error("credential is not valid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("credential is missing or has an invalid {0}")]
+    InvalidField(&'static str),
+
+    #[error("credential proof is not an eddsa-jcs-2022 DataIntegrityProof")]
+    UnsupportedProof,
+
+    #[error("credential signature is invalid")]
+    InvalidSignature,
+
+    #[error("credential does not match the attestation record")]
+    RecordMismatch,
+}
+
+/// `did:key` of an Ed25519 public key
+pub fn did_key(key: &Pubkey) -> String {
+    let mut bytes = ED25519_MULTICODEC.to_vec();
+    bytes.extend_from_slice(key.as_ref());
+    format!("did:key:z{}", bs58::encode(bytes).into_string())
+}
+
+/// Ed25519 public key of a `did:key`, `None` for any other DID
+pub fn did_key_pubkey(did: &str) -> Option<Pubkey> {
+    let bytes = bs58::decode(did.strip_prefix("did:key:z")?)
+        .into_vec()
+        .ok()?;
+    let key = bytes.strip_prefix(&ED25519_MULTICODEC[..])?;
+    Some(Pubkey::new_from_array(key.try_into().ok()?))
+}
+
+/// The credential without its proof
+pub fn unsigned_credential(attestation: &ProofAttestation) -> Value {
+    let address = attestation_address(&attestation.claim_id);
+    let [fraud_detected, http_status, body_length, payout] = attestation.public_inputs;
+
+    let mut subject = json!({
+        "id": format!("urn:x402:claim:{}", hex(&attestation.claim_id)),
+        "attestation": address.to_string(),
+        "merchantId": hex(&attestation.merchant_id),
+        "fraudDetected": fraud_detected != 0,
+        "httpStatus": http_status,
+        "bodyLength": body_length.to_string(),
+        "payoutMicroUsdc": payout.to_string(),
+        "payoutUsdc": MicroUsdc(payout).to_usdc().to_string(),
+        "refundTransaction": bs58::encode(attestation.refund_tx_sig).into_string(),
+        "proofHash": hex(&attestation.proof_hash),
+        "hashAlgo": attestation.hash_algo,
+        "circuitVersion": attestation.circuit_version,
+        "sequence": attestation.sequence.to_string(),
+        "canonicalDigest": hex(&attestation.canonical_digest()),
+    });
+    let fields = subject.as_object_mut().expect("subject is an object");
+    if let Some(related) = &attestation.related_claim_id {
+        fields.insert("relatedClaimId".into(), hex(related).into());
+    }
+    if let Some(secondary) = &attestation.proof_hash_secondary {
+        fields.insert("proofHashSecondary".into(), hex(secondary).into());
+    }
+    if attestation.imported {
+        fields.insert("imported".into(), true.into());
+    }
+
+    json!({
+        "@context": [CREDENTIALS_V2_CONTEXT, { "@vocab": X402_VOCAB }],
+        "id": format!("urn:x402:attestation:{address}"),
+        "type": ["VerifiableCredential", CREDENTIAL_TYPE],
+        "issuer": did_key(&attestation.attester),
+        "validFrom": rfc3339(attestation.attested_at),
+        "credentialSubject": subject,
+    })
+}
+
+/// Issue the credential, signed by the attester at unix time `created`
+pub fn issue_credential(
+    attestation: &ProofAttestation,
+    created: i64,
+    signer: impl Ed25519Signer,
+) -> Value {
+    let mut credential = unsigned_credential(attestation);
+    let issuer = did_key(&attestation.attester);
+    let mut proof = json!({
+        "type": "DataIntegrityProof",
+        "cryptosuite": "eddsa-jcs-2022",
+        "created": rfc3339(created),
+        "verificationMethod": format!("{issuer}#{}", issuer.trim_start_matches("did:key:")),
+        "proofPurpose": "assertionMethod",
+    });
+
+    let signature = signer.sign(&signing_input(&credential, &proof));
+    proof["proofValue"] = format!("z{}", bs58::encode(signature).into_string()).into();
+    credential["proof"] = proof;
+    credential
+}
+
+/// Verify a credential's proof, and its binding to `record` if given
+///
+/// Without a record this proves the issuer signed the document; with one
+/// it also proves the document says exactly what the record does.
+pub fn verify_credential(
+    document: &str,
+    record: Option<&ProofAttestation>,
+) -> Result<(), CredentialError> {
+    let mut credential: Value =
+        serde_json::from_str(document).map_err(|e| CredentialError::InvalidJson(e.to_string()))?;
+    let credential_fields = credential
+        .as_object_mut()
+        .ok_or(CredentialError::InvalidField("document"))?;
+    let mut proof = credential_fields
+        .remove("proof")
+        .ok_or(CredentialError::InvalidField("proof"))?;
+    let proof_fields = proof
+        .as_object_mut()
+        .ok_or(CredentialError::InvalidField("proof"))?;
+
+    if proof_fields.get("type") != Some(&Value::from("DataIntegrityProof"))
+        || proof_fields.get("cryptosuite") != Some(&Value::from("eddsa-jcs-2022"))
+    {
+        return Err(CredentialError::UnsupportedProof);
+    }
+    let signature: [u8; 64] = proof_fields
+        .remove("proofValue")
+        .as_ref()
+        .and_then(Value::as_str)
+        .and_then(|value| value.strip_prefix('z'))
+        .and_then(|value| bs58::decode(value).into_vec().ok())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(CredentialError::InvalidField("proofValue"))?;
+
+    let issuer = credential
+        .get("issuer")
+        .and_then(Value::as_str)
+        .ok_or(CredentialError::InvalidField("issuer"))?;
+    let key = did_key_pubkey(issuer).ok_or(CredentialError::InvalidField("issuer"))?;
+    let method = proof
+        .get("verificationMethod")
+        .and_then(Value::as_str)
+        .ok_or(CredentialError::InvalidField("verificationMethod"))?;
+    if method.split('#').next() != Some(issuer) {
+        return Err(CredentialError::InvalidField("verificationMethod"));
+    }
+
+    if let Some(record) = record {
+        if credential != unsigned_credential(record) {
+            return Err(CredentialError::RecordMismatch);
+        }
+    }
+
+    if verify_ed25519(&key, &signing_input(&credential, &proof), &signature) {
+        Ok(())
+    } else {
+        Err(CredentialError::InvalidSignature)
+    }
+}
+
+/// `SHA-256(JCS(proof config)) ‖ SHA-256(JCS(document))`, per eddsa-jcs-2022
+///
+/// The proof config is the proof without `proofValue`, carrying the
+/// document's `@context`.
+fn signing_input(credential: &Value, proof: &Value) -> [u8; 64] {
+    let mut config = proof.clone();
+    if let Some(context) = credential.get("@context") {
+        config["@context"] = context.clone();
+    }
+
+    let mut input = [0u8; 64];
+    input[..32].copy_from_slice(&Sha256::digest(canonical_json(&config)));
+    input[32..].copy_from_slice(&Sha256::digest(canonical_json(credential)));
+    input
+}
+
+/// RFC 8785 canonical JSON: sorted keys, no whitespace
+///
+/// Numbers are written as serde_json does, which matches RFC 8785 for the
+/// integers below 2^53 these documents hold; larger amounts are strings.
+pub(crate) fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(fields) => {
+            // RFC 8785 orders keys by UTF-16 code units
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::from(key.as_str()).to_string());
+                out.push(':');
+                write_canonical(&fields[key], out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// `YYYY-MM-DDTHH:MM:SSZ` of a unix timestamp
+pub(crate) fn rfc3339(unix_timestamp: i64) -> String {
+    let seconds = unix_timestamp.rem_euclid(86_400);
+    // Days-to-civil conversion (Howard Hinnant's algorithm), as `month_id`
+    let z = unix_timestamp.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        seconds / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample_attestation;
+    use crate::receipt::tests::keypair;
+
+    #[test]
+    fn canonical_json_follows_rfc_8785() {
+        let value = json!({
+            "b": [1, { "d": true, "c": null }],
+            "a": "quote \" and \u{e9}",
+            "\u{fb33}": 1,
+            "\u{1f600}": 2,
+        });
+        // UTF-16 order puts the surrogate pair of U+1F600 before U+FB33
+        assert_eq!(
+            canonical_json(&value),
+            "{\"a\":\"quote \\\" and \u{e9}\",\"b\":[1,{\"c\":null,\"d\":true}],\
+             \"\u{1f600}\":2,\"\u{fb33}\":1}"
+        );
+        assert_eq!(rfc3339(1_760_000_000), "2025-10-09T08:53:20Z");
+        assert_eq!(rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn did_key_round_trips_ed25519_keys_only() {
+        let key = Pubkey::new_unique();
+        let did = did_key(&key);
+        assert!(did.starts_with("did:key:z6Mk"));
+        assert_eq!(did_key_pubkey(&did), Some(key));
+
+        // A secp256k1 key (multicodec 0xe7) and a non-key DID
+        let mut secp = vec![0xe7, 0x01];
+        secp.extend_from_slice(&[2; 33]);
+        let secp = format!("did:key:z{}", bs58::encode(secp).into_string());
+        assert_eq!(did_key_pubkey(&secp), None);
+        assert_eq!(did_key_pubkey("did:web:example.com"), None);
+    }
+
+    #[test]
+    fn issued_credentials_verify_and_reject_tampering() {
+        let (attester, sign) = keypair([3; 32]);
+        let record = sample_attestation(2, attester, 1_760_000_000);
+        let credential = issue_credential(&record, 1_760_000_100, &sign);
+        let document = credential.to_string();
+        assert_eq!(verify_credential(&document, Some(&record)), Ok(()));
+        assert_eq!(verify_credential(&document, None), Ok(()));
+
+        let mut raised = credential.clone();
+        raised["credentialSubject"]["payoutMicroUsdc"] = "999999999".into();
+        assert_eq!(
+            verify_credential(&raised.to_string(), None),
+            Err(CredentialError::InvalidSignature)
+        );
+        assert_eq!(
+            verify_credential(&raised.to_string(), Some(&record)),
+            Err(CredentialError::RecordMismatch)
+        );
+
+        let mut other = record.clone();
+        other.sequence += 1;
+        assert_eq!(
+            verify_credential(&document, Some(&other)),
+            Err(CredentialError::RecordMismatch)
+        );
+
+        let mut legacy = credential.clone();
+        legacy["proof"]["type"] = "Ed25519Signature2020".into();
+        legacy["proof"]
+            .as_object_mut()
+            .unwrap()
+            .remove("cryptosuite");
+        assert_eq!(
+            verify_credential(&legacy.to_string(), None),
+            Err(CredentialError::UnsupportedProof)
+        );
+
+        let mut rehomed = credential;
+        rehomed["proof"]["verificationMethod"] = did_key(&Pubkey::new_unique()).into();
+        assert_eq!(
+            verify_credential(&rehomed.to_string(), None),
+            Err(CredentialError::InvalidField("verificationMethod"))
+        );
+    }
+}
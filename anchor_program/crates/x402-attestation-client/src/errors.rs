@@ -0,0 +1,533 @@
+//! Typed program and framework errors
+//!
+//! Decodes the custom error code of a failed transaction into an
+//! [`AttestationError`]: one of the program's own `ErrorCode`s, or an
+//! Anchor account/constraint check folded into a few [`FrameworkError`]
+//! kinds. Each carries a [`Remediation`] for support tooling.
+
+use std::fmt;
+
+use anchor_lang::error::ERROR_CODE_OFFSET;
+
+use crate::sender::SendError;
+
+pub use x402_attestation::ErrorCode;
+
+/// Program errors indexed by `code - ERROR_CODE_OFFSET`, in declaration order
+const PROGRAM_ERRORS: &[ErrorCode] = &[
+    ErrorCode::InvalidProofHash,
+    ErrorCode::InvalidPublicInputs,
+    ErrorCode::AttestationExists,
+    ErrorCode::Unauthorized,
+    ErrorCode::InvalidConfig,
+    ErrorCode::AttestationTooRecent,
+    ErrorCode::PeriodMismatch,
+    ErrorCode::ArithmeticOverflow,
+    ErrorCode::RelatedClaimMismatch,
+    ErrorCode::ClaimIdMismatch,
+    ErrorCode::HashAlgoNotAllowed,
+    ErrorCode::EvidenceTooOld,
+    ErrorCode::InvalidEvidenceSlot,
+    ErrorCode::InstructionDisabled,
+    ErrorCode::SecondaryProofRequired,
+    ErrorCode::SecondaryProverRequired,
+    ErrorCode::UnknownAccountLayout,
+    ErrorCode::MigrationOutOfOrder,
+    ErrorCode::InvalidNonceAccount,
+    ErrorCode::NonceRegistryFull,
+    ErrorCode::ReservesNotOverdue,
+    ErrorCode::InvalidProofUri,
+    ErrorCode::ProofChecksumMismatch,
+    ErrorCode::InvalidProgramData,
+    ErrorCode::AttesterNotStale,
+    ErrorCode::RefundTransferRequired,
+    ErrorCode::InvalidSchema,
+    ErrorCode::SchemaMismatch,
+    ErrorCode::ImportClosed,
+    ErrorCode::InvalidImportProof,
+    ErrorCode::InvalidResponseFingerprint,
+    ErrorCode::CategoryMismatch,
+    ErrorCode::RetentionNotElapsed,
+    ErrorCode::AlreadyUnlinked,
+    ErrorCode::InvalidClaimBloom,
+    ErrorCode::ClaimBloomMismatch,
+    ErrorCode::InvalidWebhookSigner,
+    ErrorCode::InvalidRegion,
+    ErrorCode::RegionDiversityRequired,
+    ErrorCode::InvalidForeignAttestation,
+    ErrorCode::AnnotationsFull,
+    ErrorCode::InvalidMaintenanceWindow,
+    ErrorCode::MaintenanceScheduleFull,
+    ErrorCode::MaintenanceScheduleMismatch,
+    ErrorCode::MaintenanceWindowActive,
+    ErrorCode::InvalidAttesterRotation,
+    ErrorCode::InvalidSettlementBatch,
+    ErrorCode::ConfigInvariantViolated,
+    ErrorCode::StatsInvariantViolated,
+    ErrorCode::ReservesNotCovered,
+    ErrorCode::NonceRegistryInvariantViolated,
+    ErrorCode::ClaimVerdictsFull,
+    ErrorCode::VerdictAlreadyRecorded,
+    ErrorCode::VerdictThresholdNotMet,
+    ErrorCode::InvalidInclusionProof,
+    ErrorCode::AggregateExhausted,
+    ErrorCode::MerchantDashboardMismatch,
+    ErrorCode::ClusterMismatch,
+    ErrorCode::ReportPeriodOpen,
+    ErrorCode::SettlementBacklog,
+];
+
+/// Anchor framework error kinds, by cause rather than by check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameworkError {
+    /// Instruction data didn't deserialize; SDK and program versions differ
+    InstructionMismatch,
+    /// An account is not the expected PDA or address
+    WrongAccountAddress,
+    /// A required signature is missing
+    MissingSignature,
+    /// A required account does not exist yet
+    AccountNotInitialized,
+    /// An account holds a different type or layout
+    AccountTypeMismatch,
+    /// Any other account or constraint check
+    Other,
+}
+
+/// How to respond to an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Remediation {
+    /// Rebuilding and resending the transaction may succeed
+    pub retryable: bool,
+    /// Only the config admin can fix the cause
+    pub requires_admin: bool,
+    /// Explanation fit for end users and support staff
+    pub user_facing_message: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AttestationError {
+    /// One of the program's own errors
+    Program(ErrorCode),
+    /// An Anchor account or constraint check, with its raw code
+    Framework { code: u32, kind: FrameworkError },
+    /// A code neither the program nor Anchor defines
+    Unknown(u32),
+}
+
+impl AttestationError {
+    /// Decode a custom program error code
+    pub fn from_code(code: u32) -> AttestationError {
+        if let Some(index) = code.checked_sub(ERROR_CODE_OFFSET) {
+            return match PROGRAM_ERRORS.get(index as usize) {
+                Some(error) => AttestationError::Program(*error),
+                None => AttestationError::Unknown(code),
+            };
+        }
+        let kind = match code {
+            100..=103 => FrameworkError::InstructionMismatch,
+            // ConstraintSeeds, ConstraintAddress
+            2006 | 2012 => FrameworkError::WrongAccountAddress,
+            // ConstraintSigner, AccountNotSigner
+            2002 | 3010 => FrameworkError::MissingSignature,
+            3012 => FrameworkError::AccountNotInitialized,
+            // discriminator not found/mismatch, did not deserialize, wrong owner
+            3001..=3003 | 3007 => FrameworkError::AccountTypeMismatch,
+            2000..=3999 => FrameworkError::Other,
+            _ => return AttestationError::Unknown(code),
+        };
+        AttestationError::Framework { code, kind }
+    }
+
+    /// Decode the custom error in a transaction error or log message
+    ///
+    /// Understands `custom program error: 0x1770` as well as the JSON and
+    /// debug forms `{"Custom":6000}` and `Custom(6000)`. `None` if the
+    /// message holds no custom error.
+    pub fn from_transaction_error(message: &str) -> Option<AttestationError> {
+        let code = if let Some((_, rest)) = message.split_once("custom program error: 0x") {
+            let hex: String = rest.chars().take_while(char::is_ascii_hexdigit).collect();
+            u32::from_str_radix(&hex, 16).ok()?
+        } else {
+            let (_, rest) = message
+                .split_once("\"Custom\":")
+                .or_else(|| message.split_once("Custom("))?;
+            let digits: String = rest
+                .trim_start()
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            digits.parse().ok()?
+        };
+        Some(AttestationError::from_code(code))
+    }
+
+    /// Decode the failure of a [`SendError::TransactionFailed`]
+    pub fn from_send_error(error: &SendError) -> Option<AttestationError> {
+        match error {
+            SendError::TransactionFailed(message) => {
+                AttestationError::from_transaction_error(message)
+            }
+            _ => None,
+        }
+    }
+
+    /// The raw custom error code
+    pub fn code(&self) -> u32 {
+        match self {
+            AttestationError::Program(error) => u32::from(*error),
+            AttestationError::Framework { code, .. } | AttestationError::Unknown(code) => *code,
+        }
+    }
+
+    pub fn remediation(&self) -> Remediation {
+        let (retryable, requires_admin, user_facing_message) = match self {
+            AttestationError::Program(error) => program_remediation(*error),
+            AttestationError::Framework { kind, .. } => match kind {
+                FrameworkError::InstructionMismatch => (
+                    false,
+                    false,
+                    "The client is out of date with the deployed program; upgrade the SDK.",
+                ),
+                FrameworkError::WrongAccountAddress => (
+                    true,
+                    false,
+                    "An account address did not match, e.g., across a billing month boundary; rebuild the transaction.",
+                ),
+                FrameworkError::MissingSignature => {
+                    (false, false, "A required signer did not sign the transaction.")
+                }
+                FrameworkError::AccountNotInitialized => (
+                    false,
+                    true,
+                    "A program account is not initialized yet; the admin must create it.",
+                ),
+                FrameworkError::AccountTypeMismatch => (
+                    false,
+                    true,
+                    "An account has an unexpected type or layout; it may need migrating.",
+                ),
+                FrameworkError::Other => {
+                    (false, false, "An account check failed; the transaction was built incorrectly.")
+                }
+            },
+            AttestationError::Unknown(_) => (false, false, "The program returned an unknown error."),
+        };
+        Remediation {
+            retryable,
+            requires_admin,
+            user_facing_message,
+        }
+    }
+}
+
+fn program_remediation(error: ErrorCode) -> (bool, bool, &'static str) {
+    match error {
+        ErrorCode::InvalidProofHash => (
+            false,
+            false,
+            "The proof hash was rejected; regenerate the proof.",
+        ),
+        ErrorCode::InvalidPublicInputs => (
+            false,
+            false,
+            "The proof's public inputs don't fit the evidence schema.",
+        ),
+        ErrorCode::AttestationExists => (false, false, "This claim has already been attested."),
+        ErrorCode::Unauthorized => (
+            false,
+            true,
+            "Only the configured admin or attester may do this.",
+        ),
+        ErrorCode::InvalidConfig => (false, true, "A configuration value is out of range."),
+        ErrorCode::AttestationTooRecent => (
+            true,
+            false,
+            "The attestation is too recent to archive; try again later.",
+        ),
+        ErrorCode::PeriodMismatch => (
+            false,
+            false,
+            "The archive period doesn't match the attestation's month.",
+        ),
+        ErrorCode::ArithmeticOverflow => (
+            false,
+            true,
+            "A program counter overflowed; contact the operator.",
+        ),
+        ErrorCode::RelatedClaimMismatch => (
+            false,
+            false,
+            "The related claim doesn't match the attestation passed with it.",
+        ),
+        ErrorCode::ClaimIdMismatch => (
+            false,
+            false,
+            "The claim ID doesn't match the payment it was derived from.",
+        ),
+        ErrorCode::HashAlgoNotAllowed => (false, true, "This proof hash algorithm isn't enabled."),
+        ErrorCode::EvidenceTooOld => (
+            false,
+            false,
+            "The claim evidence is too old to be attested.",
+        ),
+        ErrorCode::InvalidEvidenceSlot => (
+            true,
+            false,
+            "The evidence slot is ahead of the cluster; retry shortly.",
+        ),
+        ErrorCode::InstructionDisabled => (false, true, "This operation is currently disabled."),
+        ErrorCode::SecondaryProofRequired => (
+            false,
+            false,
+            "Payouts this large need a second, independent proof.",
+        ),
+        ErrorCode::SecondaryProverRequired => (
+            false,
+            false,
+            "The secondary proof must be co-signed by the configured prover.",
+        ),
+        ErrorCode::UnknownAccountLayout => (
+            false,
+            true,
+            "An account isn't an attestation in a known layout.",
+        ),
+        ErrorCode::MigrationOutOfOrder => (
+            true,
+            true,
+            "The migration batch is out of order; rebuild it from the current status.",
+        ),
+        ErrorCode::InvalidNonceAccount => (
+            false,
+            true,
+            "The durable nonce account isn't registered or initialized.",
+        ),
+        ErrorCode::NonceRegistryFull => (false, true, "The nonce registry is full."),
+        ErrorCode::ReservesNotOverdue => {
+            (false, false, "The reserve attestation isn't overdue yet.")
+        }
+        ErrorCode::InvalidProofUri => (false, false, "The proof URI is empty or too long."),
+        ErrorCode::ProofChecksumMismatch => (
+            false,
+            false,
+            "The proof checksum doesn't match the attested proof hash.",
+        ),
+        ErrorCode::InvalidProgramData => (
+            false,
+            true,
+            "The account passed isn't this program's ProgramData.",
+        ),
+        ErrorCode::AttesterNotStale => (
+            false,
+            false,
+            "The attester is still within its heartbeat window.",
+        ),
+        ErrorCode::RefundTransferRequired => (
+            false,
+            false,
+            "The attestation must be followed by its refund transfer.",
+        ),
+        ErrorCode::InvalidSchema => (false, true, "The schema definition is invalid."),
+        ErrorCode::SchemaMismatch => (
+            false,
+            false,
+            "The schema account doesn't match the schema id.",
+        ),
+        ErrorCode::ImportClosed => (false, true, "Historical import is closed."),
+        ErrorCode::InvalidImportProof => (
+            false,
+            false,
+            "The record isn't in the published historical dataset.",
+        ),
+        ErrorCode::InvalidResponseFingerprint => (
+            false,
+            false,
+            "The response fingerprint is zero or was already recorded for this claim.",
+        ),
+        ErrorCode::CategoryMismatch => (
+            false,
+            false,
+            "The merchant category accounts don't match the claim's merchant.",
+        ),
+        ErrorCode::RetentionNotElapsed => (
+            true,
+            false,
+            "The retention period hasn't passed yet; try again later.",
+        ),
+        ErrorCode::AlreadyUnlinked => (
+            false,
+            false,
+            "The claimant has already been unlinked from this claim.",
+        ),
+        ErrorCode::InvalidClaimBloom => (
+            false,
+            false,
+            "The claim filter size or hash count is out of range.",
+        ),
+        ErrorCode::ClaimBloomMismatch => (
+            false,
+            false,
+            "The claim filter passed doesn't belong to the claim's merchant.",
+        ),
+        ErrorCode::InvalidWebhookSigner => (
+            false,
+            true,
+            "The new webhook signing key must be set and differ from the current one.",
+        ),
+        ErrorCode::InvalidRegion => (
+            false,
+            false,
+            "The region must be a two-letter uppercase country code.",
+        ),
+        ErrorCode::RegionDiversityRequired => (
+            false,
+            true,
+            "This claim needs co-signers registered in two different regions.",
+        ),
+        ErrorCode::InvalidForeignAttestation => (
+            false,
+            false,
+            "The foreign chain id and attestation reference must be set.",
+        ),
+        ErrorCode::AnnotationsFull => (
+            false,
+            false,
+            "This attestation already has the maximum number of annotations.",
+        ),
+        ErrorCode::InvalidMaintenanceWindow => (
+            false,
+            false,
+            "Maintenance windows must be declared before they start.",
+        ),
+        ErrorCode::MaintenanceScheduleFull => (
+            true,
+            false,
+            "The merchant has too many pending maintenance windows; retry after one ends.",
+        ),
+        ErrorCode::MaintenanceScheduleMismatch => (
+            false,
+            false,
+            "Pass the maintenance schedule of the attested merchant.",
+        ),
+        ErrorCode::MaintenanceWindowActive => (
+            false,
+            false,
+            "The evidence falls inside the merchant's declared maintenance window and is not covered.",
+        ),
+        ErrorCode::InvalidAttesterRotation => (
+            false,
+            true,
+            "Rotate to a different key and have the new key co-sign the rotation.",
+        ),
+        ErrorCode::InvalidSettlementBatch => (
+            true,
+            false,
+            "The settlement batch is stale or out of order; rebuild it from the ledger.",
+        ),
+        ErrorCode::ConfigInvariantViolated => (
+            false,
+            true,
+            "The program config is internally inconsistent; an operator must correct it.",
+        ),
+        ErrorCode::StatsInvariantViolated => (
+            false,
+            true,
+            "The program's attestation statistics disagree; an operator must investigate.",
+        ),
+        ErrorCode::ReservesNotCovered => (
+            false,
+            true,
+            "The latest reserve statement shows the vault below its reserved exposure.",
+        ),
+        ErrorCode::NonceRegistryInvariantViolated => (
+            false,
+            true,
+            "The nonce registry holds unset or duplicate entries; an operator must investigate.",
+        ),
+        ErrorCode::ClaimVerdictsFull => (
+            false,
+            false,
+            "This claim already has the maximum number of verdicts.",
+        ),
+        ErrorCode::VerdictAlreadyRecorded => (
+            false,
+            false,
+            "This verdict source has already ruled on the claim.",
+        ),
+        ErrorCode::VerdictThresholdNotMet => (
+            true,
+            false,
+            "The claim doesn't have enough fraud verdicts yet; retry once more sources have ruled.",
+        ),
+        ErrorCode::InvalidInclusionProof => (
+            false,
+            false,
+            "The claim is not in the aggregated batch; rebuild its path from the batch's claim list.",
+        ),
+        ErrorCode::AggregateExhausted => (
+            false,
+            false,
+            "The aggregated proof's claim count or total payout is already used up.",
+        ),
+        ErrorCode::MerchantDashboardMismatch => (
+            false,
+            false,
+            "The merchant dashboard passed doesn't belong to the claim's merchant.",
+        ),
+        ErrorCode::ClusterMismatch => (
+            false,
+            false,
+            "The transaction was signed for a different cluster; rebuild it for this one.",
+        ),
+        ErrorCode::ReportPeriodOpen => (
+            false,
+            false,
+            "Reports can only be anchored for months that have ended.",
+        ),
+        ErrorCode::SettlementBacklog => (
+            true,
+            false,
+            "Too many claims await settlement; retry after the next settlement batch.",
+        ),
+    }
+}
+
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttestationError::Program(error) => write!(f, "{} ({})", error, self.code()),
+            AttestationError::Framework { code, kind } => write!(f, "{kind:?} ({code})"),
+            AttestationError::Unknown(code) => write!(f, "unknown error ({code})"),
+        }
+    }
+}
+
+impl std::error::Error for AttestationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn program_errors_follow_declaration_order() {
+        for (index, error) in PROGRAM_ERRORS.iter().enumerate() {
+            let code = ERROR_CODE_OFFSET + index as u32;
+            assert_eq!(u32::from(*error), code, "{error:?} is out of order");
+            match AttestationError::from_code(code) {
+                AttestationError::Program(decoded) => assert_eq!(u32::from(decoded), code),
+                other => panic!("{code} decoded as {other:?}"),
+            }
+        }
+
+        // The first code past the table is not a program error
+        let next = ERROR_CODE_OFFSET + PROGRAM_ERRORS.len() as u32;
+        assert!(matches!(
+            AttestationError::from_code(next),
+            AttestationError::Unknown(code) if code == next
+        ));
+        assert!(matches!(
+            AttestationError::from_transaction_error("custom program error: 0x1770"),
+            Some(AttestationError::Program(ErrorCode::InvalidProofHash))
+        ));
+    }
+}
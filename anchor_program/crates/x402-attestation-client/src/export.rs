@@ -0,0 +1,240 @@
+//! Audit log export
+//!
+//! Builds month-bounded CSV reports of claims, payouts and fraud verdicts
+//! from decoded accounts, for readers who won't query the chain. Columns
+//! are fixed per kind (`*_COLUMNS`) and rows sorted by time, so the same
+//! accounts always give the same bytes. Anchor a report's checksum with
+//! [`report_hash`]; [`Report::matches`] checks a file against it later.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use sha2::{Digest, Sha256};
+
+use crate::amount::MicroUsdc;
+use crate::credential::{hex, rfc3339};
+use crate::{
+    audit_report_address, config_address, month_id, AuditReport, ClaimVerdicts, ProofAttestation,
+    ID, REPORT_KIND_CLAIMS, REPORT_KIND_PAYOUTS, REPORT_KIND_VERDICTS,
+};
+
+pub const CLAIMS_COLUMNS: &[&str] = &[
+    "claim_id",
+    "sequence",
+    "attested_at",
+    "merchant_id",
+    "fraud_detected",
+    "http_status",
+    "body_length",
+    "proof_hash",
+    "hash_algo",
+    "circuit_version",
+    "schema_id",
+    "imported",
+];
+
+pub const PAYOUTS_COLUMNS: &[&str] = &[
+    "claim_id",
+    "sequence",
+    "attested_at",
+    "payout_micro_usdc",
+    "payout_usdc",
+    "refund_tx_sig",
+];
+
+pub const VERDICTS_COLUMNS: &[&str] = &[
+    "claim_id",
+    "recorded_at",
+    "source",
+    "kind",
+    "weight",
+    "fraud_detected",
+    "evidence_hash",
+];
+
+/// One exported report file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// One of the `REPORT_KIND_*` codes
+    pub kind: u8,
+    /// Month covered, encoded as YYYYMM (UTC)
+    pub period: u32,
+    pub csv: String,
+    /// Data rows, header excluded
+    pub row_count: u64,
+}
+
+impl Report {
+    /// SHA-256 of the file, as anchored by `report_hash`
+    pub fn checksum(&self) -> [u8; 32] {
+        Sha256::digest(self.csv.as_bytes()).into()
+    }
+
+    /// Whether this is the report `anchored` was recorded for
+    pub fn matches(&self, anchored: &AuditReport) -> bool {
+        anchored.kind == self.kind
+            && anchored.period == self.period
+            && anchored.row_count == self.row_count
+            && anchored.checksum == self.checksum()
+    }
+}
+
+/// Claims attested in `period`
+pub fn claims_report(period: u32, attestations: &[ProofAttestation]) -> Report {
+    let rows = in_period(period, attestations).into_iter().map(|record| {
+        let [fraud_detected, http_status, body_length, _] = record.public_inputs;
+        vec![
+            hex(&record.claim_id),
+            record.sequence.to_string(),
+            rfc3339(record.attested_at),
+            hex(&record.merchant_id),
+            fraud_detected.to_string(),
+            http_status.to_string(),
+            body_length.to_string(),
+            hex(&record.proof_hash),
+            record.hash_algo.to_string(),
+            record.circuit_version.to_string(),
+            record.schema_id.to_string(),
+            record.imported.to_string(),
+        ]
+    });
+    report(REPORT_KIND_CLAIMS, period, CLAIMS_COLUMNS, rows)
+}
+
+/// Payouts of the claims attested in `period`
+pub fn payouts_report(period: u32, attestations: &[ProofAttestation]) -> Report {
+    let rows = in_period(period, attestations).into_iter().map(|record| {
+        let payout = MicroUsdc(record.public_inputs[3]);
+        vec![
+            hex(&record.claim_id),
+            record.sequence.to_string(),
+            rfc3339(record.attested_at),
+            payout.0.to_string(),
+            payout.to_usdc().to_string(),
+            bs58::encode(record.refund_tx_sig).into_string(),
+        ]
+    });
+    report(REPORT_KIND_PAYOUTS, period, PAYOUTS_COLUMNS, rows)
+}
+
+/// Fraud verdicts recorded in `period`, whenever their claim was filed
+pub fn verdicts_report(period: u32, verdicts: &[ClaimVerdicts]) -> Report {
+    let mut entries: Vec<_> = verdicts
+        .iter()
+        .flat_map(|claim| {
+            claim
+                .entries
+                .iter()
+                .map(move |entry| (claim.claim_id, entry))
+        })
+        .filter(|(_, entry)| month_id(entry.recorded_at) == period)
+        .collect();
+    entries.sort_by_key(|(claim_id, entry)| (entry.recorded_at, *claim_id, entry.source));
+
+    let rows = entries.into_iter().map(|(claim_id, entry)| {
+        vec![
+            hex(&claim_id),
+            rfc3339(entry.recorded_at),
+            entry.source.to_string(),
+            entry.kind.to_string(),
+            entry.weight.to_string(),
+            entry.fraud_detected.to_string(),
+            hex(&entry.evidence_hash),
+        ]
+    });
+    report(REPORT_KIND_VERDICTS, period, VERDICTS_COLUMNS, rows)
+}
+
+/// `report_hash` anchoring `report`'s checksum
+pub fn report_hash(admin: &Pubkey, report: &Report) -> Instruction {
+    let accounts = x402_attestation::accounts::ReportHash {
+        config: config_address(),
+        report: audit_report_address(report.kind, report.period),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::ReportHash {
+            kind: report.kind,
+            period: report.period,
+            checksum: report.checksum(),
+            row_count: report.row_count,
+        }
+        .data(),
+    }
+}
+
+fn in_period(period: u32, attestations: &[ProofAttestation]) -> Vec<&ProofAttestation> {
+    let mut records: Vec<_> = attestations
+        .iter()
+        .filter(|record| month_id(record.attested_at) == period)
+        .collect();
+    records.sort_by_key(|record| (record.attested_at, record.claim_id));
+    records
+}
+
+fn report(
+    kind: u8,
+    period: u32,
+    columns: &[&str],
+    rows: impl Iterator<Item = Vec<String>>,
+) -> Report {
+    let mut csv = columns.join(",");
+    csv.push('\n');
+    let mut row_count = 0;
+    for row in rows {
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+        row_count += 1;
+    }
+    Report {
+        kind,
+        period,
+        csv,
+        row_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample_attestation;
+
+    #[test]
+    fn reports_are_period_bounded_and_reproducible() {
+        let attester = Pubkey::new_unique();
+        // 2025-10-09 and 2025-10-31, then 2025-11-01
+        let october = [
+            sample_attestation(2, attester, 1_761_868_800),
+            sample_attestation(1, attester, 1_760_000_000),
+        ];
+        let november = sample_attestation(3, attester, 1_761_955_200);
+        let all = [october[0].clone(), november, october[1].clone()];
+
+        let report = payouts_report(202510, &all);
+        assert_eq!(report.row_count, 2);
+        let lines: Vec<&str> = report.csv.lines().collect();
+        assert_eq!(lines[0], PAYOUTS_COLUMNS.join(","));
+        assert!(lines[1].starts_with(&hex(&october[1].claim_id)));
+        assert!(lines[2].starts_with(&hex(&october[0].claim_id)));
+        assert_eq!(payouts_report(202510, &october), report);
+        assert_eq!(claims_report(202511, &all).row_count, 1);
+
+        let mut anchored = AuditReport {
+            kind: REPORT_KIND_PAYOUTS,
+            period: 202510,
+            checksum: report.checksum(),
+            row_count: 2,
+            reported_by: Pubkey::new_unique(),
+            reported_at: 0,
+            bump: 0,
+        };
+        assert!(report.matches(&anchored));
+        anchored.checksum[0] ^= 1;
+        assert!(!report.matches(&anchored));
+    }
+}
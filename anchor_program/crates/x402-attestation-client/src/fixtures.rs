@@ -0,0 +1,294 @@
+//! Deterministic program state for integration tests
+//!
+//! [`Fixtures`] builds a consistent set of program accounts (config,
+//! recent-attestations feed, statistics, billing periods and attestations)
+//! without running the program, and dumps each one in the JSON format of
+//! `solana account --output json`. Load them with
+//!
+//! ```text
+//! solana-test-validator --bpf-program <program id> x402_attestation.so \
+//!     --account <address> <address>.json ...
+//! ```
+//!
+//! or hand the same fields to bankrun's `addAccount`. The same inputs
+//! always produce byte-identical dumps.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::{AccountSerialize, Space, ZeroCopy};
+use base64::Engine;
+
+use x402_attestation::{
+    BillingPeriod, Config, RecentAttestation, RecentAttestations, ATTESTATION_LAYOUT_VERSION,
+    ATTESTATION_SEED, BILLING_PERIOD_SEED, CONFIG_SEED, DEFAULT_SCHEMA_ID, HASH_ALGO_BLAKE3,
+    IX_ARCHIVE, IX_ATTEST, RECENT_ATTESTATIONS_LEN, RECENT_ATTESTATIONS_SEED,
+    STATS_CIRCUIT_BUCKETS, STATS_LAYOUT_BUCKETS, STATS_SEED,
+};
+
+use crate::{month_id, ProgramStats, ProofAttestation, ID};
+
+/// One account, ready to load into a test validator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountFixture {
+    pub address: Pubkey,
+    /// Rent-exempt minimum for the data length
+    pub lamports: u64,
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+}
+
+impl AccountFixture {
+    /// Serialize a program account, zero-padded to `8 + space`
+    pub fn program_account<T: AccountSerialize>(
+        address: Pubkey,
+        account: &T,
+        space: usize,
+    ) -> AccountFixture {
+        let mut data = Vec::with_capacity(8 + space);
+        account
+            .try_serialize(&mut data)
+            .expect("serializing into a Vec cannot fail");
+        data.resize(8 + space, 0);
+
+        AccountFixture {
+            address,
+            lamports: Rent::default().minimum_balance(data.len()),
+            owner: ID,
+            data,
+        }
+    }
+
+    /// Lay out a zero-copy program account: discriminator, then its bytes
+    pub fn zero_copy_account<T: ZeroCopy>(address: Pubkey, account: &T) -> AccountFixture {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(account));
+
+        AccountFixture {
+            address,
+            lamports: Rent::default().minimum_balance(data.len()),
+            owner: ID,
+            data,
+        }
+    }
+
+    /// `solana account --output json` representation
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "pubkey": self.address.to_string(),
+            "account": {
+                "lamports": self.lamports,
+                "data": [base64::engine::general_purpose::STANDARD.encode(&self.data), "base64"],
+                "owner": self.owner.to_string(),
+                "executable": false,
+                "rentEpoch": 0,
+                "space": self.data.len(),
+            },
+        })
+        .to_string()
+    }
+}
+
+/// Program state built up as the program would record it
+pub struct Fixtures {
+    config: Config,
+    recent: Box<RecentAttestations>,
+    stats: ProgramStats,
+    billing: BTreeMap<u32, BillingPeriod>,
+    attestations: Vec<ProofAttestation>,
+    extra: Vec<AccountFixture>,
+}
+
+impl Fixtures {
+    /// Config as `initialize_config` leaves it, with empty feed and stats
+    pub fn new(admin: Pubkey, archive_ttl_seconds: i64) -> Fixtures {
+        let config = Config {
+            admin,
+            archive_ttl_seconds,
+            emit_evidence_events: false,
+            allowed_hash_algos: 1 << HASH_ALGO_BLAKE3,
+            max_claim_age_slots: 0,
+            enabled_instructions: IX_ATTEST | IX_ARCHIVE,
+            dual_proof_threshold: 0,
+            secondary_prover: Pubkey::default(),
+            reserve_auditor: Pubkey::default(),
+            reserve_interval_seconds: 0,
+            attester_offline_after_slots: 0,
+            require_refund_transfer: false,
+            payout_mint: Pubkey::default(),
+            import_root: [0; 32],
+            import_until: 0,
+            fingerprint_threshold: 0,
+            retention_seconds: 0,
+            require_region_diversity: false,
+            verdict_threshold: 0,
+            max_pending_settlements: 0,
+            refund_source: Pubkey::default(),
+            bump: bump(&[CONFIG_SEED]),
+        };
+        let recent = Box::new(RecentAttestations {
+            total: 0,
+            entries: [RecentAttestation::default(); RECENT_ATTESTATIONS_LEN],
+            bump: bump(&[RECENT_ATTESTATIONS_SEED]),
+            _padding: [0; 7],
+        });
+        let stats = ProgramStats {
+            by_layout: [0; STATS_LAYOUT_BUCKETS],
+            by_circuit: [0; STATS_CIRCUIT_BUCKETS],
+            bump: bump(&[STATS_SEED]),
+        };
+
+        Fixtures {
+            config,
+            recent,
+            stats,
+            billing: BTreeMap::new(),
+            attestations: Vec::new(),
+            extra: Vec::new(),
+        }
+    }
+
+    /// Adjust config parameters, as `update_config` would
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    /// Record an attestation, filling in what the program derives
+    ///
+    /// Assigns the next sequence number, the bump, billing period and
+    /// `evm_digest`, and counts it into the feed, statistics and billing
+    /// period. `slot` is the slot entered in the feed.
+    pub fn attest(&mut self, mut attestation: ProofAttestation, slot: u64) -> &ProofAttestation {
+        attestation.sequence = self
+            .recent
+            .push(RecentAttestation {
+                claim_id: attestation.claim_id,
+                payout_amount: attestation.public_inputs[3],
+                slot,
+            })
+            .expect("fixture sequence overflow");
+        attestation.bump = bump(&[ATTESTATION_SEED, &attestation.claim_id]);
+        attestation.billing_period_id = month_id(attestation.attested_at);
+        attestation.evm_digest = attestation.evm_struct_hash();
+
+        self.stats.by_layout[usize::from(ATTESTATION_LAYOUT_VERSION)] += 1;
+        self.stats.by_circuit
+            [usize::from(attestation.circuit_version).min(STATS_CIRCUIT_BUCKETS - 1)] += 1;
+
+        let period = attestation.billing_period_id;
+        let billing = self.billing.entry(period).or_insert_with(|| BillingPeriod {
+            period,
+            count: 0,
+            total_payout: 0,
+            bump: bump(&[BILLING_PERIOD_SEED, &period.to_le_bytes()]),
+        });
+        billing.count += 1;
+        billing.total_payout += attestation.public_inputs[3];
+
+        self.attestations.push(attestation);
+        self.attestations.last().expect("just pushed")
+    }
+
+    /// Add any other program account, e.g., a schema or registry entry
+    pub fn push_account<T: AccountSerialize>(
+        &mut self,
+        address: Pubkey,
+        account: &T,
+        space: usize,
+    ) {
+        self.extra
+            .push(AccountFixture::program_account(address, account, space));
+    }
+
+    /// Every account, singletons first, then attestations in record order
+    pub fn accounts(&self) -> Vec<AccountFixture> {
+        let mut accounts = vec![
+            AccountFixture::program_account(
+                crate::config_address(),
+                &self.config,
+                Config::INIT_SPACE,
+            ),
+            AccountFixture::zero_copy_account(
+                crate::recent_attestations_address(),
+                self.recent.as_ref(),
+            ),
+            AccountFixture::program_account(
+                crate::program_stats_address(),
+                &self.stats,
+                ProgramStats::INIT_SPACE,
+            ),
+        ];
+        accounts.extend(self.billing.iter().map(|(period, billing)| {
+            AccountFixture::program_account(
+                crate::billing_period_address(*period),
+                billing,
+                BillingPeriod::INIT_SPACE,
+            )
+        }));
+        accounts.extend(self.attestations.iter().map(|attestation| {
+            AccountFixture::program_account(
+                crate::attestation_address(&attestation.claim_id),
+                attestation,
+                ProofAttestation::INIT_SPACE,
+            )
+        }));
+        accounts.extend(self.extra.iter().cloned());
+        accounts
+    }
+
+    /// Write each account to `<dir>/<address>.json`, returning the paths
+    pub fn write(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        self.accounts()
+            .into_iter()
+            .map(|account| {
+                let path = dir.join(format!("{}.json", account.address));
+                fs::write(&path, account.to_json())?;
+                Ok(path)
+            })
+            .collect()
+    }
+}
+
+/// Deterministic sample attestation number `index`, attested at `attested_at`
+///
+/// Fields the program derives are left for [`Fixtures::attest`].
+pub fn sample_attestation(index: u32, attester: Pubkey, attested_at: i64) -> ProofAttestation {
+    let seed = index.to_le_bytes();
+    let derive = |tag: &[u8]| hashv(&[b"x402-fixture", tag, &seed]).to_bytes();
+    let mut refund_tx_sig = [0u8; 64];
+    refund_tx_sig[..32].copy_from_slice(&derive(b"refund"));
+    refund_tx_sig[32..].copy_from_slice(&derive(b"refund-2"));
+
+    ProofAttestation {
+        claim_id: derive(b"claim"),
+        proof_hash: derive(b"proof"),
+        hash_algo: HASH_ALGO_BLAKE3,
+        proof_hash_secondary: None,
+        public_inputs: [1, 503, 0, 10_000 * (u64::from(index % 10) + 1)],
+        evidence_slot: 0,
+        refund_tx_sig,
+        merchant_id: derive(b"merchant"),
+        related_claim_id: None,
+        attested_at,
+        attester,
+        sequence: 0,
+        bump: 0,
+        evm_digest: [0; 32],
+        circuit_version: 1,
+        schema_id: DEFAULT_SCHEMA_ID,
+        billing_period_id: 0,
+        imported: false,
+        response_fingerprint: [0; 32],
+        refund_destination: Pubkey::default(),
+    }
+}
+
+fn bump(seeds: &[&[u8]]) -> u8 {
+    Pubkey::find_program_address(seeds, &ID).1
+}
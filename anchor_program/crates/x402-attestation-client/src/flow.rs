@@ -0,0 +1,371 @@
+//! The full claim flow as resumable, ordered transactions
+//!
+//! A claim takes up to four transactions, each depending on the one
+//! before: `start_proof_job` while the proof is generated, the
+//! attestation, then `register_proof_uri` for the archived proof and
+//! `link_foreign_attestation` for its EVM counterpart. [`ClaimFlow`]
+//! builds and sends them in order through a [`Sender`], recording each
+//! one that lands in a [`FlowState`] the caller persists, so a flow
+//! interrupted at any point resumes at the first step not yet done.
+//!
+//! The refund is the backend's own transfer rather than a step: pass its
+//! signature in [`AttestArgs::refund_signature`] or, with
+//! `Config::require_refund_transfer`, bundle it into the attestation
+//! with [`ClaimFlow::refund_transfer`].
+//!
+//! Every step creates a PDA, so a step that landed without being
+//! recorded is detected on resume rather than sent twice.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::VersionedMessage;
+use serde::{Deserialize, Serialize};
+
+use crate::instructions::{self, AttestArgs};
+use crate::sender::{RpcEndpoint, SendError, SendOutcome, Sender, SignedTransaction};
+use crate::{attestation_address, foreign_link_address, proof_job_address, proof_uri_address};
+
+/// One transaction of the flow, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowStep {
+    StartProofJob,
+    Attest,
+    RegisterProofUri,
+    LinkForeignAttestation,
+}
+
+/// A step that landed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompletedStep {
+    pub step: FlowStep,
+    /// Base58 transaction signature; `None` when the step was found
+    /// already landed rather than confirmed by this flow
+    pub signature: Option<String>,
+}
+
+/// Persisted progress of one claim's flow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowState {
+    pub claim_id: [u8; 32],
+    /// In the order they landed
+    pub completed: Vec<CompletedStep>,
+}
+
+impl FlowState {
+    pub fn new(claim_id: [u8; 32]) -> FlowState {
+        FlowState {
+            claim_id,
+            completed: Vec::new(),
+        }
+    }
+
+    pub fn is_complete(&self, step: FlowStep) -> bool {
+        self.completed.iter().any(|done| done.step == step)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("flow state always serializes")
+    }
+
+    pub fn from_json(json: &str) -> Result<FlowState, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    fn complete(&mut self, step: FlowStep, signature: Option<[u8; 64]>) {
+        if !self.is_complete(step) {
+            self.completed.push(CompletedStep {
+                step,
+                signature: signature.map(|sig| bs58::encode(sig).into_string()),
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FlowError {
+    #[error("flow state is for a different claim")]
+    WrongClaim,
+
+    #[error("{step:?} failed: {source}")]
+    Send {
+        step: FlowStep,
+        #[source]
+        source: SendError,
+    },
+
+    #[error("persisting flow state failed: {0}")]
+    Persist(String),
+}
+
+/// Proof archive location registered after the attestation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofArchive {
+    pub uri: String,
+    pub size: u64,
+    pub checksum: [u8; 32],
+}
+
+/// Builder and driver for one claim's transactions
+///
+/// `authority` signs and pays for every step; it must be the attester
+/// for the steps after the attestation.
+#[derive(Debug, Clone)]
+pub struct ClaimFlow {
+    authority: Pubkey,
+    attest: AttestArgs,
+    derived: Option<([u8; 64], [u8; 32], Pubkey)>,
+    refund_transfer: Option<Instruction>,
+    proof_archive: Option<ProofArchive>,
+    foreign: Option<(u64, [u8; 32])>,
+}
+
+impl ClaimFlow {
+    /// Flow for `attest_claim_proof` with `args`
+    ///
+    /// Set `args.proof_job` to start a proof job first.
+    pub fn new(authority: Pubkey, args: AttestArgs) -> ClaimFlow {
+        ClaimFlow {
+            authority,
+            attest: args,
+            derived: None,
+            refund_transfer: None,
+            proof_archive: None,
+            foreign: None,
+        }
+    }
+
+    /// Attest with `attest_derived_claim_proof` instead; `args.claim_id`
+    /// must be the derived ID
+    pub fn derived(
+        mut self,
+        payment_signature: [u8; 64],
+        endpoint_hash: [u8; 32],
+        claimant: Pubkey,
+    ) -> ClaimFlow {
+        self.derived = Some((payment_signature, endpoint_hash, claimant));
+        self
+    }
+
+    /// Send the refund in the attestation's transaction, right after the
+    /// attest instruction (see [`instructions::refund_transfer`])
+    pub fn refund_transfer(mut self, transfer: Instruction) -> ClaimFlow {
+        self.refund_transfer = Some(transfer);
+        self
+    }
+
+    /// Register the proof's archive location once attested
+    pub fn proof_archive(mut self, archive: ProofArchive) -> ClaimFlow {
+        self.proof_archive = Some(archive);
+        self
+    }
+
+    /// Link the EVM verifier's record once attested
+    pub fn foreign_attestation(mut self, chain_id: u64, reference: [u8; 32]) -> ClaimFlow {
+        self.foreign = Some((chain_id, reference));
+        self
+    }
+
+    pub fn claim_id(&self) -> &[u8; 32] {
+        &self.attest.claim_id
+    }
+
+    /// The steps this flow runs, in order
+    pub fn steps(&self) -> Vec<FlowStep> {
+        let optional = [
+            (self.attest.proof_job, FlowStep::StartProofJob),
+            (true, FlowStep::Attest),
+            (self.proof_archive.is_some(), FlowStep::RegisterProofUri),
+            (self.foreign.is_some(), FlowStep::LinkForeignAttestation),
+        ];
+        optional
+            .into_iter()
+            .filter_map(|(included, step)| included.then_some(step))
+            .collect()
+    }
+
+    /// The first step `state` hasn't recorded, `None` once all are done
+    pub fn next_step(&self, state: &FlowState) -> Option<FlowStep> {
+        self.steps()
+            .into_iter()
+            .find(|&step| !state.is_complete(step))
+    }
+
+    /// PDA the step creates, which marks it as landed
+    pub fn step_account(&self, step: FlowStep) -> Pubkey {
+        let claim_id = &self.attest.claim_id;
+        match step {
+            FlowStep::StartProofJob => proof_job_address(claim_id),
+            FlowStep::Attest => attestation_address(claim_id),
+            FlowStep::RegisterProofUri => proof_uri_address(claim_id),
+            FlowStep::LinkForeignAttestation => foreign_link_address(claim_id),
+        }
+    }
+
+    /// Instructions of one step's transaction
+    pub fn instructions(&self, step: FlowStep) -> Vec<Instruction> {
+        let authority = &self.authority;
+        let claim_id = &self.attest.claim_id;
+        match step {
+            FlowStep::StartProofJob => vec![instructions::start_proof_job(authority, *claim_id)],
+            FlowStep::Attest => {
+                let args = self.attest.clone();
+                let attest = match self.derived {
+                    Some((payment_signature, endpoint_hash, claimant)) => {
+                        instructions::attest_derived_claim_proof(
+                            authority,
+                            payment_signature,
+                            endpoint_hash,
+                            claimant,
+                            args,
+                        )
+                    }
+                    None => instructions::attest_claim_proof(authority, args),
+                };
+                std::iter::once(attest)
+                    .chain(self.refund_transfer.clone())
+                    .collect()
+            }
+            FlowStep::RegisterProofUri => self
+                .proof_archive
+                .iter()
+                .map(|archive| {
+                    instructions::register_proof_uri(
+                        authority,
+                        claim_id,
+                        archive.uri.clone(),
+                        archive.size,
+                        archive.checksum,
+                    )
+                })
+                .collect(),
+            FlowStep::LinkForeignAttestation => self
+                .foreign
+                .iter()
+                .map(|&(chain_id, reference)| {
+                    instructions::link_foreign_attestation(authority, claim_id, chain_id, reference)
+                })
+                .collect(),
+        }
+    }
+
+    /// Send every step `state` hasn't recorded, in order
+    ///
+    /// `persist` is called with the updated state after each step lands,
+    /// before the next is sent; stop on its error rather than run ahead
+    /// of what is saved. The proof job is closed by the attestation, so
+    /// an existing attestation also marks it done instead of a second job
+    /// being started.
+    pub fn run<E, S, P>(
+        &self,
+        sender: &mut Sender<E>,
+        state: &mut FlowState,
+        mut sign: S,
+        mut persist: P,
+    ) -> Result<(), FlowError>
+    where
+        E: RpcEndpoint,
+        S: FnMut(&VersionedMessage) -> Result<SignedTransaction, String>,
+        P: FnMut(&FlowState) -> Result<(), String>,
+    {
+        if state.claim_id != self.attest.claim_id {
+            return Err(FlowError::WrongClaim);
+        }
+
+        while let Some(step) = self.next_step(state) {
+            if step == FlowStep::StartProofJob {
+                let attestation = self.step_account(FlowStep::Attest);
+                let attested = sender
+                    .account_exists(&attestation)
+                    .map_err(|source| FlowError::Send { step, source })?;
+                if attested {
+                    state.complete(step, None);
+                    persist(state).map_err(FlowError::Persist)?;
+                    continue;
+                }
+            }
+
+            let outcome = sender
+                .send(
+                    &self.authority,
+                    &self.instructions(step),
+                    &self.step_account(step),
+                    &mut sign,
+                )
+                .map_err(|source| FlowError::Send { step, source })?;
+            let signature = match outcome {
+                SendOutcome::Confirmed { signature, .. } => Some(signature),
+                SendOutcome::AlreadyLanded => None,
+            };
+            state.complete(step, signature);
+            persist(state).map_err(FlowError::Persist)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::PublicInputs;
+    use crate::MicroUsdc;
+
+    fn args(proof_job: bool) -> AttestArgs {
+        AttestArgs {
+            claim_id: [7; 32],
+            proof_hash: [1; 32],
+            hash_algo: 0,
+            public_inputs: PublicInputs {
+                fraud_detected: true,
+                http_status: 503,
+                body_length: 0,
+                payout: MicroUsdc(1_000_000),
+            },
+            evidence_slot: 1,
+            refund_signature: [2; 64],
+            merchant_id: [3; 32],
+            related_claim_id: None,
+            secondary_proof: None,
+            signer_regions: false,
+            circuit_version: 1,
+            schema_id: x402_attestation::DEFAULT_SCHEMA_ID,
+            billing_period: 0,
+            day_index: None,
+            proof_job,
+            merchant_category: None,
+            claim_bloom: false,
+            claim_verdicts: false,
+            merchant_dashboard: false,
+            settlement_queue: false,
+            refund_claimant: None,
+            durable_nonce: false,
+        }
+    }
+
+    #[test]
+    fn steps_follow_configuration_and_resume_from_state() {
+        let authority = Pubkey::new_unique();
+        let flow = ClaimFlow::new(authority, args(true)).foreign_attestation(1, [9; 32]);
+        assert_eq!(
+            flow.steps(),
+            vec![
+                FlowStep::StartProofJob,
+                FlowStep::Attest,
+                FlowStep::LinkForeignAttestation
+            ]
+        );
+
+        let mut state = FlowState::new(*flow.claim_id());
+        state.complete(FlowStep::StartProofJob, Some([5; 64]));
+        let state = FlowState::from_json(&state.to_json()).unwrap();
+        assert_eq!(flow.next_step(&state), Some(FlowStep::Attest));
+        assert_eq!(
+            flow.step_account(FlowStep::Attest),
+            attestation_address(&[7; 32])
+        );
+
+        let bare = ClaimFlow::new(authority, args(false));
+        assert_eq!(bare.steps(), vec![FlowStep::Attest]);
+        assert!(bare.instructions(FlowStep::RegisterProofUri).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+//! Cross-chain links to the zkEngine verifier's EVM attestations
+//!
+//! zkEngine proofs are attested on Solana by this program and on an EVM
+//! chain by a verifier contract. `link_foreign_attestation` records the
+//! counterpart's chain id and reference in a [`ForeignLink`] beside the
+//! Solana record; these helpers fetch the counterpart through the
+//! caller's EVM client and check both records describe the same proof
+//! and outcome.
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::{attestation_address, ForeignLink, ProofAttestation};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ForeignError {
+    #[error("link is for a different claim")]
+    WrongLink,
+
+    #[error("foreign attestation not found on chain {0}")]
+    NotFound(u64),
+
+    #[error("fetching the foreign attestation failed: {0}")]
+    Fetch(String),
+
+    #[error("foreign attestation disagrees on {0}")]
+    Mismatch(&'static str),
+}
+
+/// Verifier contract record, as read by the caller's EVM client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForeignAttestation {
+    /// EIP-155 chain id the record was read from
+    pub chain_id: u64,
+    /// The contract's reference to the record
+    pub reference: [u8; 32],
+    pub proof_hash: [u8; 32],
+    /// `[fraud_detected, http_status, body_length, payout_amount]`
+    pub public_inputs: [u64; 4],
+    /// Solana claim id, if the contract stores it
+    pub claim_id: Option<[u8; 32]>,
+    /// Bridged `ProofAttestation::evm_digest`, if the contract stores it
+    pub solana_struct_hash: Option<[u8; 32]>,
+}
+
+impl ForeignAttestation {
+    /// Solana attestation PDA of the record, when it names its claim
+    pub fn solana_attestation_address(&self) -> Option<Pubkey> {
+        self.claim_id.as_ref().map(attestation_address)
+    }
+}
+
+/// Record lookup on the foreign chain, implemented over the caller's EVM client
+pub trait ForeignChain {
+    /// `None` when the contract has no record under `reference`
+    fn fetch_attestation(
+        &mut self,
+        chain_id: u64,
+        reference: &[u8; 32],
+    ) -> Result<Option<ForeignAttestation>, String>;
+}
+
+/// Fetch the record `link` points to and cross-check it against `attestation`
+///
+/// `link` is the decoded account at
+/// [`foreign_link_address`](crate::foreign_link_address) of the claim.
+pub fn resolve_foreign<C: ForeignChain>(
+    attestation: &ProofAttestation,
+    link: &ForeignLink,
+    chain: &mut C,
+) -> Result<ForeignAttestation, ForeignError> {
+    let foreign = chain
+        .fetch_attestation(link.foreign_chain_id, &link.foreign_attestation_ref)
+        .map_err(ForeignError::Fetch)?
+        .ok_or(ForeignError::NotFound(link.foreign_chain_id))?;
+    cross_check(attestation, link, &foreign)?;
+    Ok(foreign)
+}
+
+/// Check that both records describe the same proof and outcome
+///
+/// The proof hash and public inputs must always agree; the claim id and
+/// bridged struct hash are compared when the contract records them. The
+/// struct hash is recomputed from the Solana record's fields rather than
+/// read from `evm_digest`.
+pub fn cross_check(
+    attestation: &ProofAttestation,
+    link: &ForeignLink,
+    foreign: &ForeignAttestation,
+) -> Result<(), ForeignError> {
+    if link.claim_id != attestation.claim_id {
+        return Err(ForeignError::WrongLink);
+    }
+    let checks = [
+        (foreign.chain_id == link.foreign_chain_id, "chain id"),
+        (
+            foreign.reference == link.foreign_attestation_ref,
+            "reference",
+        ),
+        (foreign.proof_hash == attestation.proof_hash, "proof hash"),
+        (
+            foreign.public_inputs == attestation.public_inputs,
+            "public inputs",
+        ),
+        (
+            foreign.claim_id.is_none_or(|id| id == attestation.claim_id),
+            "claim id",
+        ),
+        (
+            foreign
+                .solana_struct_hash
+                .is_none_or(|hash| hash == attestation.evm_struct_hash()),
+            "struct hash",
+        ),
+    ];
+    match checks.iter().find(|(agrees, _)| !agrees) {
+        Some((_, field)) => Err(ForeignError::Mismatch(field)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,173 @@
+//! Historical claims import
+//!
+//! Builds the Merkle tree over the off-chain claims history whose root is
+//! published in `Config::import_root`, and the `import_historical_attestation`
+//! instructions that replay each record with its path. Leaves are ordered
+//! as given; publish the dataset in that order so anyone can rebuild the
+//! root.
+//!
+//! Claims that were only ever recorded as SPL Memos go through
+//! `claim_legacy_attestation` instead, with the memo parsed into a
+//! `HistoricalRecord` and no tree.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use crate::instructions::PublicInputs;
+use crate::{
+    attestation_address, billing_period_address, config_address, legacy_memo_address, month_id,
+    program_stats_address, ID,
+};
+
+pub use x402_attestation::{historical_leaf, merkle_root, HISTORICAL_LEAF_DOMAIN};
+
+/// One claim from the pre-program history
+#[derive(Debug, Clone)]
+pub struct HistoricalRecord {
+    pub claim_id: [u8; 32],
+    /// Original unix timestamp of the refund
+    pub attested_at: i64,
+    pub proof_hash: [u8; 32],
+    pub hash_algo: u8,
+    pub public_inputs: PublicInputs,
+    pub refund_signature: [u8; 64],
+    pub merchant_id: [u8; 32],
+}
+
+impl HistoricalRecord {
+    pub fn leaf(&self) -> [u8; 32] {
+        historical_leaf(
+            &self.claim_id,
+            self.attested_at,
+            &self.proof_hash,
+            self.hash_algo,
+            &self.public_inputs.to_array(),
+            &self.refund_signature,
+            &self.merchant_id,
+        )
+    }
+}
+
+/// Merkle tree over historical leaves, hashed as `merkle_root` expects
+///
+/// An odd node at the end of a level is carried up unchanged.
+#[derive(Debug, Clone)]
+pub struct HistoricalTree {
+    /// `levels[0]` are the leaves, the last level is the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl HistoricalTree {
+    /// # Panics
+    ///
+    /// If `leaves` is empty.
+    pub fn new(leaves: Vec<[u8; 32]>) -> HistoricalTree {
+        assert!(!leaves.is_empty(), "HistoricalTree needs at least one leaf");
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] if a <= b => hashv(&[a, b]).to_bytes(),
+                    [a, b] => hashv(&[b, a]).to_bytes(),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        HistoricalTree { levels }
+    }
+
+    /// Value for `Config::import_root`
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Merkle path of the leaf at `index`, `None` if out of range
+    pub fn proof(&self, index: usize) -> Option<Vec<[u8; 32]>> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let mut proof = Vec::new();
+        let mut index = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// `import_historical_attestation`, signed by the config admin
+pub fn import_historical_attestation(
+    admin: &Pubkey,
+    record: &HistoricalRecord,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::ImportHistoricalAttestation {
+        config: config_address(),
+        attestation: attestation_address(&record.claim_id),
+        program_stats: program_stats_address(),
+        billing_period: billing_period_address(month_id(record.attested_at)),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::ImportHistoricalAttestation {
+        claim_id: record.claim_id,
+        attested_at: record.attested_at,
+        proof_hash: record.proof_hash,
+        hash_algo: record.hash_algo,
+        public_inputs: record.public_inputs.to_array(),
+        refund_signature: record.refund_signature,
+        merchant_id: record.merchant_id,
+        proof,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `claim_legacy_attestation`, signed by the config admin
+///
+/// `memo_signature` is the transaction that carried the memo `record` was
+/// parsed from.
+pub fn claim_legacy_attestation(
+    admin: &Pubkey,
+    memo_signature: [u8; 64],
+    record: &HistoricalRecord,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::ClaimLegacyAttestation {
+        config: config_address(),
+        attestation: attestation_address(&record.claim_id),
+        legacy_memo: legacy_memo_address(&record.claim_id),
+        program_stats: program_stats_address(),
+        billing_period: billing_period_address(month_id(record.attested_at)),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::ClaimLegacyAttestation {
+        memo_signature,
+        claim_id: record.claim_id,
+        attested_at: record.attested_at,
+        proof_hash: record.proof_hash,
+        hash_algo: record.hash_algo,
+        public_inputs: record.public_inputs.to_array(),
+        refund_signature: record.refund_signature,
+        merchant_id: record.merchant_id,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
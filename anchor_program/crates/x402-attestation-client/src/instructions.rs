@@ -0,0 +1,850 @@
+//! Instruction builders
+//!
+//! Build ready-to-sign `Instruction`s with the PDAs filled in.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use crate::amount::MicroUsdc;
+use crate::nonce::nonce_registry_address;
+use crate::{
+    annotations_address, attestation_address, attester_link_address, attester_status_address,
+    auditor_address, billing_period_address, category_stats_address, claim_bloom_address,
+    claim_verdicts_address, config_address, day_index_address, fingerprint_index_address,
+    foreign_link_address, maintenance_schedule_address, merchant_category_address,
+    merchant_dashboard_address, program_data_address, program_stats_address, proof_job_address,
+    proof_uri_address, recent_attestations_address, reserve_attestation_address, schema_address,
+    settlement_batch_address, settlement_ledger_address, settlement_queue_address,
+    upgrade_info_address, verdict_source_address, webhook_signer_address, ID,
+};
+use x402_attestation::{SchemaField, DEFAULT_SCHEMA_ID};
+
+/// Named public inputs of a fraud proof
+///
+/// Encoded on-chain as `[fraud_detected, http_status, body_length, payout_amount]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicInputs {
+    pub fraud_detected: bool,
+    pub http_status: u16,
+    pub body_length: u64,
+    pub payout: MicroUsdc,
+}
+
+impl PublicInputs {
+    pub fn to_array(&self) -> [u64; 4] {
+        [
+            self.fraud_detected as u64,
+            u64::from(self.http_status),
+            self.body_length,
+            self.payout.0,
+        ]
+    }
+}
+
+/// Arguments shared by both attestation instructions
+#[derive(Debug, Clone)]
+pub struct AttestArgs {
+    pub claim_id: [u8; 32],
+    pub proof_hash: [u8; 32],
+    pub hash_algo: u8,
+    pub public_inputs: PublicInputs,
+    /// Slot at which the fraud evidence was captured
+    pub evidence_slot: u64,
+    pub refund_signature: [u8; 64],
+    pub merchant_id: [u8; 32],
+    pub related_claim_id: Option<[u8; 32]>,
+    /// Secondary pipeline proof hash and its co-signing prover key
+    pub secondary_proof: Option<([u8; 32], Pubkey)>,
+    /// Whether to pass both signers' registry entries with a secondary
+    /// proof, for `Config::require_region_diversity`
+    pub signer_regions: bool,
+    /// zkEngine circuit version that produced the proof
+    pub circuit_version: u16,
+    /// Evidence input layout, [`DEFAULT_SCHEMA_ID`] for the built-in one
+    pub schema_id: u16,
+    /// `month_id` of the cluster time the transaction lands at; across a
+    /// month boundary the attestation fails its seeds check and must be
+    /// rebuilt
+    pub billing_period: u32,
+    /// `unix_day` of the cluster time the transaction lands at, to add the
+    /// claim to that day's index; across midnight the attestation fails
+    /// its seeds check and must be rebuilt
+    pub day_index: Option<u32>,
+    /// Whether [`start_proof_job`] was sent for the claim; the attestation
+    /// closes the job
+    pub proof_job: bool,
+    /// Category registered for `merchant_id`, if any, to count the claim into
+    pub merchant_category: Option<u8>,
+    /// Whether `merchant_id` has a paid-claim filter to insert the claim into
+    pub claim_bloom: bool,
+    /// Whether to pass the claim's recorded verdicts, required with
+    /// `Config::verdict_threshold`
+    pub claim_verdicts: bool,
+    /// Whether `merchant_id` has an open dashboard to update
+    pub merchant_dashboard: bool,
+    /// Whether to count the claim into the settlement queue, required
+    /// with `Config::max_pending_settlements`
+    pub settlement_queue: bool,
+    /// Claimant receiving the refund in their associated token account,
+    /// when the config requires the refund transfer in the same
+    /// transaction (see [`refund_transfer`])
+    pub refund_claimant: Option<Pubkey>,
+    /// Whether the transaction advances a durable nonce (see
+    /// [`crate::nonce::durable_message`]), which must be registered
+    pub durable_nonce: bool,
+}
+
+fn attest_accounts(
+    authority: &Pubkey,
+    args: &AttestArgs,
+) -> x402_attestation::accounts::AttestProof {
+    let signer_statuses = args
+        .secondary_proof
+        .filter(|_| args.signer_regions)
+        .map(|(_, prover)| prover);
+    x402_attestation::accounts::AttestProof {
+        config: config_address(),
+        attestation: attestation_address(&args.claim_id),
+        related_attestation: args.related_claim_id.as_ref().map(attestation_address),
+        schema: (args.schema_id != DEFAULT_SCHEMA_ID).then(|| schema_address(args.schema_id)),
+        recent_attestations: recent_attestations_address(),
+        program_stats: program_stats_address(),
+        billing_period: billing_period_address(args.billing_period),
+        day_index: args.day_index.map(day_index_address),
+        proof_job: args.proof_job.then(|| proof_job_address(&args.claim_id)),
+        merchant_category: args
+            .merchant_category
+            .map(|_| merchant_category_address(&args.merchant_id)),
+        category_stats: args.merchant_category.map(category_stats_address),
+        claim_bloom: args
+            .claim_bloom
+            .then(|| claim_bloom_address(&args.merchant_id)),
+        maintenance_schedule: maintenance_schedule_address(&args.merchant_id),
+        merchant_dashboard: args
+            .merchant_dashboard
+            .then(|| merchant_dashboard_address(&args.merchant_id)),
+        claim_verdicts: args
+            .claim_verdicts
+            .then(|| claim_verdicts_address(&args.claim_id)),
+        settlement_queue: args.settlement_queue.then(settlement_queue_address),
+        authority: *authority,
+        secondary_prover: args.secondary_proof.map(|(_, prover)| prover),
+        authority_status: signer_statuses.map(|_| attester_status_address(authority)),
+        secondary_prover_status: signer_statuses.as_ref().map(attester_status_address),
+        claimant: args.refund_claimant,
+        nonce_registry: args.durable_nonce.then(nonce_registry_address),
+        instructions: sysvar_instructions::ID,
+        system_program: system_program::ID,
+    }
+}
+
+/// `attest_claim_proof`, paid for and signed by `authority`
+pub fn attest_claim_proof(authority: &Pubkey, args: AttestArgs) -> Instruction {
+    let accounts = attest_accounts(authority, &args);
+    let data = x402_attestation::instruction::AttestClaimProof {
+        claim_id: args.claim_id,
+        proof_hash: args.proof_hash,
+        hash_algo: args.hash_algo,
+        public_inputs: args.public_inputs.to_array(),
+        evidence_slot: args.evidence_slot,
+        refund_signature: args.refund_signature,
+        merchant_id: args.merchant_id,
+        related_claim_id: args.related_claim_id,
+        proof_hash_secondary: args.secondary_proof.map(|(hash, _)| hash),
+        circuit_version: args.circuit_version,
+        schema_id: args.schema_id,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// SPL Token `TransferChecked` of a refund, sent with its attestation
+///
+/// With `Config::require_refund_transfer` set, add this to the attest
+/// instruction's transaction, from `Config::refund_source` to the
+/// claimant's [`crate::associated_token_address`]. `token_program` is
+/// [`crate::TOKEN_PROGRAM_ID`] or, for Token-2022 mints,
+/// [`crate::TOKEN_2022_PROGRAM_ID`].
+pub fn refund_transfer(
+    token_program: Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    amount: MicroUsdc,
+    decimals: u8,
+) -> Instruction {
+    let mut data = Vec::with_capacity(10);
+    data.push(12);
+    data.extend_from_slice(&amount.0.to_le_bytes());
+    data.push(decimals);
+
+    Instruction {
+        program_id: token_program,
+        accounts: vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data,
+    }
+}
+
+/// `attest_derived_claim_proof`; `args.claim_id` must be the derived ID
+pub fn attest_derived_claim_proof(
+    authority: &Pubkey,
+    payment_signature: [u8; 64],
+    endpoint_hash: [u8; 32],
+    claimant: Pubkey,
+    args: AttestArgs,
+) -> Instruction {
+    let accounts = attest_accounts(authority, &args);
+    let data = x402_attestation::instruction::AttestDerivedClaimProof {
+        claim_id: args.claim_id,
+        payment_signature,
+        endpoint_hash,
+        claimant,
+        proof_hash: args.proof_hash,
+        hash_algo: args.hash_algo,
+        public_inputs: args.public_inputs.to_array(),
+        evidence_slot: args.evidence_slot,
+        refund_signature: args.refund_signature,
+        merchant_id: args.merchant_id,
+        related_claim_id: args.related_claim_id,
+        proof_hash_secondary: args.secondary_proof.map(|(hash, _)| hash),
+        circuit_version: args.circuit_version,
+        schema_id: args.schema_id,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `record_response_fingerprint`, paid for and signed by the claim's attester
+///
+/// Usually sent right after the attest instruction, in the same transaction.
+pub fn record_response_fingerprint(
+    authority: &Pubkey,
+    claim_id: [u8; 32],
+    response_fingerprint: [u8; 32],
+) -> Instruction {
+    let accounts = x402_attestation::accounts::RecordResponseFingerprint {
+        config: config_address(),
+        attestation: attestation_address(&claim_id),
+        fingerprint_index: fingerprint_index_address(&response_fingerprint),
+        authority: *authority,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RecordResponseFingerprint {
+        claim_id,
+        response_fingerprint,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `register_schema`, signed by the config admin
+pub fn register_schema(admin: &Pubkey, schema_id: u16, fields: Vec<SchemaField>) -> Instruction {
+    let accounts = x402_attestation::accounts::RegisterSchema {
+        config: config_address(),
+        schema: schema_address(schema_id),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RegisterSchema { schema_id, fields };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `register_merchant_category`, signed by the config admin
+pub fn register_merchant_category(
+    admin: &Pubkey,
+    merchant_id: [u8; 32],
+    category: u8,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::RegisterMerchantCategory {
+        config: config_address(),
+        merchant_category: merchant_category_address(&merchant_id),
+        category_stats: category_stats_address(category),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RegisterMerchantCategory {
+        merchant_id,
+        category,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `create_claim_bloom`, signed by the config admin
+///
+/// Size the filter with [`crate::claim_bloom_parameters`].
+pub fn create_claim_bloom(
+    admin: &Pubkey,
+    merchant_id: [u8; 32],
+    size_bytes: u32,
+    hash_count: u8,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::CreateClaimBloom {
+        config: config_address(),
+        claim_bloom: claim_bloom_address(&merchant_id),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::CreateClaimBloom {
+        merchant_id,
+        size_bytes,
+        hash_count,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `declare_maintenance`, signed by the config admin
+pub fn declare_maintenance(
+    admin: &Pubkey,
+    merchant_id: [u8; 32],
+    start_slot: u64,
+    end_slot: u64,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::DeclareMaintenance {
+        config: config_address(),
+        maintenance_schedule: maintenance_schedule_address(&merchant_id),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::DeclareMaintenance {
+        merchant_id,
+        start_slot,
+        end_slot,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `open_merchant_dashboard`, paid for and signed by `payer`
+pub fn open_merchant_dashboard(payer: &Pubkey, merchant_id: [u8; 32]) -> Instruction {
+    let accounts = x402_attestation::accounts::OpenMerchantDashboard {
+        merchant_dashboard: merchant_dashboard_address(&merchant_id),
+        payer: *payer,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::OpenMerchantDashboard { merchant_id };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `close_merchant_dashboard`, signed by whoever opened it, who receives the rent
+pub fn close_merchant_dashboard(opened_by: &Pubkey, merchant_id: [u8; 32]) -> Instruction {
+    let accounts = x402_attestation::accounts::CloseMerchantDashboard {
+        merchant_dashboard: merchant_dashboard_address(&merchant_id),
+        opened_by: *opened_by,
+    };
+    let data = x402_attestation::instruction::CloseMerchantDashboard { merchant_id };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `link_foreign_attestation`, signed by the attestation's attester
+pub fn link_foreign_attestation(
+    attester: &Pubkey,
+    claim_id: &[u8; 32],
+    foreign_chain_id: u64,
+    foreign_attestation_ref: [u8; 32],
+) -> Instruction {
+    let accounts = x402_attestation::accounts::LinkForeignAttestation {
+        attestation: attestation_address(claim_id),
+        foreign_link: foreign_link_address(claim_id),
+        attester: *attester,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::LinkForeignAttestation {
+        foreign_chain_id,
+        foreign_attestation_ref,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `commit_settlement_batch`, signed by the config admin
+///
+/// `claim_ids` are the batched claims in ascending attestation sequence,
+/// all after `SettlementLedger::next_sequence`; `batch_index` is
+/// `SettlementLedger::batch_count`. Around 20 claims fit a transaction
+/// without a lookup table. Pass `settlement_queue` once the queue exists.
+pub fn commit_settlement_batch(
+    admin: &Pubkey,
+    batch_index: u64,
+    claim_ids: &[[u8; 32]],
+    settlement_queue: bool,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::CommitSettlementBatch {
+        config: config_address(),
+        settlement_ledger: settlement_ledger_address(),
+        settlement_batch: settlement_batch_address(batch_index),
+        settlement_queue: settlement_queue.then(settlement_queue_address),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(
+        claim_ids
+            .iter()
+            .map(|claim_id| AccountMeta::new_readonly(attestation_address(claim_id), false)),
+    );
+
+    Instruction {
+        program_id: ID,
+        accounts: metas,
+        data: x402_attestation::instruction::CommitSettlementBatch { batch_index }.data(),
+    }
+}
+
+/// `initialize_settlement_queue`, signed by the config admin
+///
+/// Pass `settlement_ledger` once a settlement batch was committed.
+pub fn initialize_settlement_queue(admin: &Pubkey, settlement_ledger: bool) -> Instruction {
+    let accounts = x402_attestation::accounts::InitializeSettlementQueue {
+        config: config_address(),
+        settlement_queue: settlement_queue_address(),
+        recent_attestations: recent_attestations_address(),
+        settlement_ledger: settlement_ledger.then(settlement_ledger_address),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::InitializeSettlementQueue {}.data(),
+    }
+}
+
+/// `register_auditor`, signed by the config admin
+pub fn register_auditor(admin: &Pubkey, auditor: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::RegisterAuditor {
+        config: config_address(),
+        auditor_registration: auditor_address(auditor),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RegisterAuditor { auditor: *auditor };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `remove_auditor`, signed by the config admin, who receives the rent
+pub fn remove_auditor(admin: &Pubkey, auditor: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::RemoveAuditor {
+        config: config_address(),
+        auditor_registration: auditor_address(auditor),
+        admin: *admin,
+    };
+    let data = x402_attestation::instruction::RemoveAuditor { auditor: *auditor };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `register_verdict_source`, signed by the config admin
+///
+/// `kind` is one of [`crate::VERDICT_KIND_ZK_PROOF`],
+/// [`crate::VERDICT_KIND_ORACLE`] or [`crate::VERDICT_KIND_ADJUSTER`].
+pub fn register_verdict_source(
+    admin: &Pubkey,
+    source: &Pubkey,
+    kind: u8,
+    weight: u64,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::RegisterVerdictSource {
+        config: config_address(),
+        verdict_source: verdict_source_address(source),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RegisterVerdictSource {
+        source: *source,
+        kind,
+        weight,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `remove_verdict_source`, signed by the config admin, who receives the rent
+pub fn remove_verdict_source(admin: &Pubkey, source: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::RemoveVerdictSource {
+        config: config_address(),
+        verdict_source: verdict_source_address(source),
+        admin: *admin,
+    };
+    let data = x402_attestation::instruction::RemoveVerdictSource { source: *source };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `record_verdict`, paid for and signed by a registered verdict source
+pub fn record_verdict(
+    source: &Pubkey,
+    claim_id: [u8; 32],
+    fraud_detected: bool,
+    evidence_hash: [u8; 32],
+) -> Instruction {
+    let accounts = x402_attestation::accounts::RecordVerdict {
+        verdict_source: verdict_source_address(source),
+        claim_verdicts: claim_verdicts_address(&claim_id),
+        source: *source,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RecordVerdict {
+        claim_id,
+        fraud_detected,
+        evidence_hash,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `annotate_attestation`, signed by a registered auditor
+pub fn annotate_attestation(
+    auditor: &Pubkey,
+    claim_id: &[u8; 32],
+    note_hash: [u8; 32],
+    category: u8,
+) -> Instruction {
+    let accounts = x402_attestation::accounts::AnnotateAttestation {
+        auditor_registration: auditor_address(auditor),
+        attestation: attestation_address(claim_id),
+        annotations: annotations_address(claim_id),
+        auditor: *auditor,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::AnnotateAttestation {
+        note_hash,
+        category,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `register_proof_uri`, signed by the attestation's attester
+pub fn register_proof_uri(
+    attester: &Pubkey,
+    claim_id: &[u8; 32],
+    uri: String,
+    size: u64,
+    checksum: [u8; 32],
+) -> Instruction {
+    let accounts = x402_attestation::accounts::RegisterProofUri {
+        attestation: attestation_address(claim_id),
+        proof_uri: proof_uri_address(claim_id),
+        attester: *attester,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RegisterProofUri {
+        uri,
+        size,
+        checksum,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `unlink_claimant`, signed by the config admin
+///
+/// Keep `salt` with the deletion record: with it and the original URI,
+/// the `uri_commitment` in the program's event can still be opened.
+pub fn unlink_claimant(admin: &Pubkey, claim_id: &[u8; 32], salt: [u8; 32]) -> Instruction {
+    let accounts = x402_attestation::accounts::UnlinkClaimant {
+        config: config_address(),
+        attestation: attestation_address(claim_id),
+        proof_uri: proof_uri_address(claim_id),
+        admin: *admin,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::UnlinkClaimant { salt }.data(),
+    }
+}
+
+/// `rotate_webhook_signer`, signed by the config admin
+pub fn rotate_webhook_signer(admin: &Pubkey, new_key: Pubkey, overlap_seconds: i64) -> Instruction {
+    let accounts = x402_attestation::accounts::RotateWebhookSigner {
+        config: config_address(),
+        webhook_signer: webhook_signer_address(),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RotateWebhookSigner {
+        new_key,
+        overlap_seconds,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `record_build_hash`, signed by the config admin
+pub fn record_build_hash(
+    admin: &Pubkey,
+    commit_hash: [u8; 20],
+    verified_build_hash: [u8; 32],
+) -> Instruction {
+    let accounts = x402_attestation::accounts::RecordBuildHash {
+        config: config_address(),
+        upgrade_info: upgrade_info_address(),
+        program_data: program_data_address(),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RecordBuildHash {
+        commit_hash,
+        verified_build_hash,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `migrate_config`, signed by the config admin
+///
+/// Run once right after upgrading a program whose config predates
+/// fields added since; until then the config doesn't decode.
+pub fn migrate_config(admin: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::MigrateConfig {
+        config: config_address(),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::MigrateConfig {}.data(),
+    }
+}
+
+/// `migrate_program_stats`, signed by the config admin
+///
+/// Run once right after upgrading a program whose statistics predate
+/// layout 8; until then they don't decode.
+pub fn migrate_program_stats(admin: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::MigrateProgramStats {
+        config: config_address(),
+        program_stats: program_stats_address(),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::MigrateProgramStats {}.data(),
+    }
+}
+
+/// `resize_recent_attestations`, signed by the config admin
+///
+/// Run once after upgrading a program whose ring buffer predates the
+/// zero-copy layout, before the next attestation.
+pub fn resize_recent_attestations(admin: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::ResizeRecentAttestations {
+        config: config_address(),
+        recent_attestations: recent_attestations_address(),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::ResizeRecentAttestations {}.data(),
+    }
+}
+
+/// `start_proof_job`, paid for and signed by the attester who will attest the claim
+pub fn start_proof_job(attester: &Pubkey, claim_id: [u8; 32]) -> Instruction {
+    let accounts = x402_attestation::accounts::StartProofJob {
+        proof_job: proof_job_address(&claim_id),
+        attester: *attester,
+        system_program: system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::StartProofJob { claim_id }.data(),
+    }
+}
+
+/// `attester_heartbeat`, signed by the registered attester
+pub fn attester_heartbeat(attester: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::AttesterHeartbeat {
+        attester_status: attester_status_address(attester),
+        attester: *attester,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::AttesterHeartbeat {}.data(),
+    }
+}
+
+/// `set_attester_region`, signed by the config admin
+pub fn set_attester_region(admin: &Pubkey, attester: &Pubkey, region: [u8; 2]) -> Instruction {
+    let accounts = x402_attestation::accounts::SetAttesterRegion {
+        config: config_address(),
+        attester_status: attester_status_address(attester),
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::SetAttesterRegion {
+        attester: *attester,
+        region,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `rotate_attester_key`, signed by the config admin and the new key
+pub fn rotate_attester_key(admin: &Pubkey, old: &Pubkey, new: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::RotateAttesterKey {
+        config: config_address(),
+        old_status: attester_status_address(old),
+        old_link: attester_link_address(old),
+        new_status: attester_status_address(new),
+        new_link: attester_link_address(new),
+        new_attester: *new,
+        admin: *admin,
+        system_program: system_program::ID,
+    };
+    let data = x402_attestation::instruction::RotateAttesterKey {
+        old: *old,
+        new: *new,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// `assert_invariants`; any fee payer may send or simulate it
+///
+/// Set `reserves` and `nonce_registry` to also check those accounts; both
+/// must exist when set. A failure decodes with
+/// [`AttestationError`](crate::errors::AttestationError) to the violated
+/// invariant.
+pub fn assert_invariants(reserves: bool, nonce_registry: bool) -> Instruction {
+    let accounts = x402_attestation::accounts::AssertInvariants {
+        config: config_address(),
+        program_stats: program_stats_address(),
+        reserve_attestation: reserves.then(reserve_attestation_address),
+        nonce_registry: nonce_registry.then(nonce_registry_address),
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::AssertInvariants {}.data(),
+    }
+}
+
+/// `mark_attester_offline` for `attester`; any fee payer may send it
+pub fn mark_attester_offline(attester: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::MarkAttesterOffline {
+        config: config_address(),
+        attester_status: attester_status_address(attester),
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::MarkAttesterOffline {}.data(),
+    }
+}
@@ -0,0 +1,582 @@
+//! Off-chain client helpers for the x402 attestation program
+//!
+//! Transport-agnostic: callers fetch raw account data with whatever RPC
+//! client they use and hand it to these helpers for decoding and traversal.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::{AccountDeserialize, Discriminator};
+
+pub mod aggregate;
+pub mod amount;
+pub mod audit;
+pub mod backend;
+pub mod bench;
+pub mod bundle;
+pub mod cluster;
+pub mod codegen;
+pub mod credential;
+pub mod errors;
+pub mod export;
+pub mod fixtures;
+pub mod flow;
+pub mod foreign;
+pub mod history;
+pub mod instructions;
+pub mod lookup_table;
+pub mod migration;
+pub mod nonce;
+pub mod preflight;
+pub mod receipt;
+pub mod rounding;
+pub mod sender;
+pub mod settlement;
+pub mod watch;
+pub mod webhook;
+
+pub use amount::{AmountError, MicroUsdc, UsdcAmount};
+pub use rounding::{Rounding, RoundingPolicy};
+
+pub use x402_attestation::{
+    associated_token_address, derive_claim_id, month_id, unix_day, AggregateProof, Annotation,
+    AttestationAnnotations, AttesterLink, AttesterStatus, AuditReport, AuditorRegistration,
+    BillingPeriod, CategoryStats, ClaimBloom, ClaimVerdicts, ClusterInfo, DashboardDay, DayIndex,
+    FingerprintIndex, ForeignLink, LegacyMemo, MaintenanceSchedule, MaintenanceWindow,
+    MerchantCategory, MerchantDashboard, NotificationChannel, ProgramStats, ProofAttestation,
+    ProofJob, ProofUri, PublicInputsSchema, RecentAttestations, ReserveAttestation, SchemaField,
+    SchemaFieldKind, SettlementBatch, SettlementLedger, SettlementQueue, UpgradeInfo, Verdict,
+    VerdictSource, WebhookSigner, AGGREGATE_SEED, ANNOTATIONS_SEED, ASSOCIATED_TOKEN_PROGRAM_ID,
+    ATTESTATION_SEED, ATTESTER_LINK_SEED, ATTESTER_SEED, AUDITOR_SEED, BILLING_PERIOD_SEED,
+    CATEGORY_DATA_FEED, CATEGORY_INFERENCE_API, CATEGORY_STATS_SEED, CATEGORY_STORAGE,
+    CLAIM_BLOOM_SEED, CLAIM_VERDICTS_SEED, CLUSTER_SEED, CONFIG_SEED, DASHBOARD_WINDOW_DAYS,
+    DAY_INDEX_SEED, DEFAULT_SCHEMA_ID, EVM_ATTESTATION_TYPE, FINGERPRINT_SEED, FOREIGN_LINK_SEED,
+    ID, LEGACY_MEMO_SEED, MAINTENANCE_SEED, MAX_ANNOTATIONS, MAX_CLAIM_BLOOM_BYTES,
+    MAX_CLAIM_BLOOM_HASHES, MAX_CLAIM_VERDICTS, MAX_DAY_INDEX_CLAIMS, MAX_MAINTENANCE_WINDOWS,
+    MERCHANT_CATEGORY_SEED, MERCHANT_DASHBOARD_SEED, NOTIFICATION_SEED, PROOF_JOB_SEED,
+    PROOF_URI_SEED, RECENT_ATTESTATIONS_SEED, RECENT_ATTESTATIONS_SPACE, REPORT_HASH_SEED,
+    REPORT_KIND_CLAIMS, REPORT_KIND_PAYOUTS, REPORT_KIND_VERDICTS, RESERVES_SEED, SCHEMA_SEED,
+    SETTLEMENT_BATCH_SEED, SETTLEMENT_LEDGER_SEED, SETTLEMENT_QUEUE_SEED, STATS_SEED,
+    TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID, UPGRADE_INFO_SEED, VERDICT_KIND_ADJUSTER,
+    VERDICT_KIND_ORACLE, VERDICT_KIND_ZK_PROOF, VERDICT_SOURCE_SEED, WEBHOOK_SIGNER_SEED,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+
+    #[error("failed to decode account {0}: {1}")]
+    Decode(Pubkey, anchor_lang::error::Error),
+
+    #[error("fetch failed for {0}: {1}")]
+    Fetch(Pubkey, String),
+
+    #[error("invalid lookup table {0}: {1}")]
+    LookupTable(Pubkey, String),
+
+    #[error("failed to compile message: {0}")]
+    Compile(String),
+
+    #[error("account {0} is not an initialized nonce account")]
+    InvalidNonceAccount(Pubkey),
+}
+
+/// Derive the attestation PDA for a claim
+pub fn attestation_address(claim_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[ATTESTATION_SEED, claim_id.as_ref()], &ID).0
+}
+
+/// Derive the proof archive location PDA for a claim
+pub fn proof_uri_address(claim_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[PROOF_URI_SEED, claim_id.as_ref()], &ID).0
+}
+
+/// Derive the PDA of an aggregated proof over a batch of claims
+pub fn aggregate_proof_address(batch_root: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[AGGREGATE_SEED, batch_root.as_ref()], &ID).0
+}
+
+/// Decode raw aggregated proof data (including the discriminator)
+pub fn decode_aggregate_proof(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<AggregateProof, ClientError> {
+    let mut data = data;
+    AggregateProof::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the memo provenance PDA of a claim imported by `claim_legacy_attestation`
+pub fn legacy_memo_address(claim_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[LEGACY_MEMO_SEED, claim_id.as_ref()], &ID).0
+}
+
+/// Decode raw memo provenance data (including the discriminator)
+pub fn decode_legacy_memo(address: &Pubkey, data: &[u8]) -> Result<LegacyMemo, ClientError> {
+    let mut data = data;
+    LegacyMemo::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the cross-chain link PDA for a claim
+pub fn foreign_link_address(claim_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[FOREIGN_LINK_SEED, claim_id.as_ref()], &ID).0
+}
+
+/// Derive the annotation list PDA for a claim
+pub fn annotations_address(claim_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[ANNOTATIONS_SEED, claim_id.as_ref()], &ID).0
+}
+
+/// Derive a registered verdict source's PDA
+pub fn verdict_source_address(source: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VERDICT_SOURCE_SEED, source.as_ref()], &ID).0
+}
+
+/// Decode raw verdict source data (including the discriminator)
+pub fn decode_verdict_source(address: &Pubkey, data: &[u8]) -> Result<VerdictSource, ClientError> {
+    let mut data = data;
+    VerdictSource::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the verdict list PDA for a claim
+pub fn claim_verdicts_address(claim_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[CLAIM_VERDICTS_SEED, claim_id.as_ref()], &ID).0
+}
+
+/// Decode raw claim verdict list data (including the discriminator)
+pub fn decode_claim_verdicts(address: &Pubkey, data: &[u8]) -> Result<ClaimVerdicts, ClientError> {
+    let mut data = data;
+    ClaimVerdicts::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive a registered auditor's PDA
+pub fn auditor_address(auditor: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[AUDITOR_SEED, auditor.as_ref()], &ID).0
+}
+
+/// Derive the claim index PDA of a `unix_day`
+pub fn day_index_address(day: u32) -> Pubkey {
+    Pubkey::find_program_address(&[DAY_INDEX_SEED, day.to_le_bytes().as_ref()], &ID).0
+}
+
+/// Decode raw day index data (including the discriminator)
+pub fn decode_day_index(address: &Pubkey, data: &[u8]) -> Result<DayIndex, ClientError> {
+    let mut data = data;
+    DayIndex::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the singleton settlement queue PDA
+pub fn settlement_queue_address() -> Pubkey {
+    Pubkey::find_program_address(&[SETTLEMENT_QUEUE_SEED], &ID).0
+}
+
+/// Decode raw settlement queue data (including the discriminator)
+pub fn decode_settlement_queue(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<SettlementQueue, ClientError> {
+    let mut data = data;
+    SettlementQueue::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the settlement batch ledger PDA
+pub fn settlement_ledger_address() -> Pubkey {
+    Pubkey::find_program_address(&[SETTLEMENT_LEDGER_SEED], &ID).0
+}
+
+/// Decode raw settlement batch ledger data (including the discriminator)
+pub fn decode_settlement_ledger(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<SettlementLedger, ClientError> {
+    let mut data = data;
+    SettlementLedger::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the PDA of a settlement batch
+pub fn settlement_batch_address(batch_index: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[SETTLEMENT_BATCH_SEED, batch_index.to_le_bytes().as_ref()],
+        &ID,
+    )
+    .0
+}
+
+/// Decode raw settlement batch data (including the discriminator)
+pub fn decode_settlement_batch(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<SettlementBatch, ClientError> {
+    let mut data = data;
+    SettlementBatch::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the billing period aggregate PDA for a `month_id`
+pub fn billing_period_address(period: u32) -> Pubkey {
+    Pubkey::find_program_address(&[BILLING_PERIOD_SEED, period.to_le_bytes().as_ref()], &ID).0
+}
+
+/// Decode raw billing period aggregate data (including the discriminator)
+pub fn decode_billing_period(address: &Pubkey, data: &[u8]) -> Result<BillingPeriod, ClientError> {
+    let mut data = data;
+    BillingPeriod::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the counter PDA of a response fingerprint
+pub fn fingerprint_index_address(fingerprint: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[FINGERPRINT_SEED, fingerprint.as_ref()], &ID).0
+}
+
+/// Derive the category PDA of a merchant
+pub fn merchant_category_address(merchant_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[MERCHANT_CATEGORY_SEED, merchant_id.as_ref()], &ID).0
+}
+
+/// Decode raw merchant category data (including the discriminator)
+pub fn decode_merchant_category(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<MerchantCategory, ClientError> {
+    let mut data = data;
+    MerchantCategory::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the aggregate PDA of a merchant category
+pub fn category_stats_address(category: u8) -> Pubkey {
+    Pubkey::find_program_address(&[CATEGORY_STATS_SEED, &[category]], &ID).0
+}
+
+/// Decode raw category aggregate data (including the discriminator)
+pub fn decode_category_stats(address: &Pubkey, data: &[u8]) -> Result<CategoryStats, ClientError> {
+    let mut data = data;
+    CategoryStats::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive a merchant's paid-claim filter PDA
+pub fn claim_bloom_address(merchant_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[CLAIM_BLOOM_SEED, merchant_id.as_ref()], &ID).0
+}
+
+/// Derive a merchant's dashboard PDA
+pub fn merchant_dashboard_address(merchant_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[MERCHANT_DASHBOARD_SEED, merchant_id.as_ref()], &ID).0
+}
+
+/// Decode raw merchant dashboard data (including the discriminator)
+pub fn decode_merchant_dashboard(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<MerchantDashboard, ClientError> {
+    let mut data = data;
+    MerchantDashboard::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive a merchant's maintenance schedule PDA
+pub fn maintenance_schedule_address(merchant_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[MAINTENANCE_SEED, merchant_id.as_ref()], &ID).0
+}
+
+/// Decode raw maintenance schedule data (including the discriminator)
+pub fn decode_maintenance_schedule(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<MaintenanceSchedule, ClientError> {
+    let mut data = data;
+    MaintenanceSchedule::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Decode raw paid-claim filter data (including the discriminator)
+pub fn decode_claim_bloom(address: &Pubkey, data: &[u8]) -> Result<ClaimBloom, ClientError> {
+    let mut data = data;
+    ClaimBloom::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// `(size_bytes, hash_count)` for `create_claim_bloom` that keeps the
+/// false-positive rate at `false_positive_rate` up to `expected_claims`
+///
+/// Clamped to the program's limits; check the result with
+/// [`claim_bloom_false_positive_rate`] when it may have been.
+pub fn claim_bloom_parameters(expected_claims: u64, false_positive_rate: f64) -> (u32, u8) {
+    let claims = expected_claims.max(1) as f64;
+    let ln2 = std::f64::consts::LN_2;
+    let bits = (-claims * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+    let size_bytes = ((bits / 8.0).ceil() as u32).clamp(1, MAX_CLAIM_BLOOM_BYTES);
+    let hashes = (f64::from(size_bytes) * 8.0 / claims * ln2).round() as u8;
+    (size_bytes, hashes.clamp(1, MAX_CLAIM_BLOOM_HASHES))
+}
+
+/// Expected false-positive rate of a filter holding `claims` claims
+pub fn claim_bloom_false_positive_rate(size_bytes: u32, hash_count: u8, claims: u64) -> f64 {
+    let bits = f64::from(size_bytes) * 8.0;
+    let hashes = f64::from(hash_count);
+    (1.0 - (-hashes * claims as f64 / bits).exp()).powf(hashes)
+}
+
+/// Decode raw response fingerprint counter data (including the discriminator)
+pub fn decode_fingerprint_index(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<FingerprintIndex, ClientError> {
+    let mut data = data;
+    FingerprintIndex::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the public-inputs schema PDA
+pub fn schema_address(schema_id: u16) -> Pubkey {
+    Pubkey::find_program_address(&[SCHEMA_SEED, schema_id.to_le_bytes().as_ref()], &ID).0
+}
+
+/// Decode raw public-inputs schema data (including the discriminator)
+pub fn decode_schema(address: &Pubkey, data: &[u8]) -> Result<PublicInputsSchema, ClientError> {
+    let mut data = data;
+    PublicInputsSchema::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the proving job PDA for a claim
+pub fn proof_job_address(claim_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[PROOF_JOB_SEED, claim_id.as_ref()], &ID).0
+}
+
+/// Decode raw proving job data (including the discriminator)
+pub fn decode_proof_job(address: &Pubkey, data: &[u8]) -> Result<ProofJob, ClientError> {
+    let mut data = data;
+    ProofJob::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the config PDA
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED], &ID).0
+}
+
+/// Derive the recent-attestations ring buffer PDA
+pub fn recent_attestations_address() -> Pubkey {
+    Pubkey::find_program_address(&[RECENT_ATTESTATIONS_SEED], &ID).0
+}
+
+/// Read raw recent-attestations data (including the discriminator)
+///
+/// The account is zero-copy, so this is a single unaligned read of its
+/// bytes rather than a Borsh decode. Buffers not yet grown by
+/// `resize_recent_attestations` fail to decode.
+pub fn decode_recent_attestations(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<RecentAttestations, ClientError> {
+    let decode_error =
+        |code: anchor_lang::error::ErrorCode| ClientError::Decode(*address, code.into());
+    let (discriminator, body) = data
+        .split_at_checked(8)
+        .ok_or(decode_error(anchor_lang::error::ErrorCode::AccountDiscriminatorNotFound))?;
+    if discriminator != RecentAttestations::DISCRIMINATOR {
+        return Err(decode_error(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch));
+    }
+    let body = body
+        .get(..RECENT_ATTESTATIONS_SPACE)
+        .ok_or(decode_error(anchor_lang::error::ErrorCode::AccountDidNotDeserialize))?;
+    Ok(bytemuck::pod_read_unaligned(body))
+}
+
+/// Derive the program statistics PDA
+pub fn program_stats_address() -> Pubkey {
+    Pubkey::find_program_address(&[STATS_SEED], &ID).0
+}
+
+/// Decode raw program statistics data (including the discriminator)
+pub fn decode_program_stats(address: &Pubkey, data: &[u8]) -> Result<ProgramStats, ClientError> {
+    let mut data = data;
+    ProgramStats::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the singleton cluster identity PDA
+pub fn cluster_info_address() -> Pubkey {
+    Pubkey::find_program_address(&[CLUSTER_SEED], &ID).0
+}
+
+/// Decode raw cluster identity data (including the discriminator)
+pub fn decode_cluster_info(address: &Pubkey, data: &[u8]) -> Result<ClusterInfo, ClientError> {
+    let mut data = data;
+    ClusterInfo::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the checksum PDA of the `kind` audit report for `period` (YYYYMM)
+pub fn audit_report_address(kind: u8, period: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            REPORT_HASH_SEED,
+            [kind].as_ref(),
+            period.to_le_bytes().as_ref(),
+        ],
+        &ID,
+    )
+    .0
+}
+
+/// Decode raw audit report checksum data (including the discriminator)
+pub fn decode_audit_report(address: &Pubkey, data: &[u8]) -> Result<AuditReport, ClientError> {
+    let mut data = data;
+    AuditReport::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the proof-of-reserve attestation PDA
+pub fn reserve_attestation_address() -> Pubkey {
+    Pubkey::find_program_address(&[RESERVES_SEED], &ID).0
+}
+
+/// Decode raw proof-of-reserve account data (including the discriminator)
+pub fn decode_reserve_attestation(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<ReserveAttestation, ClientError> {
+    let mut data = data;
+    ReserveAttestation::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the claims feed channel PDA of a merchant
+pub fn notification_channel_address(merchant_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[NOTIFICATION_SEED, merchant_id.as_ref()], &ID).0
+}
+
+/// Decode raw claims feed channel data (including the discriminator)
+pub fn decode_notification_channel(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<NotificationChannel, ClientError> {
+    let mut data = data;
+    NotificationChannel::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the liveness PDA of a registered attester
+pub fn attester_status_address(attester: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ATTESTER_SEED, attester.as_ref()], &ID).0
+}
+
+/// Derive the rotation link PDA of an attester key
+pub fn attester_link_address(attester: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ATTESTER_LINK_SEED, attester.as_ref()], &ID).0
+}
+
+/// Decode raw attester rotation link data (including the discriminator)
+pub fn decode_attester_link(address: &Pubkey, data: &[u8]) -> Result<AttesterLink, ClientError> {
+    let mut data = data;
+    AttesterLink::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Operator an attester key attests for
+///
+/// `link` is the account at [`attester_link_address`] of `attester`, `None`
+/// if it doesn't exist. Group attestations by this rather than by
+/// `ProofAttestation::attester` to keep an operator's history and metrics
+/// together across key rotations.
+pub fn attester_operator(attester: &Pubkey, link: Option<&AttesterLink>) -> Pubkey {
+    link.map_or(*attester, |link| link.operator)
+}
+
+/// Decode raw attester liveness data (including the discriminator)
+pub fn decode_attester_status(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<AttesterStatus, ClientError> {
+    let mut data = data;
+    AttesterStatus::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Derive the verified build record PDA
+pub fn upgrade_info_address() -> Pubkey {
+    Pubkey::find_program_address(&[UPGRADE_INFO_SEED], &ID).0
+}
+
+/// Derive the singleton webhook signing key PDA
+pub fn webhook_signer_address() -> Pubkey {
+    Pubkey::find_program_address(&[WEBHOOK_SIGNER_SEED], &ID).0
+}
+
+/// Decode raw webhook signing key data (including the discriminator)
+pub fn decode_webhook_signer(address: &Pubkey, data: &[u8]) -> Result<WebhookSigner, ClientError> {
+    let mut data = data;
+    WebhookSigner::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// The program's upgradeable loader ProgramData account
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID).0
+}
+
+/// Decode raw verified build record data (including the discriminator)
+pub fn decode_upgrade_info(address: &Pubkey, data: &[u8]) -> Result<UpgradeInfo, ClientError> {
+    let mut data = data;
+    UpgradeInfo::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Decode raw cross-chain link data (including the discriminator)
+pub fn decode_foreign_link(address: &Pubkey, data: &[u8]) -> Result<ForeignLink, ClientError> {
+    let mut data = data;
+    ForeignLink::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Decode raw annotation list data (including the discriminator)
+pub fn decode_annotations(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<AttestationAnnotations, ClientError> {
+    let mut data = data;
+    AttestationAnnotations::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Decode raw attestation account data (including the discriminator)
+pub fn decode_attestation(address: &Pubkey, data: &[u8]) -> Result<ProofAttestation, ClientError> {
+    let mut data = data;
+    ProofAttestation::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Decode raw proof archive location data (including the discriminator)
+pub fn decode_proof_uri(address: &Pubkey, data: &[u8]) -> Result<ProofUri, ClientError> {
+    let mut data = data;
+    ProofUri::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Walk the chain of related claims starting at `claim_id`
+///
+/// Yields the starting attestation first, then each `related_claim_id` in
+/// turn until a record has no link. `fetch` returns the raw account data
+/// for an address, or `None` if the account does not exist (e.g., it was
+/// archived), which ends the walk with `ClientError::AccountNotFound`.
+pub fn related_claims<F>(claim_id: [u8; 32], fetch: F) -> RelatedClaims<F>
+where
+    F: FnMut(&Pubkey) -> Result<Option<Vec<u8>>, String>,
+{
+    RelatedClaims {
+        next: Some(claim_id),
+        fetch,
+    }
+}
+
+/// Iterator returned by [`related_claims`]
+pub struct RelatedClaims<F> {
+    next: Option<[u8; 32]>,
+    fetch: F,
+}
+
+impl<F> Iterator for RelatedClaims<F>
+where
+    F: FnMut(&Pubkey) -> Result<Option<Vec<u8>>, String>,
+{
+    type Item = Result<ProofAttestation, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let claim_id = self.next.take()?;
+        let address = attestation_address(&claim_id);
+
+        let data = match (self.fetch)(&address) {
+            Ok(Some(data)) => data,
+            Ok(None) => return Some(Err(ClientError::AccountNotFound(address))),
+            Err(e) => return Some(Err(ClientError::Fetch(address, e))),
+        };
+
+        let attestation = match decode_attestation(&address, &data) {
+            Ok(attestation) => attestation,
+            Err(e) => return Some(Err(e)),
+        };
+
+        self.next = attestation.related_claim_id;
+        Some(Ok(attestation))
+    }
+}
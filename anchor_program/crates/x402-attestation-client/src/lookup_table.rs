@@ -0,0 +1,116 @@
+//! Address lookup table management for batch flows
+//!
+//! Batched attestations reference the same handful of program accounts in
+//! every instruction. Keeping them in an address lookup table and sending
+//! v0 transactions keeps batches under the legacy account-key limit.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::address_lookup_table::instruction::{
+    create_lookup_table, extend_lookup_table,
+};
+use anchor_lang::solana_program::address_lookup_table::state::AddressLookupTable;
+use anchor_lang::solana_program::address_lookup_table::AddressLookupTableAccount;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::{v0, VersionedMessage};
+use anchor_lang::solana_program::system_program;
+
+use crate::{config_address, recent_attestations_address, ClientError, ID};
+
+/// Addresses added per `ExtendLookupTable` instruction
+const EXTEND_CHUNK: usize = 20;
+
+/// Program accounts touched by every attestation, plus caller extras
+///
+/// `extra` is for deployment-specific keys such as the backend authority
+/// or token mints.
+pub fn hot_accounts(extra: &[Pubkey]) -> Vec<Pubkey> {
+    let mut accounts = vec![
+        ID,
+        config_address(),
+        recent_attestations_address(),
+        system_program::ID,
+    ];
+    for key in extra {
+        if !accounts.contains(key) {
+            accounts.push(*key);
+        }
+    }
+    accounts
+}
+
+/// Instructions creating a lookup table pre-filled with the hot accounts
+///
+/// Returns the table address and the create + extend instructions, which
+/// fit in a single transaction signed by `authority` and `payer`.
+pub fn create_hot_accounts_table(
+    authority: Pubkey,
+    payer: Pubkey,
+    recent_slot: u64,
+    extra: &[Pubkey],
+) -> (Pubkey, Vec<Instruction>) {
+    let (create_ix, table) = create_lookup_table(authority, payer, recent_slot);
+
+    let mut instructions = vec![create_ix];
+    instructions.extend(extend_instructions(table, authority, payer, hot_accounts(extra)));
+    (table, instructions)
+}
+
+/// Instructions adding any hot accounts missing from an existing table
+///
+/// `table_data` is the raw lookup table account data. Returns no
+/// instructions when the table is already complete.
+pub fn sync_hot_accounts_table(
+    table: Pubkey,
+    table_data: &[u8],
+    authority: Pubkey,
+    payer: Pubkey,
+    extra: &[Pubkey],
+) -> Result<Vec<Instruction>, ClientError> {
+    let existing = decode_lookup_table(table, table_data)?;
+    let missing: Vec<Pubkey> = hot_accounts(extra)
+        .into_iter()
+        .filter(|key| !existing.addresses.contains(key))
+        .collect();
+
+    Ok(extend_instructions(table, authority, payer, missing))
+}
+
+/// Decode raw lookup table account data for message compilation
+pub fn decode_lookup_table(
+    table: Pubkey,
+    data: &[u8],
+) -> Result<AddressLookupTableAccount, ClientError> {
+    let state = AddressLookupTable::deserialize(data)
+        .map_err(|e| ClientError::LookupTable(table, e.to_string()))?;
+
+    Ok(AddressLookupTableAccount {
+        key: table,
+        addresses: state.addresses.to_vec(),
+    })
+}
+
+/// Compile instructions into a v0 message using the given lookup tables
+pub fn compile_v0_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedMessage, ClientError> {
+    let message = v0::Message::try_compile(payer, instructions, tables, recent_blockhash)
+        .map_err(|e| ClientError::Compile(e.to_string()))?;
+
+    Ok(VersionedMessage::V0(message))
+}
+
+fn extend_instructions(
+    table: Pubkey,
+    authority: Pubkey,
+    payer: Pubkey,
+    addresses: Vec<Pubkey>,
+) -> Vec<Instruction> {
+    addresses
+        .chunks(EXTEND_CHUNK)
+        .map(|chunk| extend_lookup_table(table, authority, Some(payer), chunk.to_vec()))
+        .collect()
+}
@@ -0,0 +1,137 @@
+//! Attestation layout migration tooling
+//!
+//! Sorts the program's attestation accounts by layout and batches the
+//! `scan_and_tag` / `upgrade_layout` admin instructions over them, so a
+//! migration runs as many small transactions instead of one big-bang
+//! rewrite. Progress is tracked on-chain in the `MigrationStatus` PDA.
+//!
+//! Fetch the accounts with `getProgramAccounts`, filtering on a memcmp of
+//! `ProofAttestation::DISCRIMINATOR` at offset 0.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+
+use crate::{config_address, program_stats_address, ClientError, ID};
+
+pub use x402_attestation::{
+    attestation_layout_version, MigrationStatus, ATTESTATION_LAYOUT_VERSION, MIGRATION_SEED,
+};
+
+/// Attestations per `scan_and_tag` transaction
+pub const SCAN_BATCH: usize = 24;
+
+/// Attestations per `upgrade_layout` transaction (each is reallocated)
+pub const UPGRADE_BATCH: usize = 8;
+
+/// Derive the migration status PDA
+pub fn migration_status_address() -> Pubkey {
+    Pubkey::find_program_address(&[MIGRATION_SEED], &ID).0
+}
+
+/// Decode raw migration status account data (including the discriminator)
+pub fn decode_migration_status(
+    address: &Pubkey,
+    data: &[u8],
+) -> Result<MigrationStatus, ClientError> {
+    let mut data = data;
+    MigrationStatus::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// Attestation accounts grouped for a migration, each list sorted by address
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationPlan {
+    /// Every attestation in a known layout, in scan order
+    pub attestations: Vec<Pubkey>,
+    /// Attestations in an old layout, still to be upgraded
+    pub legacy: Vec<Pubkey>,
+    /// Accounts with the attestation discriminator but an unknown size
+    pub unknown: Vec<Pubkey>,
+}
+
+/// Classify raw `(address, data)` pairs by attestation layout
+pub fn plan_migration<I>(accounts: I) -> MigrationPlan
+where
+    I: IntoIterator<Item = (Pubkey, Vec<u8>)>,
+{
+    let mut plan = MigrationPlan::default();
+    for (address, data) in accounts {
+        match attestation_layout_version(&data) {
+            Some(ATTESTATION_LAYOUT_VERSION) => plan.attestations.push(address),
+            Some(_) => {
+                plan.attestations.push(address);
+                plan.legacy.push(address);
+            }
+            None => plan.unknown.push(address),
+        }
+    }
+    plan.attestations.sort();
+    plan.legacy.sort();
+    plan.unknown.sort();
+    plan
+}
+
+/// `scan_and_tag` instructions covering every planned attestation, in order
+///
+/// With `restart` the first batch resets the on-chain counters. To resume
+/// an interrupted scan, drop the addresses at or below
+/// `MigrationStatus::last_scanned` and pass `restart: false`.
+pub fn scan_instructions(admin: &Pubkey, attestations: &[Pubkey], restart: bool) -> Vec<Instruction> {
+    attestations
+        .chunks(SCAN_BATCH)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let accounts = x402_attestation::accounts::ScanAndTag {
+                config: config_address(),
+                migration_status: migration_status_address(),
+                program_stats: program_stats_address(),
+                admin: *admin,
+                system_program: system_program::ID,
+            };
+            let data = x402_attestation::instruction::ScanAndTag {
+                restart: restart && i == 0,
+            };
+            batch_instruction(accounts.to_account_metas(None), data.data(), chunk, false)
+        })
+        .collect()
+}
+
+/// `upgrade_layout` instructions covering the given legacy attestations
+pub fn upgrade_instructions(admin: &Pubkey, legacy: &[Pubkey]) -> Vec<Instruction> {
+    legacy
+        .chunks(UPGRADE_BATCH)
+        .map(|chunk| {
+            let accounts = x402_attestation::accounts::UpgradeLayout {
+                config: config_address(),
+                migration_status: migration_status_address(),
+                program_stats: program_stats_address(),
+                admin: *admin,
+                system_program: system_program::ID,
+            };
+            let data = x402_attestation::instruction::UpgradeLayout {};
+            batch_instruction(accounts.to_account_metas(None), data.data(), chunk, true)
+        })
+        .collect()
+}
+
+fn batch_instruction(
+    mut accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+    batch: &[Pubkey],
+    writable: bool,
+) -> Instruction {
+    accounts.extend(batch.iter().map(|key| {
+        if writable {
+            AccountMeta::new(*key, false)
+        } else {
+            AccountMeta::new_readonly(*key, false)
+        }
+    }));
+
+    Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    }
+}
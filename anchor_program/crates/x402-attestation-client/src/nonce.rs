@@ -0,0 +1,305 @@
+//! Durable nonce submission
+//!
+//! A transaction built against a durable nonce instead of a recent
+//! blockhash stays valid until the nonce is advanced, so queued
+//! attestations can be signed long before they are sent. Each nonce
+//! transaction advances its nonce first; once one lands, every other
+//! transaction signed against the same value is rejected, so a delayed
+//! resend can never attest twice.
+//!
+//! The program only accepts attestations advancing a nonce account of the
+//! `NonceRegistry`; build them with `AttestArgs::durable_nonce` set.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::{v0, VersionedMessage};
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+
+use crate::{config_address, ClientError, ID};
+
+pub use x402_attestation::{
+    is_initialized_nonce, NonceRegistry, MAX_NONCE_ACCOUNTS, NONCE_ACCOUNT_LEN,
+    NONCE_REGISTRY_SEED,
+};
+
+/// Decoded system program nonce account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceAccount {
+    /// Key allowed to advance the nonce
+    pub authority: Pubkey,
+    /// Value to use as the transaction's recent blockhash
+    pub blockhash: Hash,
+    pub lamports_per_signature: u64,
+}
+
+/// Decode raw nonce account data
+pub fn decode_nonce_account(address: &Pubkey, data: &[u8]) -> Result<NonceAccount, ClientError> {
+    if data.len() != NONCE_ACCOUNT_LEN || !is_initialized_nonce(data) {
+        return Err(ClientError::InvalidNonceAccount(*address));
+    }
+    let array = |range: std::ops::Range<usize>| -> [u8; 32] {
+        data[range].try_into().expect("range is 32 bytes")
+    };
+
+    Ok(NonceAccount {
+        authority: Pubkey::new_from_array(array(8..40)),
+        blockhash: Hash::new_from_array(array(40..72)),
+        lamports_per_signature: u64::from_le_bytes(
+            data[72..80].try_into().expect("range is 8 bytes"),
+        ),
+    })
+}
+
+/// Derive the nonce registry PDA
+pub fn nonce_registry_address() -> Pubkey {
+    Pubkey::find_program_address(&[NONCE_REGISTRY_SEED], &ID).0
+}
+
+/// Decode raw nonce registry account data (including the discriminator)
+pub fn decode_nonce_registry(address: &Pubkey, data: &[u8]) -> Result<NonceRegistry, ClientError> {
+    let mut data = data;
+    NonceRegistry::try_deserialize(&mut data).map_err(|e| ClientError::Decode(*address, e))
+}
+
+/// `register_nonce_account`, signed by the config admin
+pub fn register_nonce_account(admin: &Pubkey, nonce_account: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::RegisterNonceAccount {
+        config: config_address(),
+        nonce_registry: nonce_registry_address(),
+        nonce_account: *nonce_account,
+        admin: *admin,
+        system_program: anchor_lang::solana_program::system_program::ID,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: x402_attestation::instruction::RegisterNonceAccount {}.data(),
+    }
+}
+
+/// `remove_nonce_account`, signed by the config admin
+pub fn remove_nonce_account(admin: &Pubkey, nonce_account: &Pubkey) -> Instruction {
+    let accounts = x402_attestation::accounts::RemoveNonceAccount {
+        config: config_address(),
+        nonce_registry: nonce_registry_address(),
+        admin: *admin,
+    };
+    let data = x402_attestation::instruction::RemoveNonceAccount {
+        nonce_account: *nonce_account,
+    };
+
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Compile a message that uses `nonce` as its recent blockhash
+///
+/// `advance_nonce_account` is prepended, as the runtime requires, so
+/// `nonce_authority` must sign alongside the payer.
+pub fn durable_message(
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+    nonce: Hash,
+    instructions: &[Instruction],
+) -> Result<VersionedMessage, ClientError> {
+    let mut all = Vec::with_capacity(instructions.len() + 1);
+    all.push(system_instruction::advance_nonce_account(nonce_account, nonce_authority));
+    all.extend_from_slice(instructions);
+
+    let message = v0::Message::try_compile(payer, &all, &[], nonce)
+        .map_err(|e| ClientError::Compile(e.to_string()))?;
+    Ok(VersionedMessage::V0(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::hash::hashv;
+    use anchor_lang::solana_program::system_program;
+
+    use super::*;
+    use crate::attestation_address;
+    use crate::sender::{
+        BlockhashSource, Commitment, RpcEndpoint, SendConfig, SendOutcome, Sender,
+        SignedTransaction,
+    };
+
+    fn key(byte: u8) -> Pubkey {
+        Pubkey::new_from_array([byte; 32])
+    }
+
+    fn nonce_data(authority: &Pubkey, nonce: &Hash) -> Vec<u8> {
+        let mut data = Vec::with_capacity(NONCE_ACCOUNT_LEN);
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(nonce.as_ref());
+        data.extend_from_slice(&5_000u64.to_le_bytes());
+        data
+    }
+
+    fn memo(byte: u8) -> Instruction {
+        Instruction::new_with_bytes(key(9), &[byte], vec![])
+    }
+
+    /// Deterministic stand-in for Ed25519 signing: same message, same signature
+    fn sign(message: &VersionedMessage) -> Result<SignedTransaction, String> {
+        let wire = message.serialize();
+        let digest = hashv(&[&wire]).to_bytes();
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&digest);
+        signature[32..].copy_from_slice(&digest);
+        Ok(SignedTransaction { signature, wire })
+    }
+
+    /// Endpoint whose nonce advances, and attestation appears, once the
+    /// `lands_on`-th submission is processed
+    struct NonceRpc {
+        nonce: Hash,
+        sent: Vec<Vec<u8>>,
+        lands_on: usize,
+    }
+
+    impl NonceRpc {
+        fn landed(&self) -> bool {
+            self.sent.len() >= self.lands_on
+        }
+    }
+
+    impl RpcEndpoint for NonceRpc {
+        fn latest_blockhash(&mut self, _: Commitment) -> Result<Hash, String> {
+            Err("durable nonce sender must not use recent blockhashes".into())
+        }
+
+        fn nonce_blockhash(&mut self, _: &Pubkey, _: Commitment) -> Result<Hash, String> {
+            Ok(self.nonce)
+        }
+
+        fn account_exists(&mut self, _: &Pubkey, _: Commitment) -> Result<bool, String> {
+            Ok(self.landed())
+        }
+
+        fn send_transaction(&mut self, wire: &[u8]) -> Result<(), String> {
+            self.sent.push(wire.to_vec());
+            if self.landed() {
+                self.nonce = hashv(&[self.nonce.as_ref()]);
+            }
+            Ok(())
+        }
+
+        fn signature_status(
+            &mut self,
+            _: &[u8; 64],
+            _: Commitment,
+        ) -> Result<Option<Result<(), String>>, String> {
+            Ok(None)
+        }
+
+        fn recent_prioritization_fees(&mut self, _: &[Pubkey]) -> Result<Vec<u64>, String> {
+            Ok(vec![])
+        }
+    }
+
+    fn nonce_sender(rpc: NonceRpc) -> Sender<NonceRpc> {
+        Sender::new(
+            vec![rpc],
+            SendConfig {
+                blockhash: BlockhashSource::DurableNonce {
+                    account: key(2),
+                    authority: key(3),
+                },
+                confirm_polls: 1,
+                max_attempts: 3,
+                ..SendConfig::default()
+            },
+        )
+    }
+
+    #[test]
+    fn decodes_initialized_nonce_account() {
+        let nonce = Hash::new_from_array([7; 32]);
+        let decoded = decode_nonce_account(&key(2), &nonce_data(&key(3), &nonce)).unwrap();
+
+        assert_eq!(decoded.authority, key(3));
+        assert_eq!(decoded.blockhash, nonce);
+        assert_eq!(decoded.lamports_per_signature, 5_000);
+    }
+
+    #[test]
+    fn rejects_uninitialized_nonce_account() {
+        let mut data = nonce_data(&key(3), &Hash::default());
+        data[4..8].copy_from_slice(&0u32.to_le_bytes());
+
+        assert!(matches!(
+            decode_nonce_account(&key(2), &data),
+            Err(ClientError::InvalidNonceAccount(_))
+        ));
+    }
+
+    #[test]
+    fn durable_message_advances_nonce_first() {
+        let nonce = Hash::new_from_array([7; 32]);
+        let message = durable_message(&key(1), &key(2), &key(3), nonce, &[memo(0)]).unwrap();
+
+        let VersionedMessage::V0(message) = message else {
+            panic!("expected a v0 message");
+        };
+        assert_eq!(message.recent_blockhash, nonce);
+        let first = &message.instructions[0];
+        assert_eq!(
+            message.account_keys[usize::from(first.program_id_index)],
+            system_program::ID
+        );
+        assert_eq!(first.data[..4], 4u32.to_le_bytes(), "AdvanceNonceAccount");
+    }
+
+    #[test]
+    fn resend_against_same_nonce_is_the_same_transaction() {
+        let nonce = Hash::new_from_array([7; 32]);
+        let build = || durable_message(&key(1), &key(2), &key(3), nonce, &[memo(0)]).unwrap();
+
+        assert_eq!(sign(&build()).unwrap().wire, sign(&build()).unwrap().wire);
+    }
+
+    #[test]
+    fn delayed_resend_is_rebroadcast_not_replayed() {
+        let mut sender = nonce_sender(NonceRpc {
+            nonce: Hash::new_from_array([7; 32]),
+            sent: vec![],
+            lands_on: 2,
+        });
+        let outcome = sender
+            .send(&key(1), &[memo(0)], &attestation_address(&[0; 32]), sign)
+            .unwrap();
+
+        assert_eq!(outcome, SendOutcome::AlreadyLanded);
+        let sent = &sender_endpoint(&sender).sent;
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0], sent[1], "unconfirmed retry must reuse the nonce");
+    }
+
+    #[test]
+    fn landed_attestation_is_not_sent_again() {
+        let mut sender = nonce_sender(NonceRpc {
+            nonce: Hash::new_from_array([7; 32]),
+            sent: vec![],
+            lands_on: 1,
+        });
+        let outcome = sender
+            .send(&key(1), &[memo(0)], &attestation_address(&[0; 32]), sign)
+            .unwrap();
+
+        assert_eq!(outcome, SendOutcome::AlreadyLanded);
+        assert_eq!(sender_endpoint(&sender).sent.len(), 1);
+    }
+
+    fn sender_endpoint(sender: &Sender<NonceRpc>) -> &NonceRpc {
+        &sender.endpoints()[0]
+    }
+}
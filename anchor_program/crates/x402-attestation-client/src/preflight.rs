@@ -0,0 +1,147 @@
+//! Simulation-first preflight checks
+//!
+//! Runs an attestation through the caller's `simulateTransaction` and turns
+//! the raw result into a [`PreflightReport`], so support tooling can see
+//! whether (and why) an attestation would fail before paying priority fees.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+
+use crate::instructions::{self, AttestArgs};
+use crate::ID;
+
+/// Base fee charged per transaction signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Raw simulation result, as returned by the caller's RPC client
+#[derive(Debug, Clone, Default)]
+pub struct Simulation {
+    /// Transaction error string, `None` on success
+    pub err: Option<String>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+}
+
+/// Fee inputs for the estimate
+#[derive(Debug, Clone, Copy)]
+pub struct FeeParams {
+    pub signatures: u64,
+    /// Compute unit price in micro-lamports (ComputeBudget `SetComputeUnitPrice`)
+    pub compute_unit_price: u64,
+}
+
+impl Default for FeeParams {
+    fn default() -> Self {
+        FeeParams {
+            signatures: 1,
+            compute_unit_price: 0,
+        }
+    }
+}
+
+/// Error decoded from a failed simulation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightError {
+    /// Program error number (Anchor framework or this program's `ErrorCode`)
+    pub code: Option<u32>,
+    /// Error name, e.g. `HashAlgoNotAllowed`, when the program logged one
+    pub name: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightReport {
+    pub would_succeed: bool,
+    pub error: Option<PreflightError>,
+    pub estimated_compute_units: Option<u64>,
+    /// Base fee plus priority fee on the simulated compute units
+    pub estimated_fee_lamports: u64,
+}
+
+/// Simulate `attest_claim_proof` and report the outcome
+///
+/// `simulate` runs the instruction through `simulateTransaction` on the
+/// caller's RPC client (with `sigVerify: false`).
+pub fn preflight_attest<F, E>(
+    authority: &Pubkey,
+    args: AttestArgs,
+    fees: FeeParams,
+    simulate: F,
+) -> Result<PreflightReport, E>
+where
+    F: FnOnce(&Instruction) -> Result<Simulation, E>,
+{
+    let ix = instructions::attest_claim_proof(authority, args);
+    let simulation = simulate(&ix)?;
+    Ok(analyze_simulation(&simulation, fees))
+}
+
+/// Build a report from a raw simulation result
+pub fn analyze_simulation(simulation: &Simulation, fees: FeeParams) -> PreflightReport {
+    let estimated_compute_units = simulation
+        .units_consumed
+        .or_else(|| program_units_consumed(&simulation.logs));
+
+    let priority_fee = estimated_compute_units
+        .map(|units| {
+            (u128::from(units) * u128::from(fees.compute_unit_price)).div_ceil(1_000_000) as u64
+        })
+        .unwrap_or(0);
+
+    let error = simulation.err.as_ref().map(|err| {
+        decode_anchor_error(&simulation.logs).unwrap_or_else(|| PreflightError {
+            code: decode_custom_error(err),
+            name: None,
+            message: err.clone(),
+        })
+    });
+
+    PreflightReport {
+        would_succeed: simulation.err.is_none(),
+        error,
+        estimated_compute_units,
+        estimated_fee_lamports: fees
+            .signatures
+            .saturating_mul(LAMPORTS_PER_SIGNATURE)
+            .saturating_add(priority_fee),
+    }
+}
+
+/// Parse the last `AnchorError ... Error Code: X. Error Number: N. Error Message: M.` log
+fn decode_anchor_error(logs: &[String]) -> Option<PreflightError> {
+    logs.iter().rev().find_map(|line| {
+        let rest = &line[line.find("AnchorError")?..];
+        let name = between(rest, "Error Code: ", ". Error Number: ")?;
+        let number = between(rest, "Error Number: ", ". Error Message: ")?;
+        let message = rest[rest.find("Error Message: ")? + "Error Message: ".len()..]
+            .trim_end_matches('.');
+
+        Some(PreflightError {
+            code: number.parse().ok(),
+            name: Some(name.to_string()),
+            message: message.to_string(),
+        })
+    })
+}
+
+/// Parse `custom program error: 0x1771` out of a transaction error string
+fn decode_custom_error(err: &str) -> Option<u32> {
+    let hex = &err[err.find("custom program error: 0x")? + "custom program error: 0x".len()..];
+    let end = hex.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(hex.len());
+    u32::from_str_radix(&hex[..end], 16).ok()
+}
+
+/// Units from `Program <ID> consumed N of M compute units`
+pub(crate) fn program_units_consumed(logs: &[String]) -> Option<u64> {
+    let prefix = format!("Program {} consumed ", ID);
+    logs.iter().rev().find_map(|line| {
+        let rest = line.strip_prefix(&prefix)?;
+        rest.split_whitespace().next()?.parse().ok()
+    })
+}
+
+fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let from = s.find(start)? + start.len();
+    let to = from + s[from..].find(end)?;
+    Some(&s[from..to])
+}
@@ -0,0 +1,286 @@
+//! Signed attestation receipts for off-chain verification
+//!
+//! A receipt is the attester's Ed25519 signature over an attestation's
+//! canonical digest plus the slot it was read at. Merchants holding the
+//! attester's public key can check a receipt without any RPC round trip.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+use ed25519_dalek::{PublicKey, Signature};
+
+use crate::{attestation_address, ProofAttestation};
+
+/// Domain tag for the signed receipt message
+pub const RECEIPT_DOMAIN: &[u8] = b"x402-receipt:v1";
+
+/// Encoded receipt size in bytes
+pub const RECEIPT_LEN: usize = 32 + 32 + 8 + 32 + 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ReceiptError {
+    #[error("receipt must be {RECEIPT_LEN} bytes, got {0}")]
+    InvalidLength(usize),
+
+    #[error("receipt signature is invalid")]
+    InvalidSignature,
+
+    #[error("receipt does not match the attestation record")]
+    RecordMismatch,
+}
+
+/// An Ed25519 key that signs the receipts, credentials, webhooks and
+/// bundles of this crate
+///
+/// Implemented for closures returning the signature, so a Solana
+/// `Keypair` signs with `|msg: &[u8]| keypair.sign_message(msg).into()`;
+/// remote signers (HSM, KMS) implement it directly.
+pub trait Ed25519Signer {
+    fn sign(self, message: &[u8]) -> [u8; 64];
+}
+
+impl<F> Ed25519Signer for F
+where
+    F: FnOnce(&[u8]) -> [u8; 64],
+{
+    fn sign(self, message: &[u8]) -> [u8; 64] {
+        self(message)
+    }
+}
+
+/// Compact signed receipt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedReceipt {
+    /// Attestation PDA the receipt is for
+    pub attestation: Pubkey,
+    /// `ProofAttestation::canonical_digest` of the record
+    pub digest: [u8; 32],
+    /// Slot at which the attester read the record
+    pub slot: u64,
+    pub attester: Pubkey,
+    pub signature: [u8; 64],
+}
+
+impl SignedReceipt {
+    /// Bytes the attester signs
+    pub fn message(&self) -> [u8; 32] {
+        receipt_message(&self.attestation, &self.digest, self.slot)
+    }
+
+    /// Fixed-size encoding: attestation ‖ digest ‖ slot (LE) ‖ attester ‖ signature
+    pub fn to_bytes(&self) -> [u8; RECEIPT_LEN] {
+        let mut out = [0u8; RECEIPT_LEN];
+        out[..32].copy_from_slice(self.attestation.as_ref());
+        out[32..64].copy_from_slice(&self.digest);
+        out[64..72].copy_from_slice(&self.slot.to_le_bytes());
+        out[72..104].copy_from_slice(self.attester.as_ref());
+        out[104..].copy_from_slice(&self.signature);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<SignedReceipt, ReceiptError> {
+        if bytes.len() != RECEIPT_LEN {
+            return Err(ReceiptError::InvalidLength(bytes.len()));
+        }
+        let array = |range: std::ops::Range<usize>| -> [u8; 32] {
+            bytes[range].try_into().expect("range is 32 bytes")
+        };
+
+        Ok(SignedReceipt {
+            attestation: Pubkey::new_from_array(array(0..32)),
+            digest: array(32..64),
+            slot: u64::from_le_bytes(bytes[64..72].try_into().expect("range is 8 bytes")),
+            attester: Pubkey::new_from_array(array(72..104)),
+            signature: bytes[104..].try_into().expect("range is 64 bytes"),
+        })
+    }
+}
+
+fn receipt_message(attestation: &Pubkey, digest: &[u8; 32], slot: u64) -> [u8; 32] {
+    hashv(&[RECEIPT_DOMAIN, attestation.as_ref(), digest, &slot.to_le_bytes()]).to_bytes()
+}
+
+/// Produce a receipt for an attestation read at `slot`, signed by its attester
+pub fn sign_receipt(
+    attestation: &ProofAttestation,
+    slot: u64,
+    signer: impl Ed25519Signer,
+) -> SignedReceipt {
+    let address = attestation_address(&attestation.claim_id);
+    let digest = attestation.canonical_digest();
+    let signature = signer.sign(&receipt_message(&address, &digest, slot));
+
+    SignedReceipt {
+        attestation: address,
+        digest,
+        slot,
+        attester: attestation.attester,
+        signature,
+    }
+}
+
+/// Verify a receipt's signature, and its binding to `record` if given
+///
+/// Without a record this proves the attester vouched for `digest` at
+/// `slot`; with one it also proves the digest and attester match it.
+pub fn verify_receipt(
+    receipt: &SignedReceipt,
+    record: Option<&ProofAttestation>,
+) -> Result<(), ReceiptError> {
+    if let Some(record) = record {
+        if receipt.attestation != attestation_address(&record.claim_id)
+            || receipt.digest != record.canonical_digest()
+            || receipt.attester != record.attester
+        {
+            return Err(ReceiptError::RecordMismatch);
+        }
+    }
+
+    if verify_ed25519(&receipt.attester, &receipt.message(), &receipt.signature) {
+        Ok(())
+    } else {
+        Err(ReceiptError::InvalidSignature)
+    }
+}
+
+/// Strict RFC 8032 Ed25519 verification, rejecting small-order keys and
+/// non-canonical signatures
+pub(crate) fn verify_ed25519(public_key: &Pubkey, message: &[u8], signature: &[u8; 64]) -> bool {
+    let (Ok(public_key), Ok(signature)) = (
+        PublicKey::from_bytes(public_key.as_ref()),
+        Signature::from_bytes(signature),
+    ) else {
+        return false;
+    };
+    public_key.verify_strict(message, &signature).is_ok()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use ed25519_dalek::{ExpandedSecretKey, SecretKey};
+
+    use super::*;
+    use crate::fixtures::sample_attestation;
+
+    /// RFC 8032 key pair from a 32-byte seed, and a signer for it
+    pub(crate) fn keypair(seed: [u8; 32]) -> (Pubkey, impl Fn(&[u8]) -> [u8; 64]) {
+        let secret = SecretKey::from_bytes(&seed).unwrap();
+        let public = PublicKey::from(&secret);
+        let expanded = ExpandedSecretKey::from(&secret);
+        let sign = move |message: &[u8]| expanded.sign(message, &public).to_bytes();
+        (Pubkey::new_from_array(public.to_bytes()), sign)
+    }
+
+    fn unhex(value: &str) -> Vec<u8> {
+        (0..value.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn rfc8032_test_vectors_verify() {
+        // RFC 8032 §7.1, tests 1 to 3: secret key, public key, message, signature
+        let vectors = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            ),
+            (
+                "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+                "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+                "af82",
+                "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+            ),
+        ];
+
+        for (secret, public, message, signature) in vectors {
+            let public = Pubkey::try_from(unhex(public).as_slice()).unwrap();
+            let message = unhex(message);
+            let signature: [u8; 64] = unhex(signature).try_into().unwrap();
+            assert!(verify_ed25519(&public, &message, &signature));
+
+            let (derived, sign) = keypair(unhex(secret).try_into().unwrap());
+            assert_eq!(derived, public);
+            assert_eq!(sign(&message), signature);
+
+            let mut tampered = message.clone();
+            tampered.push(0);
+            assert!(!verify_ed25519(&public, &tampered, &signature));
+            let mut forged = signature;
+            forged[0] ^= 1;
+            assert!(!verify_ed25519(&public, &message, &forged));
+
+            // S + l satisfies the same equation but isn't canonical
+            let mut malleable = signature;
+            let mut carry = 0u16;
+            for (byte, order) in malleable[32..].iter_mut().zip(ED25519_ORDER) {
+                let sum = u16::from(*byte) + u16::from(order) + carry;
+                *byte = sum as u8;
+                carry = sum >> 8;
+            }
+            assert!(!verify_ed25519(&public, &message, &malleable));
+        }
+
+        // A small-order key is refused whatever the signature
+        let mut identity = [0; 32];
+        identity[0] = 1;
+        let identity = Pubkey::new_from_array(identity);
+        assert!(!verify_ed25519(&identity, b"", &[0; 64]));
+    }
+
+    /// Group order l, little-endian
+    const ED25519_ORDER: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10,
+    ];
+
+    #[test]
+    fn receipts_sign_verify_and_reject_tampering() {
+        let (attester, sign) = keypair([7; 32]);
+        let record = sample_attestation(1, attester, 1_760_000_000);
+
+        let receipt = sign_receipt(&record, 42, &sign);
+        assert_eq!(verify_receipt(&receipt, Some(&record)), Ok(()));
+        assert_eq!(verify_receipt(&receipt, None), Ok(()));
+        assert_eq!(SignedReceipt::from_bytes(&receipt.to_bytes()), Ok(receipt));
+        assert_eq!(
+            SignedReceipt::from_bytes(&receipt.to_bytes()[1..]),
+            Err(ReceiptError::InvalidLength(RECEIPT_LEN - 1))
+        );
+
+        let mut later = receipt;
+        later.slot += 1;
+        assert_eq!(
+            verify_receipt(&later, None),
+            Err(ReceiptError::InvalidSignature)
+        );
+
+        let mut changed = record.clone();
+        changed.imported = true;
+        assert_eq!(
+            verify_receipt(&receipt, Some(&changed)),
+            Err(ReceiptError::RecordMismatch)
+        );
+
+        let (other, other_sign) = keypair([8; 32]);
+        let mut forged = sign_receipt(&record, 42, other_sign);
+        assert_eq!(
+            verify_receipt(&forged, Some(&record)),
+            Err(ReceiptError::InvalidSignature)
+        );
+        forged.attester = other;
+        assert_eq!(
+            verify_receipt(&forged, Some(&record)),
+            Err(ReceiptError::RecordMismatch)
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! Rounding policy for premium, fee and payout math
+//!
+//! Micro-USDC is the currency of record: every amount the program stores
+//! and every amount charged or paid out is a whole number of micro-USDC.
+//! Rates, splits and USDC strings with more precision are rounded to it
+//! here, by a [`RoundingPolicy`] the backend and SDK share, so both sides
+//! agree to the unit. Whatever rounding leaves over in a split is reported
+//! as dust for the treasury rather than silently dropped.
+
+use serde::{Deserialize, Serialize};
+
+use crate::amount::{AmountError, MicroUsdc, UsdcAmount};
+
+/// Basis points per whole (100% = 10,000 bps)
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+const DECIMALS: usize = 6;
+
+/// How a quotient is rounded to a whole micro-USDC
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// Toward zero, as the backend's `to_micro` (`ROUND_DOWN`) does
+    #[default]
+    Floor,
+    /// Away from zero
+    Ceil,
+    /// To nearest, ties to even (banker's rounding)
+    HalfEven,
+}
+
+impl Rounding {
+    /// `numerator / denominator`, rounded; `None` if `denominator` is zero
+    pub fn divide(self, numerator: u128, denominator: u128) -> Option<u128> {
+        if denominator == 0 {
+            return None;
+        }
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        let round_up = match self {
+            Rounding::Floor => false,
+            Rounding::Ceil => remainder != 0,
+            // remainder vs. denominator / 2, without doubling the remainder
+            Rounding::HalfEven => match remainder.cmp(&(denominator - remainder)) {
+                std::cmp::Ordering::Less => false,
+                std::cmp::Ordering::Greater => true,
+                std::cmp::Ordering::Equal => quotient % 2 == 1,
+            },
+        };
+        // a remainder implies denominator > 1, so this cannot overflow
+        Some(quotient + u128::from(round_up))
+    }
+
+    /// Round the digits after the sixth decimal of a USDC amount
+    ///
+    /// `base` is the amount truncated to six decimals, `rest` the remaining
+    /// decimal digits.
+    fn round_digits(self, base: u64, rest: &str) -> Option<u64> {
+        let nonzero = rest.bytes().any(|b| b != b'0');
+        let round_up = match self {
+            Rounding::Floor => false,
+            Rounding::Ceil => nonzero,
+            Rounding::HalfEven => match rest.as_bytes().first() {
+                None => false,
+                Some(b'5') if rest[1..].bytes().all(|b| b == b'0') => base % 2 == 1,
+                Some(digit) => *digit >= b'5',
+            },
+        };
+        base.checked_add(u64::from(round_up))
+    }
+}
+
+impl MicroUsdc {
+    /// `self × numerator / denominator`, rounded
+    ///
+    /// Computed in 128 bits, so only the result can overflow. `None` on
+    /// overflow or a zero denominator.
+    pub fn mul_div(
+        self,
+        numerator: u64,
+        denominator: u64,
+        rounding: Rounding,
+    ) -> Option<MicroUsdc> {
+        let product = u128::from(self.0) * u128::from(numerator);
+        let quotient = rounding.divide(product, u128::from(denominator))?;
+        u64::try_from(quotient).ok().map(MicroUsdc)
+    }
+
+    /// `bps` basis points of `self`, rounded
+    pub fn bps(self, bps: u64, rounding: Rounding) -> Option<MicroUsdc> {
+        self.mul_div(bps, BPS_DENOMINATOR, rounding)
+    }
+}
+
+impl UsdcAmount {
+    /// Parse a decimal USDC string, rounding past the sixth decimal
+    ///
+    /// Unlike [`str::parse`], which rejects such strings, this accepts any
+    /// number of decimals, e.g., a premium computed as coverage × rate.
+    pub fn parse_rounded(s: &str, rounding: Rounding) -> Result<UsdcAmount, AmountError> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if fraction.len() <= DECIMALS {
+            return s.parse();
+        }
+        let (kept, rest) = fraction.split_at(DECIMALS);
+        if !rest.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AmountError::InvalidFormat(s.to_string()));
+        }
+
+        let base: UsdcAmount = format!("{whole}.{kept}")
+            .parse()
+            .map_err(|error| match error {
+                AmountError::InvalidFormat(_) => AmountError::InvalidFormat(s.to_string()),
+                other => other,
+            })?;
+        rounding
+            .round_digits(base.to_micro().0, rest)
+            .map(|micro| UsdcAmount::from(MicroUsdc(micro)))
+            .ok_or(AmountError::Overflow)
+    }
+}
+
+/// A fee taken out of a gross amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub fee: MicroUsdc,
+    /// `gross - fee`; the two always add back to the gross amount
+    pub net: MicroUsdc,
+}
+
+/// An amount divided into weighted shares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allocation {
+    /// One share per weight, each rounded down
+    pub shares: Vec<MicroUsdc>,
+    /// What rounding left unallocated, to sweep to the treasury
+    pub dust: MicroUsdc,
+}
+
+/// Rounding applied to each kind of amount
+///
+/// The default rounds everything down, matching the backend's `to_micro`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoundingPolicy {
+    pub premium: Rounding,
+    pub fee: Rounding,
+    pub payout: Rounding,
+}
+
+impl RoundingPolicy {
+    /// Premium of `rate_bps` on `coverage`
+    pub fn premium(&self, coverage: MicroUsdc, rate_bps: u64) -> Option<MicroUsdc> {
+        coverage.bps(rate_bps, self.premium)
+    }
+
+    /// Fee of `fee_bps` out of `gross`; `None` above 100%
+    pub fn fee(&self, gross: MicroUsdc, fee_bps: u64) -> Option<FeeSplit> {
+        if fee_bps > BPS_DENOMINATOR {
+            return None;
+        }
+        let fee = gross.bps(fee_bps, self.fee)?;
+        Some(FeeSplit {
+            fee,
+            net: gross.checked_sub(fee)?,
+        })
+    }
+
+    /// Payout of `numerator / denominator` of `coverage`, e.g., a partial refund
+    pub fn payout(
+        &self,
+        coverage: MicroUsdc,
+        numerator: u64,
+        denominator: u64,
+    ) -> Option<MicroUsdc> {
+        coverage.mul_div(numerator, denominator, self.payout)
+    }
+}
+
+/// Divide `total` by `weights`, rounding every share down
+///
+/// Shares never add up to more than `total`; the difference is the dust.
+/// `None` if the weights are empty or all zero.
+pub fn allocate(total: MicroUsdc, weights: &[u64]) -> Option<Allocation> {
+    let weight_sum: u128 = weights.iter().map(|weight| u128::from(*weight)).sum();
+    if weight_sum == 0 {
+        return None;
+    }
+    let shares: Vec<MicroUsdc> = weights
+        .iter()
+        .map(|weight| {
+            let share =
+                Rounding::Floor.divide(u128::from(total.0) * u128::from(*weight), weight_sum)?;
+            u64::try_from(share).ok().map(MicroUsdc)
+        })
+        .collect::<Option<_>>()?;
+    let allocated = shares
+        .iter()
+        .try_fold(MicroUsdc::ZERO, |sum, share| sum.checked_add(*share))?;
+
+    Some(Allocation {
+        dust: total.checked_sub(allocated)?,
+        shares,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODES: [Rounding; 3] = [Rounding::Floor, Rounding::Ceil, Rounding::HalfEven];
+
+    /// Exact reference: compares `2 × numerator` against multiples of the denominator
+    fn reference(rounding: Rounding, numerator: u128, denominator: u128) -> u128 {
+        let floor = numerator / denominator;
+        let exact = floor * denominator == numerator;
+        match rounding {
+            Rounding::Floor => floor,
+            Rounding::Ceil if exact => floor,
+            Rounding::Ceil => floor + 1,
+            Rounding::HalfEven => {
+                let twice = 2 * numerator;
+                let midpoint = (2 * floor + 1) * denominator;
+                if twice < midpoint || (twice == midpoint && floor.is_multiple_of(2)) {
+                    floor
+                } else {
+                    floor + 1
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn divide_matches_reference_exhaustively() {
+        for rounding in MODES {
+            for denominator in 1..=64u128 {
+                for numerator in 0..=1_024u128 {
+                    assert_eq!(
+                        rounding.divide(numerator, denominator),
+                        Some(reference(rounding, numerator, denominator)),
+                        "{rounding:?} {numerator}/{denominator}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn divide_by_zero_is_none() {
+        for rounding in MODES {
+            assert_eq!(rounding.divide(1, 0), None);
+            assert_eq!(rounding.divide(0, 0), None);
+        }
+    }
+
+    #[test]
+    fn divide_at_the_limits() {
+        for rounding in MODES {
+            assert_eq!(rounding.divide(u128::MAX, 1), Some(u128::MAX));
+            assert_eq!(rounding.divide(0, u128::MAX), Some(0));
+            assert_eq!(rounding.divide(u128::MAX, u128::MAX), Some(1));
+        }
+        assert_eq!(Rounding::Floor.divide(u128::MAX, 2), Some(u128::MAX / 2));
+        assert_eq!(Rounding::Ceil.divide(u128::MAX, 2), Some(u128::MAX / 2 + 1));
+        // u128::MAX / 2 = ...5 remainder 1: exactly half, rounds to the even side
+        assert_eq!(
+            Rounding::HalfEven.divide(u128::MAX, 2),
+            Some(u128::MAX / 2 + 1)
+        );
+    }
+
+    #[test]
+    fn half_even_ties() {
+        let cases = [(1, 0), (3, 2), (5, 2), (7, 4), (9, 4), (11, 6)];
+        for (halves, expected) in cases {
+            assert_eq!(
+                Rounding::HalfEven.divide(halves, 2),
+                Some(expected),
+                "{halves}/2"
+            );
+        }
+    }
+
+    #[test]
+    fn mul_div_and_bps() {
+        let amount = MicroUsdc(12_345);
+        assert_eq!(amount.bps(100, Rounding::Floor), Some(MicroUsdc(123)));
+        assert_eq!(amount.bps(100, Rounding::Ceil), Some(MicroUsdc(124)));
+        assert_eq!(amount.bps(100, Rounding::HalfEven), Some(MicroUsdc(123)));
+        assert_eq!(
+            MicroUsdc(12_350).bps(100, Rounding::HalfEven),
+            Some(MicroUsdc(124))
+        );
+        assert_eq!(
+            MicroUsdc(12_250).bps(100, Rounding::HalfEven),
+            Some(MicroUsdc(122))
+        );
+
+        // the intermediate product may exceed u64, the result may not
+        assert_eq!(
+            MicroUsdc(u64::MAX).mul_div(u64::MAX, u64::MAX, Rounding::Floor),
+            Some(MicroUsdc(u64::MAX))
+        );
+        assert_eq!(MicroUsdc(u64::MAX).mul_div(2, 1, Rounding::Floor), None);
+        assert_eq!(MicroUsdc(1).mul_div(1, 0, Rounding::Floor), None);
+    }
+
+    #[test]
+    fn mul_div_matches_reference_exhaustively() {
+        for rounding in MODES {
+            for amount in 0..=300u64 {
+                for numerator in 0..=12u64 {
+                    for denominator in 1..=12u64 {
+                        let expected = reference(
+                            rounding,
+                            u128::from(amount * numerator),
+                            u128::from(denominator),
+                        );
+                        assert_eq!(
+                            MicroUsdc(amount).mul_div(numerator, denominator, rounding),
+                            Some(MicroUsdc(expected as u64))
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fee_split_always_adds_up() {
+        for rounding in MODES {
+            let policy = RoundingPolicy {
+                fee: rounding,
+                ..RoundingPolicy::default()
+            };
+            for gross in 0..=2_000u64 {
+                for fee_bps in [0, 1, 25, 99, 100, 250, 3_333, 5_000, 9_999, 10_000] {
+                    let split = policy.fee(MicroUsdc(gross), fee_bps).unwrap();
+                    assert_eq!(split.fee.0 + split.net.0, gross);
+                    assert!(split.fee.0 <= gross);
+                }
+            }
+        }
+        assert_eq!(RoundingPolicy::default().fee(MicroUsdc(1), 10_001), None);
+    }
+
+    #[test]
+    fn policy_applies_per_kind() {
+        let policy = RoundingPolicy {
+            premium: Rounding::Ceil,
+            fee: Rounding::HalfEven,
+            payout: Rounding::Floor,
+        };
+        // 1% of 0.012345 USDC
+        assert_eq!(policy.premium(MicroUsdc(12_345), 100), Some(MicroUsdc(124)));
+        assert_eq!(
+            policy.fee(MicroUsdc(12_350), 100),
+            Some(FeeSplit {
+                fee: MicroUsdc(124),
+                net: MicroUsdc(12_226),
+            })
+        );
+        // two-thirds refund
+        assert_eq!(
+            policy.payout(MicroUsdc(10_000), 2, 3),
+            Some(MicroUsdc(6_666))
+        );
+    }
+
+    #[test]
+    fn default_policy_matches_backend_round_down() {
+        let policy = RoundingPolicy::default();
+        assert_eq!(
+            policy,
+            serde_json::from_str::<RoundingPolicy>("{}").unwrap()
+        );
+        assert_eq!(policy.premium(MicroUsdc(12_399), 100), Some(MicroUsdc(123)));
+        assert_eq!(
+            UsdcAmount::parse_rounded("0.00012399", policy.premium).unwrap(),
+            UsdcAmount::from(MicroUsdc(123))
+        );
+    }
+
+    #[test]
+    fn policy_serde() {
+        let policy: RoundingPolicy =
+            serde_json::from_str(r#"{"premium":"ceil","payout":"half_even"}"#).unwrap();
+        assert_eq!(
+            policy,
+            RoundingPolicy {
+                premium: Rounding::Ceil,
+                fee: Rounding::Floor,
+                payout: Rounding::HalfEven,
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&policy).unwrap(),
+            r#"{"premium":"ceil","fee":"floor","payout":"half_even"}"#
+        );
+    }
+
+    #[test]
+    fn parse_rounded_every_seventh_and_eighth_digit() {
+        for rounding in MODES {
+            for base in [0u64, 1, 2, 999_999] {
+                for extra in 0..100u128 {
+                    let s = format!("0.{base:06}{extra:02}");
+                    let expected = reference(rounding, u128::from(base) * 100 + extra, 100);
+                    assert_eq!(
+                        UsdcAmount::parse_rounded(&s, rounding).unwrap(),
+                        UsdcAmount::from(MicroUsdc(expected as u64)),
+                        "{rounding:?} {s}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parse_rounded_long_tails() {
+        let parse = |s, rounding| UsdcAmount::parse_rounded(s, rounding).unwrap().to_micro().0;
+        assert_eq!(parse("1.0000005", Rounding::HalfEven), 1_000_000);
+        assert_eq!(parse("1.0000015", Rounding::HalfEven), 1_000_002);
+        assert_eq!(parse("1.00000050000000001", Rounding::HalfEven), 1_000_001);
+        assert_eq!(parse("1.00000000000000001", Rounding::Ceil), 1_000_001);
+        assert_eq!(parse("1.99999999999999999", Rounding::Floor), 1_999_999);
+        assert_eq!(parse(".1234567", Rounding::Ceil), 123_457);
+        assert_eq!(parse("2.5", Rounding::Ceil), 2_500_000);
+    }
+
+    #[test]
+    fn parse_rounded_errors() {
+        for bad in [
+            "",
+            ".",
+            "abc",
+            "1.2345678x",
+            "1.x2345678",
+            "-1.0000001",
+            "1.2.3",
+        ] {
+            assert!(
+                matches!(
+                    UsdcAmount::parse_rounded(bad, Rounding::Floor),
+                    Err(AmountError::InvalidFormat(_))
+                ),
+                "{bad:?}"
+            );
+        }
+        let max = format!("{}.{}", u64::MAX / 1_000_000, u64::MAX % 1_000_000);
+        assert_eq!(
+            UsdcAmount::parse_rounded(&format!("{max}1"), Rounding::Floor)
+                .unwrap()
+                .to_micro(),
+            MicroUsdc(u64::MAX)
+        );
+        assert_eq!(
+            UsdcAmount::parse_rounded(&format!("{max}1"), Rounding::Ceil),
+            Err(AmountError::Overflow)
+        );
+    }
+
+    #[test]
+    fn allocation_never_exceeds_total() {
+        for total in 0..=500u64 {
+            for weights in [
+                &[1u64][..],
+                &[1, 1],
+                &[1, 2],
+                &[1, 1, 1],
+                &[3, 5, 7],
+                &[0, 1],
+                &[10_000, 1],
+            ] {
+                let allocation = allocate(MicroUsdc(total), weights).unwrap();
+                let allocated: u64 = allocation.shares.iter().map(|share| share.0).sum();
+                assert_eq!(allocated + allocation.dust.0, total);
+                assert!(allocation.dust.0 < weights.len() as u64);
+            }
+        }
+    }
+
+    #[test]
+    fn allocation_dust() {
+        let allocation = allocate(MicroUsdc(100), &[1, 1, 1]).unwrap();
+        assert_eq!(allocation.shares, vec![MicroUsdc(33); 3]);
+        assert_eq!(allocation.dust, MicroUsdc(1));
+        assert_eq!(
+            allocate(MicroUsdc(u64::MAX), &[u64::MAX, u64::MAX])
+                .unwrap()
+                .dust,
+            MicroUsdc(1)
+        );
+        assert_eq!(allocate(MicroUsdc(100), &[]), None);
+        assert_eq!(allocate(MicroUsdc(100), &[0, 0]), None);
+    }
+}
@@ -0,0 +1,622 @@
+//! Resilient transaction sending with RPC failover
+//!
+//! [`Sender`] drives an attestation through one or more RPC endpoints:
+//! it fails over to the next endpoint when a call errors, refreshes the
+//! blockhash between attempts, optionally uses a durable nonce instead,
+//! and prices compute from recent prioritization fees. With
+//! [`PriorityFee::Recommended`], the price is sampled on the program's hot
+//! accounts as well as the transaction's own, at a percentile that
+//! depends on the instructions sent (see [`recommended_percentile`]).
+//!
+//! Retries are idempotent. Before every attempt, and whenever a send or
+//! confirmation fails, the sender checks whether the attestation PDA
+//! already exists, so an attestation that landed on an earlier attempt
+//! is reported as [`SendOutcome::AlreadyLanded`] instead of surfacing the
+//! program's duplicate-account error.
+
+use std::thread;
+use std::time::Duration;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::message::{v0, VersionedMessage};
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::Discriminator;
+use x402_attestation::instruction as ix;
+
+use crate::{config_address, program_stats_address, recent_attestations_address, ID};
+
+/// Compute budget program (`ComputeBudget111111111111111111111111111111`)
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    3, 6, 70, 111, 229, 33, 23, 50, 255, 236, 173, 186, 114, 195, 155, 231, 188, 140, 229, 187,
+    197, 247, 18, 107, 44, 67, 155, 58, 64, 0, 0, 0,
+]);
+
+/// Commitment level for reads and confirmation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+/// One RPC endpoint, implemented over the caller's RPC client
+///
+/// Errors are the transport's message; any error fails over to the next
+/// endpoint.
+pub trait RpcEndpoint {
+    fn latest_blockhash(&mut self, commitment: Commitment) -> Result<Hash, String>;
+
+    /// Current value stored in a durable nonce account
+    fn nonce_blockhash(&mut self, nonce_account: &Pubkey, commitment: Commitment)
+        -> Result<Hash, String>;
+
+    fn account_exists(&mut self, address: &Pubkey, commitment: Commitment) -> Result<bool, String>;
+
+    /// `sendTransaction` with preflight skipped; returns the error on rejection
+    fn send_transaction(&mut self, wire_transaction: &[u8]) -> Result<(), String>;
+
+    /// `None` while unknown, otherwise the transaction's result
+    fn signature_status(
+        &mut self,
+        signature: &[u8; 64],
+        commitment: Commitment,
+    ) -> Result<Option<Result<(), String>>, String>;
+
+    /// Recent prioritization fees (micro-lamports per CU) for the accounts
+    fn recent_prioritization_fees(&mut self, accounts: &[Pubkey]) -> Result<Vec<u64>, String>;
+}
+
+/// A signed transaction ready to send
+#[derive(Debug, Clone)]
+pub struct SignedTransaction {
+    /// First (fee payer) signature, used for confirmation
+    pub signature: [u8; 64],
+    /// Serialized transaction in wire format
+    pub wire: Vec<u8>,
+}
+
+/// Where the transaction's recent blockhash comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlockhashSource {
+    /// Latest blockhash, refreshed on every attempt
+    #[default]
+    Recent,
+    /// Durable nonce; `advance_nonce_account` is prepended automatically
+    DurableNonce { account: Pubkey, authority: Pubkey },
+}
+
+/// Compute unit price for the transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PriorityFee {
+    #[default]
+    None,
+    /// Fixed price in micro-lamports per compute unit
+    Fixed(u64),
+    /// Percentile (0-100) of recent fees on the written accounts, capped at `max`
+    Percentile { percentile: u8, max: u64 },
+    /// Recent fees on the written accounts and [`hot_accounts`], at the
+    /// [`recommended_percentile`] of the instructions, capped at `max`
+    Recommended { max: u64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct SendConfig {
+    pub commitment: Commitment,
+    pub blockhash: BlockhashSource,
+    pub priority_fee: PriorityFee,
+    /// Compute unit limit to request, if any
+    pub compute_unit_limit: Option<u32>,
+    /// Sign-and-send attempts before giving up
+    pub max_attempts: u32,
+    /// Status polls per attempt before the blockhash is refreshed
+    pub confirm_polls: u32,
+    pub poll_interval: Duration,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        SendConfig {
+            commitment: Commitment::Confirmed,
+            blockhash: BlockhashSource::Recent,
+            priority_fee: PriorityFee::None,
+            compute_unit_limit: None,
+            max_attempts: 5,
+            confirm_polls: 30,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// Confirmed at the configured commitment
+    Confirmed { signature: [u8; 64], attempts: u32 },
+    /// The attestation account already existed, from an earlier attempt
+    /// or another sender
+    AlreadyLanded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SendError {
+    #[error("all {endpoints} RPC endpoints failed, last error: {last}")]
+    AllEndpointsFailed { endpoints: usize, last: String },
+
+    #[error("failed to compile message: {0}")]
+    Compile(String),
+
+    #[error("signing failed: {0}")]
+    Sign(String),
+
+    #[error("transaction failed: {0}")]
+    TransactionFailed(String),
+
+    #[error("not confirmed after {0} attempts")]
+    NotConfirmed(u32),
+}
+
+/// Failover sender over a prioritized list of endpoints
+///
+/// The endpoint that last succeeded is tried first on the next call.
+pub struct Sender<E> {
+    endpoints: Vec<E>,
+    current: usize,
+    config: SendConfig,
+}
+
+impl<E: RpcEndpoint> Sender<E> {
+    /// # Panics
+    ///
+    /// If `endpoints` is empty.
+    pub fn new(endpoints: Vec<E>, config: SendConfig) -> Sender<E> {
+        assert!(!endpoints.is_empty(), "Sender needs at least one endpoint");
+        Sender {
+            endpoints,
+            current: 0,
+            config,
+        }
+    }
+
+    pub fn config(&self) -> &SendConfig {
+        &self.config
+    }
+
+    pub fn endpoints(&self) -> &[E] {
+        &self.endpoints
+    }
+
+    /// Index of the endpoint currently preferred
+    pub fn current_endpoint(&self) -> usize {
+        self.current
+    }
+
+    /// Send `instructions` and wait for confirmation
+    ///
+    /// `attestation` is the PDA the instructions create, used to detect an
+    /// attestation that already landed. `sign` signs the compiled message
+    /// with the payer and any other required signers.
+    pub fn send<S>(
+        &mut self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        attestation: &Pubkey,
+        mut sign: S,
+    ) -> Result<SendOutcome, SendError>
+    where
+        S: FnMut(&VersionedMessage) -> Result<SignedTransaction, String>,
+    {
+        let commitment = self.config.commitment;
+        let mut last_error = None;
+
+        for attempt in 1..=self.config.max_attempts {
+            if self.call(|rpc| rpc.account_exists(attestation, commitment))? {
+                return Ok(SendOutcome::AlreadyLanded);
+            }
+
+            let message = self.compile(payer, instructions)?;
+            let transaction = sign(&message).map_err(SendError::Sign)?;
+
+            if let Err(e) = self.call(|rpc| rpc.send_transaction(&transaction.wire)) {
+                last_error = Some(e.to_string());
+                continue;
+            }
+
+            match self.confirm(&transaction.signature)? {
+                Some(Ok(())) => {
+                    return Ok(SendOutcome::Confirmed {
+                        signature: transaction.signature,
+                        attempts: attempt,
+                    })
+                }
+                Some(Err(e)) => {
+                    if self.call(|rpc| rpc.account_exists(attestation, commitment))? {
+                        return Ok(SendOutcome::AlreadyLanded);
+                    }
+                    return Err(SendError::TransactionFailed(e));
+                }
+                None => {}
+            }
+        }
+
+        match last_error {
+            Some(last) => Err(SendError::AllEndpointsFailed {
+                endpoints: self.endpoints.len(),
+                last,
+            }),
+            None => Err(SendError::NotConfirmed(self.config.max_attempts)),
+        }
+    }
+
+    /// Whether `address` exists at the configured commitment
+    pub fn account_exists(&mut self, address: &Pubkey) -> Result<bool, SendError> {
+        let commitment = self.config.commitment;
+        self.call(|rpc| rpc.account_exists(address, commitment))
+    }
+
+    /// Compute unit price the next transaction will pay
+    pub fn estimate_priority_fee(&mut self, instructions: &[Instruction]) -> Result<u64, SendError> {
+        match self.config.priority_fee {
+            PriorityFee::None => Ok(0),
+            PriorityFee::Fixed(price) => Ok(price),
+            PriorityFee::Percentile { percentile, max } => {
+                let accounts = writable_accounts(instructions);
+                let fees = self.call(|rpc| rpc.recent_prioritization_fees(&accounts))?;
+                Ok(fee_percentile(fees, percentile).min(max))
+            }
+            PriorityFee::Recommended { max } => {
+                let mut accounts = writable_accounts(instructions);
+                for account in hot_accounts() {
+                    if !accounts.contains(&account) {
+                        accounts.push(account);
+                    }
+                }
+                let percentile = instructions
+                    .iter()
+                    .map(recommended_percentile)
+                    .max()
+                    .unwrap_or(0);
+                let fees = self.call(|rpc| rpc.recent_prioritization_fees(&accounts))?;
+                Ok(fee_percentile(fees, percentile).min(max))
+            }
+        }
+    }
+
+    fn compile(
+        &mut self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+    ) -> Result<VersionedMessage, SendError> {
+        let commitment = self.config.commitment;
+        let mut all = Vec::with_capacity(instructions.len() + 3);
+
+        let blockhash = match self.config.blockhash {
+            BlockhashSource::Recent => self.call(|rpc| rpc.latest_blockhash(commitment))?,
+            BlockhashSource::DurableNonce { account, authority } => {
+                all.push(system_instruction::advance_nonce_account(&account, &authority));
+                self.call(|rpc| rpc.nonce_blockhash(&account, commitment))?
+            }
+        };
+
+        if let Some(limit) = self.config.compute_unit_limit {
+            all.push(set_compute_unit_limit(limit));
+        }
+        let price = self.estimate_priority_fee(instructions)?;
+        if price > 0 {
+            all.push(set_compute_unit_price(price));
+        }
+        all.extend_from_slice(instructions);
+
+        let message = v0::Message::try_compile(payer, &all, &[], blockhash)
+            .map_err(|e| SendError::Compile(e.to_string()))?;
+        Ok(VersionedMessage::V0(message))
+    }
+
+    fn confirm(&mut self, signature: &[u8; 64]) -> Result<Option<Result<(), String>>, SendError> {
+        let commitment = self.config.commitment;
+        for poll in 0..self.config.confirm_polls {
+            if poll > 0 {
+                thread::sleep(self.config.poll_interval);
+            }
+            if let Some(status) = self.call(|rpc| rpc.signature_status(signature, commitment))? {
+                return Ok(Some(status));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Run `f` against each endpoint in turn, starting at the current one
+    fn call<T, F>(&mut self, mut f: F) -> Result<T, SendError>
+    where
+        F: FnMut(&mut E) -> Result<T, String>,
+    {
+        let count = self.endpoints.len();
+        let mut last = String::new();
+        for offset in 0..count {
+            let index = (self.current + offset) % count;
+            match f(&mut self.endpoints[index]) {
+                Ok(value) => {
+                    self.current = index;
+                    return Ok(value);
+                }
+                Err(e) => last = e,
+            }
+        }
+        Err(SendError::AllEndpointsFailed {
+            endpoints: count,
+            last,
+        })
+    }
+}
+
+/// ComputeBudget `SetComputeUnitLimit`
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+}
+
+/// ComputeBudget `SetComputeUnitPrice`, in micro-lamports per compute unit
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_PROGRAM_ID, &data, vec![])
+}
+
+/// Program accounts contended by most transactions: the config every
+/// instruction reads, and the stats and recent-attestations feed every
+/// attestation writes
+pub fn hot_accounts() -> [Pubkey; 3] {
+    [
+        config_address(),
+        program_stats_address(),
+        recent_attestations_address(),
+    ]
+}
+
+/// Fee percentile recommended for an instruction, by type
+///
+/// Attestations are the ones dropped under congestion and pay the 90th
+/// percentile; the rest of the claim pipeline (proof jobs, response
+/// fingerprints, verdicts, materialized batch claims, settlement batches)
+/// the 75th, and admin and maintenance instructions the median.
+/// Instructions of other programs don't count (0).
+pub fn recommended_percentile(instruction: &Instruction) -> u8 {
+    if instruction.program_id != ID {
+        return 0;
+    }
+    let Some(discriminator) = instruction.data.get(..8) else {
+        return 0;
+    };
+    let is = |expected: [u8; 8]| discriminator == expected;
+    if is(ix::AttestClaimProof::DISCRIMINATOR)
+        || is(ix::AttestDerivedClaimProof::DISCRIMINATOR)
+        || is(ix::AttestAggregatedProof::DISCRIMINATOR)
+    {
+        90
+    } else if is(ix::StartProofJob::DISCRIMINATOR)
+        || is(ix::MaterializeFromBatch::DISCRIMINATOR)
+        || is(ix::RecordResponseFingerprint::DISCRIMINATOR)
+        || is(ix::RecordVerdict::DISCRIMINATOR)
+        || is(ix::CommitSettlementBatch::DISCRIMINATOR)
+    {
+        75
+    } else {
+        50
+    }
+}
+
+fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts = Vec::new();
+    for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts
+}
+
+/// Nearest-rank percentile of the fees, 0 when there are none
+fn fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (usize::from(percentile.min(100)) * fees.len()).div_ceil(100);
+    fees[rank.saturating_sub(1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// Endpoint answering from a script, down after `up_for` calls
+    #[derive(Default)]
+    struct Scripted {
+        up_for: Option<usize>,
+        calls: usize,
+        exists: VecDeque<bool>,
+        statuses: VecDeque<Option<Result<(), String>>>,
+        fees: Vec<u64>,
+        sent: usize,
+    }
+
+    impl Scripted {
+        fn down() -> Scripted {
+            Scripted {
+                up_for: Some(0),
+                ..Scripted::default()
+            }
+        }
+
+        fn answer<T>(&mut self, value: impl FnOnce(&mut Scripted) -> T) -> Result<T, String> {
+            self.calls += 1;
+            if self.up_for.is_some_and(|up_for| self.calls > up_for) {
+                return Err(format!("endpoint down after {} calls", self.calls - 1));
+            }
+            Ok(value(self))
+        }
+    }
+
+    impl RpcEndpoint for Scripted {
+        fn latest_blockhash(&mut self, _: Commitment) -> Result<Hash, String> {
+            self.answer(|_| Hash::default())
+        }
+
+        fn nonce_blockhash(&mut self, _: &Pubkey, _: Commitment) -> Result<Hash, String> {
+            self.answer(|_| Hash::default())
+        }
+
+        fn account_exists(&mut self, _: &Pubkey, _: Commitment) -> Result<bool, String> {
+            self.answer(|rpc| rpc.exists.pop_front().unwrap_or(false))
+        }
+
+        fn send_transaction(&mut self, _: &[u8]) -> Result<(), String> {
+            self.answer(|rpc| rpc.sent += 1)
+        }
+
+        fn signature_status(
+            &mut self,
+            _: &[u8; 64],
+            _: Commitment,
+        ) -> Result<Option<Result<(), String>>, String> {
+            self.answer(|rpc| rpc.statuses.pop_front().flatten())
+        }
+
+        fn recent_prioritization_fees(&mut self, _: &[Pubkey]) -> Result<Vec<u64>, String> {
+            self.answer(|rpc| rpc.fees.clone())
+        }
+    }
+
+    fn sender(endpoints: Vec<Scripted>) -> Sender<Scripted> {
+        let config = SendConfig {
+            max_attempts: 2,
+            confirm_polls: 1,
+            poll_interval: Duration::ZERO,
+            ..SendConfig::default()
+        };
+        Sender::new(endpoints, config)
+    }
+
+    fn send(sender: &mut Sender<Scripted>) -> Result<SendOutcome, SendError> {
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(ID, &[0; 8], vec![]);
+        sender.send(&payer, &[instruction], &Pubkey::new_unique(), |_| {
+            Ok(SignedTransaction {
+                signature: [1; 64],
+                wire: vec![],
+            })
+        })
+    }
+
+    fn confirmed() -> VecDeque<Option<Result<(), String>>> {
+        VecDeque::from([Some(Ok(()))])
+    }
+
+    #[test]
+    fn fails_over_in_order_and_sticks_to_the_last_good_endpoint() {
+        // A send is four calls: existence, blockhash, send and one poll
+        let mut sender = sender(vec![
+            Scripted::down(),
+            Scripted {
+                up_for: Some(4),
+                statuses: confirmed(),
+                ..Scripted::default()
+            },
+            Scripted {
+                statuses: confirmed(),
+                ..Scripted::default()
+            },
+        ]);
+
+        assert_eq!(
+            send(&mut sender),
+            Ok(SendOutcome::Confirmed {
+                signature: [1; 64],
+                attempts: 1,
+            })
+        );
+        assert_eq!(sender.current_endpoint(), 1);
+        assert_eq!(sender.endpoints()[1].sent, 1);
+
+        // The second send starts at endpoint 1, not at the dead endpoint 0
+        assert!(matches!(
+            send(&mut sender),
+            Ok(SendOutcome::Confirmed { .. })
+        ));
+        assert_eq!(sender.current_endpoint(), 2);
+        assert_eq!(sender.endpoints()[0].calls, 1);
+        assert_eq!(sender.endpoints()[2].sent, 1);
+
+        let mut dead = self::sender(vec![Scripted::down(), Scripted::down()]);
+        assert_eq!(
+            send(&mut dead),
+            Err(SendError::AllEndpointsFailed {
+                endpoints: 2,
+                last: "endpoint down after 0 calls".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn reports_already_landed_before_sending_and_after_a_failure() {
+        let mut landed = sender(vec![Scripted {
+            exists: VecDeque::from([true]),
+            ..Scripted::default()
+        }]);
+        assert_eq!(send(&mut landed), Ok(SendOutcome::AlreadyLanded));
+        assert_eq!(landed.endpoints()[0].sent, 0);
+
+        // Failed because an earlier attempt landed first
+        let failure = || VecDeque::from([Some(Err("account already in use".to_string()))]);
+        let mut raced = sender(vec![Scripted {
+            exists: VecDeque::from([false, true]),
+            statuses: failure(),
+            ..Scripted::default()
+        }]);
+        assert_eq!(send(&mut raced), Ok(SendOutcome::AlreadyLanded));
+        assert_eq!(raced.endpoints()[0].sent, 1);
+
+        let mut failed = sender(vec![Scripted {
+            statuses: failure(),
+            ..Scripted::default()
+        }]);
+        assert_eq!(
+            send(&mut failed),
+            Err(SendError::TransactionFailed(
+                "account already in use".into()
+            ))
+        );
+
+        let mut unconfirmed = sender(vec![Scripted::default()]);
+        assert_eq!(send(&mut unconfirmed), Err(SendError::NotConfirmed(2)));
+        assert_eq!(unconfirmed.endpoints()[0].sent, 2);
+    }
+
+    #[test]
+    fn fee_percentiles_use_nearest_rank() {
+        assert_eq!(fee_percentile(vec![], 90), 0);
+        assert_eq!(fee_percentile(vec![], 0), 0);
+
+        let fees = vec![40, 10, 30, 20];
+        assert_eq!(fee_percentile(fees.clone(), 0), 10);
+        assert_eq!(fee_percentile(fees.clone(), 25), 10);
+        assert_eq!(fee_percentile(fees.clone(), 26), 20);
+        assert_eq!(fee_percentile(fees.clone(), 50), 20);
+        assert_eq!(fee_percentile(fees.clone(), 100), 40);
+        assert_eq!(fee_percentile(fees, 255), 40);
+
+        let mut sender = sender(vec![Scripted {
+            fees: vec![100, 5_000],
+            ..Scripted::default()
+        }]);
+        sender.config.priority_fee = PriorityFee::Percentile {
+            percentile: 100,
+            max: 1_000,
+        };
+        assert_eq!(sender.estimate_priority_fee(&[]), Ok(1_000));
+    }
+}
@@ -0,0 +1,134 @@
+//! Inclusion proofs for settlement batches
+//!
+//! `commit_settlement_batch` commits the `evm_digest`s of a batch of
+//! attestations to an ordered keccak256 Merkle root, which a relayer posts
+//! to the EVM notary contract. These helpers build and check the path of
+//! one leaf against that root, so a claim can be shown to be in a posted
+//! batch with the notary's root alone.
+
+use crate::{ProofAttestation, SettlementBatch};
+
+pub use x402_attestation::{settlement_node, settlement_root};
+
+/// Sibling path of `leaves[index]`, from the leaf level up
+///
+/// Levels where the node has no sibling (an odd node at the end) add no
+/// entry, matching how `settlement_root` moves it up. `None` if `index`
+/// is out of range.
+pub fn settlement_proof(leaves: &[[u8; 32]], index: usize) -> Option<Vec<[u8; 32]>> {
+    if index >= leaves.len() {
+        return None;
+    }
+
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => settlement_node(left, right),
+                _ => pair[0],
+            })
+            .collect();
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Whether `leaf` is leaf `index` of a `leaf_count`-leaf batch with `root`
+pub fn verify_settlement_proof(
+    leaf: &[u8; 32],
+    index: usize,
+    leaf_count: usize,
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+) -> bool {
+    if index >= leaf_count {
+        return false;
+    }
+
+    let mut node = *leaf;
+    let mut siblings = proof.iter();
+    let (mut index, mut width) = (index, leaf_count);
+    while width > 1 {
+        if index ^ 1 < width {
+            let Some(sibling) = siblings.next() else {
+                return false;
+            };
+            node = if index % 2 == 0 {
+                settlement_node(&node, sibling)
+            } else {
+                settlement_node(sibling, &node)
+            };
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    siblings.next().is_none() && node == *root
+}
+
+/// Whether `attestation` is leaf `index` of `batch`
+///
+/// Checks the recomputed struct hash rather than the stored
+/// `evm_digest`, like [`crate::foreign::cross_check`].
+pub fn attestation_in_batch(
+    attestation: &ProofAttestation,
+    batch: &SettlementBatch,
+    index: usize,
+    proof: &[[u8; 32]],
+) -> bool {
+    (batch.first_sequence..=batch.last_sequence).contains(&attestation.sequence)
+        && verify_settlement_proof(
+            &attestation.evm_struct_hash(),
+            index,
+            batch.leaf_count as usize,
+            proof,
+            &batch.root,
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proofs_verify_for_every_leaf_and_batch_size() {
+        for count in 1..=9u8 {
+            let leaves: Vec<[u8; 32]> = (0..count).map(|i| [i; 32]).collect();
+            let root = settlement_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = settlement_proof(&leaves, index).unwrap();
+                assert!(verify_settlement_proof(
+                    leaf,
+                    index,
+                    leaves.len(),
+                    &proof,
+                    &root
+                ));
+                assert!(!verify_settlement_proof(
+                    &[0xff; 32],
+                    index,
+                    leaves.len(),
+                    &proof,
+                    &root
+                ));
+                if leaves.len() > 1 {
+                    let other = (index + 1) % leaves.len();
+                    assert!(!verify_settlement_proof(
+                        leaf,
+                        other,
+                        leaves.len(),
+                        &proof,
+                        &root
+                    ));
+                }
+            }
+            assert!(settlement_proof(&leaves, leaves.len()).is_none());
+        }
+        assert_eq!(settlement_root(&[[7; 32]]), [7; 32]);
+    }
+}
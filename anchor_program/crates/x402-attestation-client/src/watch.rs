@@ -0,0 +1,238 @@
+//! Typed change events from account notifications
+//!
+//! Subscribe to the program's accounts with your RPC client's websocket
+//! (`programSubscribe`, or `accountSubscribe` per account) and hand each
+//! notification's raw data to [`AccountWatcher::update`]. The watcher
+//! decodes it, diffs it against the last state it saw for that address
+//! and returns what changed as [`AccountChange`]s, so services react to
+//! payouts and reserve movements without keeping their own copies.
+//!
+//! Watched are current-layout attestations and the proof-of-reserve
+//! statement; other accounts are ignored. Seed the watcher with
+//! [`AccountWatcher::observe`] from an initial fetch, or every existing
+//! attestation is reported as a new payout on its first notification.
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Discriminator;
+
+use crate::amount::MicroUsdc;
+use crate::migration::attestation_layout_version;
+use crate::{
+    decode_attestation, decode_reserve_attestation, ClientError, ProofAttestation,
+    ReserveAttestation,
+};
+use x402_attestation::ATTESTATION_LAYOUT_VERSION;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountChange {
+    /// A new attestation: the claim was paid
+    PayoutReleased {
+        address: Pubkey,
+        claim_id: [u8; 32],
+        payout: MicroUsdc,
+        sequence: u64,
+    },
+    /// An attestation was archived (its account closed or shrunk to an
+    /// `ArchivedAttestation`)
+    AttestationClosed { address: Pubkey, claim_id: [u8; 32] },
+    /// The reported payout vault balance moved
+    VaultBalanceChanged { balance: MicroUsdc, delta: i128 },
+    /// The exposure reserved against open claims moved
+    ReservedExposureChanged { exposure: MicroUsdc, delta: i128 },
+    /// The proof-of-reserve statement was flagged as missed
+    ReserveStatementMissed { missed_count: u64 },
+}
+
+#[derive(Clone)]
+enum Snapshot {
+    Attestation(Box<ProofAttestation>),
+    Reserves(ReserveAttestation),
+}
+
+/// Last seen state of each watched account
+#[derive(Default)]
+pub struct AccountWatcher {
+    accounts: HashMap<Pubkey, Snapshot>,
+}
+
+impl AccountWatcher {
+    pub fn new() -> AccountWatcher {
+        AccountWatcher::default()
+    }
+
+    /// Record the current state of `address` without reporting changes
+    pub fn observe(&mut self, address: &Pubkey, data: &[u8]) -> Result<(), ClientError> {
+        if let Some(snapshot) = snapshot(address, data)? {
+            self.accounts.insert(*address, snapshot);
+        }
+        Ok(())
+    }
+
+    /// Apply a notification; `None` data means the account was closed
+    pub fn update(
+        &mut self,
+        address: &Pubkey,
+        data: Option<&[u8]>,
+    ) -> Result<Vec<AccountChange>, ClientError> {
+        let current = match data {
+            Some(data) => snapshot(address, data)?,
+            None => None,
+        };
+        let previous = match &current {
+            Some(snapshot) => self.accounts.insert(*address, snapshot.clone()),
+            None => self.accounts.remove(address),
+        };
+        Ok(diff(address, previous.as_ref(), current.as_ref()))
+    }
+
+    /// Number of accounts being tracked
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+fn snapshot(address: &Pubkey, data: &[u8]) -> Result<Option<Snapshot>, ClientError> {
+    if data.starts_with(&ProofAttestation::DISCRIMINATOR) {
+        // Older layouts don't decode; they show up again once upgraded
+        if attestation_layout_version(data) != Some(ATTESTATION_LAYOUT_VERSION) {
+            return Ok(None);
+        }
+        return decode_attestation(address, data)
+            .map(|record| Some(Snapshot::Attestation(Box::new(record))));
+    }
+    if data.starts_with(&ReserveAttestation::DISCRIMINATOR) {
+        return decode_reserve_attestation(address, data)
+            .map(|reserves| Some(Snapshot::Reserves(reserves)));
+    }
+    Ok(None)
+}
+
+fn diff(
+    address: &Pubkey,
+    previous: Option<&Snapshot>,
+    current: Option<&Snapshot>,
+) -> Vec<AccountChange> {
+    let mut changes = Vec::new();
+    match (previous, current) {
+        (None, Some(Snapshot::Attestation(record))) => {
+            changes.push(AccountChange::PayoutReleased {
+                address: *address,
+                claim_id: record.claim_id,
+                payout: MicroUsdc(record.public_inputs[3]),
+                sequence: record.sequence,
+            });
+        }
+        (Some(Snapshot::Attestation(record)), None) => {
+            changes.push(AccountChange::AttestationClosed {
+                address: *address,
+                claim_id: record.claim_id,
+            });
+        }
+        (previous, Some(Snapshot::Reserves(reserves))) => {
+            let (balance, exposure, missed) = match previous {
+                Some(Snapshot::Reserves(old)) => {
+                    (old.vault_balance, old.reserved_exposure, old.missed)
+                }
+                _ => (0, 0, false),
+            };
+            if reserves.vault_balance != balance {
+                changes.push(AccountChange::VaultBalanceChanged {
+                    balance: MicroUsdc(reserves.vault_balance),
+                    delta: i128::from(reserves.vault_balance) - i128::from(balance),
+                });
+            }
+            if reserves.reserved_exposure != exposure {
+                changes.push(AccountChange::ReservedExposureChanged {
+                    exposure: MicroUsdc(reserves.reserved_exposure),
+                    delta: i128::from(reserves.reserved_exposure) - i128::from(exposure),
+                });
+            }
+            if reserves.missed && !missed {
+                changes.push(AccountChange::ReserveStatementMissed {
+                    missed_count: reserves.missed_count,
+                });
+            }
+        }
+        _ => {}
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Space;
+
+    use super::*;
+    use crate::fixtures::{sample_attestation, AccountFixture};
+    use crate::{attestation_address, reserve_attestation_address};
+
+    fn reserves(vault_balance: u64, reserved_exposure: u64, missed: bool) -> Vec<u8> {
+        let statement = ReserveAttestation {
+            auditor: Pubkey::new_unique(),
+            vault_balance,
+            reserved_exposure,
+            bank_reserves_hash: [0; 32],
+            attested_at: 0,
+            count: 1,
+            missed,
+            missed_count: u64::from(missed),
+            bump: 0,
+        };
+        let address = reserve_attestation_address();
+        AccountFixture::program_account(address, &statement, ReserveAttestation::INIT_SPACE).data
+    }
+
+    #[test]
+    fn reports_payouts_closures_and_reserve_deltas() {
+        let mut watcher = AccountWatcher::new();
+
+        let record = sample_attestation(3, Pubkey::new_unique(), 1_760_000_000);
+        let address = attestation_address(&record.claim_id);
+        let data =
+            AccountFixture::program_account(address, &record, ProofAttestation::INIT_SPACE).data;
+        assert_eq!(
+            watcher.update(&address, Some(&data)).unwrap(),
+            vec![AccountChange::PayoutReleased {
+                address,
+                claim_id: record.claim_id,
+                payout: MicroUsdc(record.public_inputs[3]),
+                sequence: record.sequence,
+            }]
+        );
+        assert!(watcher.update(&address, Some(&data)).unwrap().is_empty());
+        assert_eq!(
+            watcher.update(&address, None).unwrap(),
+            vec![AccountChange::AttestationClosed {
+                address,
+                claim_id: record.claim_id,
+            }]
+        );
+
+        let reserve_address = reserve_attestation_address();
+        watcher
+            .observe(&reserve_address, &reserves(500, 200, false))
+            .unwrap();
+        assert_eq!(
+            watcher
+                .update(&reserve_address, Some(&reserves(350, 200, true)))
+                .unwrap(),
+            vec![
+                AccountChange::VaultBalanceChanged {
+                    balance: MicroUsdc(350),
+                    delta: -150,
+                },
+                AccountChange::ReserveStatementMissed { missed_count: 1 },
+            ]
+        );
+        assert!(watcher
+            .update(&Pubkey::new_unique(), Some(&[0; 16]))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Webhook payload signatures against the on-chain signing key
+//!
+//! Relayers sign each delivery with the key registered by
+//! `rotate_webhook_signer` and send the result in a header:
+//!
+//! ```text
+//! X402-Signature: t=1760400000,k=<signing key>,s=<signature>
+//! ```
+//!
+//! Consumers fetch the program's [`WebhookSigner`] account and check the
+//! header against it with [`verify_webhook`], so there is no shared secret
+//! to distribute, and a rotation or revocation takes effect for everyone
+//! at once.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::receipt::{verify_ed25519, Ed25519Signer};
+use crate::WebhookSigner;
+
+/// Domain tag for the signed webhook message
+pub const WEBHOOK_DOMAIN: &[u8] = b"x402-webhook:v1";
+
+/// Header the signature is delivered in
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X402-Signature";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WebhookError {
+    #[error("malformed webhook signature header")]
+    InvalidHeader,
+
+    #[error("webhook timestamp {0} is outside the accepted window")]
+    Stale(i64),
+
+    #[error("webhook was signed by {0}, which is not a registered signing key")]
+    UnknownKey(Pubkey),
+
+    #[error("webhook signature is invalid")]
+    InvalidSignature,
+}
+
+/// Parsed `X402-Signature` header value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WebhookSignature {
+    /// Unix time the relayer signed at
+    pub timestamp: i64,
+    pub key: Pubkey,
+    pub signature: [u8; 64],
+}
+
+impl WebhookSignature {
+    pub fn to_header(&self) -> String {
+        format!(
+            "t={},k={},s={}",
+            self.timestamp,
+            self.key,
+            bs58::encode(self.signature).into_string()
+        )
+    }
+
+    pub fn parse(header: &str) -> Result<WebhookSignature, WebhookError> {
+        let (mut timestamp, mut key, mut signature) = (None, None, None);
+        for part in header.split(',') {
+            match part.trim().split_once('=') {
+                Some(("t", value)) => timestamp = value.parse().ok(),
+                Some(("k", value)) => key = value.parse().ok(),
+                Some(("s", value)) => {
+                    signature = bs58::decode(value)
+                        .into_vec()
+                        .ok()
+                        .and_then(|bytes| bytes.try_into().ok())
+                }
+                _ => {}
+            }
+        }
+
+        Ok(WebhookSignature {
+            timestamp: timestamp.ok_or(WebhookError::InvalidHeader)?,
+            key: key.ok_or(WebhookError::InvalidHeader)?,
+            signature: signature.ok_or(WebhookError::InvalidHeader)?,
+        })
+    }
+}
+
+/// Bytes the relayer signs: the domain, the timestamp and the raw body
+pub fn webhook_message(timestamp: i64, body: &[u8]) -> [u8; 32] {
+    hashv(&[WEBHOOK_DOMAIN, &timestamp.to_le_bytes(), body]).to_bytes()
+}
+
+/// Sign a delivery at unix time `timestamp` with the registered `key`
+pub fn sign_webhook(
+    key: Pubkey,
+    timestamp: i64,
+    body: &[u8],
+    signer: impl Ed25519Signer,
+) -> WebhookSignature {
+    WebhookSignature {
+        timestamp,
+        key,
+        signature: signer.sign(&webhook_message(timestamp, body)),
+    }
+}
+
+/// Verify a delivery received at unix time `now`
+///
+/// Rejects timestamps more than `tolerance_seconds` from `now`, which
+/// bounds how long a captured delivery can be replayed, and keys the
+/// registry doesn't accept at `now`.
+pub fn verify_webhook(
+    signer: &WebhookSigner,
+    header: &str,
+    body: &[u8],
+    now: i64,
+    tolerance_seconds: i64,
+) -> Result<(), WebhookError> {
+    let signature = WebhookSignature::parse(header)?;
+    if signature.timestamp.abs_diff(now) > tolerance_seconds.unsigned_abs() {
+        return Err(WebhookError::Stale(signature.timestamp));
+    }
+    if !signer.accepts(&signature.key, now) {
+        return Err(WebhookError::UnknownKey(signature.key));
+    }
+
+    let message = webhook_message(signature.timestamp, body);
+    if verify_ed25519(&signature.key, &message, &signature.signature) {
+        Ok(())
+    } else {
+        Err(WebhookError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::receipt::tests::keypair;
+
+    const NOW: i64 = 1_760_400_000;
+
+    fn registry(key: Pubkey, previous_key: Pubkey, previous_valid_until: i64) -> WebhookSigner {
+        WebhookSigner {
+            key,
+            activated_at: NOW - 60,
+            previous_key,
+            previous_valid_until,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn header_round_trips_and_rejects_malformed_values() {
+        let signature = WebhookSignature {
+            timestamp: NOW,
+            key: Pubkey::new_unique(),
+            signature: [9; 64],
+        };
+        let header = signature.to_header();
+        assert_eq!(WebhookSignature::parse(&header), Ok(signature));
+
+        // Order and padding don't matter; unknown fields are ignored
+        let (t, rest) = header.split_once(',').unwrap();
+        assert_eq!(
+            WebhookSignature::parse(&format!("v=1, {rest}, {t}")),
+            Ok(signature)
+        );
+
+        let short = format!(
+            "t={NOW},k={},s={}",
+            signature.key,
+            bs58::encode([9; 63]).into_string()
+        );
+        for malformed in [
+            "",
+            "t=soon,k=x,s=y",
+            &header.replace(&format!("t={NOW},"), ""),
+            &short,
+        ] {
+            assert_eq!(
+                WebhookSignature::parse(malformed),
+                Err(WebhookError::InvalidHeader)
+            );
+        }
+    }
+
+    #[test]
+    fn deliveries_verify_within_the_window_and_rotation_overlap() {
+        let (key, sign) = keypair([5; 32]);
+        let body = br#"{"claim_id":"01"}"#;
+        let header = sign_webhook(key, NOW, body, &sign).to_header();
+        let current = registry(key, Pubkey::default(), 0);
+
+        assert_eq!(
+            verify_webhook(&current, &header, body, NOW + 300, 300),
+            Ok(())
+        );
+        assert_eq!(
+            verify_webhook(&current, &header, body, NOW - 300, 300),
+            Ok(())
+        );
+        assert_eq!(
+            verify_webhook(&current, &header, body, NOW + 301, 300),
+            Err(WebhookError::Stale(NOW))
+        );
+        assert_eq!(
+            verify_webhook(&current, &header, body, NOW - 301, 300),
+            Err(WebhookError::Stale(NOW))
+        );
+        assert_eq!(
+            verify_webhook(&current, &header, b"{}", NOW, 300),
+            Err(WebhookError::InvalidSignature)
+        );
+
+        // Rotated away: accepted until the overlap ends, then unknown
+        let rotated = registry(Pubkey::new_unique(), key, NOW + 100);
+        assert_eq!(
+            verify_webhook(&rotated, &header, body, NOW + 100, 300),
+            Ok(())
+        );
+        assert_eq!(
+            verify_webhook(&rotated, &header, body, NOW + 101, 300),
+            Err(WebhookError::UnknownKey(key))
+        );
+
+        // Signed by another key but claiming the registered one
+        let (_, other_sign) = keypair([6; 32]);
+        let forged = sign_webhook(key, NOW, body, other_sign).to_header();
+        assert_eq!(
+            verify_webhook(&current, &forged, body, NOW, 300),
+            Err(WebhookError::InvalidSignature)
+        );
+    }
+}
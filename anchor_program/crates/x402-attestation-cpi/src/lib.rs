@@ -0,0 +1,83 @@
+//! CPI bindings for the x402 attestation program
+//!
+//! Lets other Solana programs (e.g., a merchant escrow program) attest or
+//! look up claim proofs via CPI without vendoring the program source.
+//!
+//! # Example
+//!
+//! Releasing escrowed funds only once a claim has been attested:
+//!
+//! ```ignore
+//! use anchor_lang::prelude::*;
+//! use x402_attestation_cpi::{cpi, ProofAttestation};
+//!
+//! pub fn release_escrow(ctx: Context<ReleaseEscrow>, claim_id: [u8; 32]) -> Result<()> {
+//!     let (expected, _) = x402_attestation_cpi::attestation_address(&claim_id);
+//!     require_keys_eq!(ctx.accounts.attestation.key(), expected);
+//!
+//!     let cpi_ctx = CpiContext::new(
+//!         ctx.accounts.attestation_program.to_account_info(),
+//!         cpi::accounts::QueryAttestation {
+//!             attestation: ctx.accounts.attestation.to_account_info(),
+//!         },
+//!     );
+//!     let attestation: ProofAttestation = cpi::query(cpi_ctx)?;
+//!     require!(attestation.claim_id == claim_id, EscrowError::ClaimMismatch);
+//!
+//!     // ... pay out attestation.public_inputs[3] micro-USDC from escrow
+//!     Ok(())
+//! }
+//!
+//! #[derive(Accounts)]
+//! pub struct ReleaseEscrow<'info> {
+//!     /// CHECK: validated against the derived PDA and by the attestation program
+//!     pub attestation: UncheckedAccount<'info>,
+//!     pub attestation_program: Program<'info, x402_attestation_cpi::X402Attestation>,
+//! }
+//! ```
+
+use anchor_lang::prelude::*;
+
+pub use x402_attestation::program::X402Attestation;
+pub use x402_attestation::{
+    id, ClaimBloom, Config, ProofAttestation, ProofAttested, ProofAttestedV2, RecentAttestation,
+    RecentAttestations, ATTESTATION_SEED, CLAIM_BLOOM_SEED, CONFIG_SEED, ID,
+    RECENT_ATTESTATIONS_SEED,
+};
+
+pub mod cpi {
+    use super::*;
+
+    pub use x402_attestation::cpi::{attest_claim_proof, attest_derived_claim_proof};
+
+    pub mod accounts {
+        pub use x402_attestation::cpi::accounts::{AttestProof, QueryAttestation};
+    }
+
+    /// Query an attestation and decode the returned record
+    pub fn query<'a, 'b, 'c, 'info>(
+        ctx: CpiContext<'a, 'b, 'c, 'info, accounts::QueryAttestation<'info>>,
+    ) -> Result<ProofAttestation> {
+        Ok(x402_attestation::cpi::query_attestation(ctx)?.get())
+    }
+}
+
+/// Derive the attestation PDA for a claim
+pub fn attestation_address(claim_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ATTESTATION_SEED, claim_id.as_ref()], &ID)
+}
+
+/// Derive the recent-attestations ring buffer PDA
+pub fn recent_attestations_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECENT_ATTESTATIONS_SEED], &ID)
+}
+
+/// Derive a merchant's paid-claim filter PDA
+///
+/// Load it as `Account<ClaimBloom>` and call `contains(&claim_id)`: a
+/// `false` means the claim was never attested against the merchant; a
+/// `true` may be a false positive, so confirm it by loading the
+/// attestation before paying on it.
+pub fn claim_bloom_address(merchant_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_BLOOM_SEED, merchant_id.as_ref()], &ID)
+}
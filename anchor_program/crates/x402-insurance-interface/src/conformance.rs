@@ -0,0 +1,143 @@
+//! Conformance checks for backends
+//!
+//! [`check_backend`] walks one fresh claim through the lifecycle and
+//! checks each answer against what the trait promises. Operations the
+//! backend reports as unsupported are skipped, as long as it says so with
+//! the right [`Operation`].
+
+use std::fmt;
+
+use crate::{
+    ClaimRequest, ClaimStatus, Dispute, InsuranceBackend, InsuranceError, Operation, QuoteRequest,
+};
+
+/// The first check a backend failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    pub check: &'static str,
+    pub detail: String,
+}
+
+impl fmt::Display for ConformanceFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.check, self.detail)
+    }
+}
+
+impl std::error::Error for ConformanceFailure {}
+
+/// Run the lifecycle checks with `claim`, which must not be filed yet
+///
+/// The claim must carry evidence of fraud, so that the backend pays it.
+pub fn check_backend<B>(backend: &mut B, claim: &ClaimRequest) -> Result<(), ConformanceFailure>
+where
+    B: InsuranceBackend + ?Sized,
+{
+    let fail = |check: &'static str, detail: &dyn fmt::Debug| ConformanceFailure {
+        check,
+        detail: format!("{detail:?}"),
+    };
+    let unsupported = |result: &InsuranceError, operation: Operation| {
+        *result == InsuranceError::Unsupported(operation)
+    };
+
+    match backend.quote(&QuoteRequest {
+        merchant_id: claim.merchant_id,
+        coverage: claim.payout,
+    }) {
+        Ok(quote) if quote.coverage == claim.payout => match backend.bind(&quote) {
+            Ok(policy) if policy.coverage == quote.coverage && policy.premium == quote.premium => {}
+            Err(e) if unsupported(&e, Operation::Bind) => {}
+            other => return Err(fail("bind returns the quoted terms", &other)),
+        },
+        Err(e) if unsupported(&e, Operation::Quote) => {}
+        other => return Err(fail("quote covers the requested amount", &other)),
+    }
+
+    match backend.claim_status(&claim.claim_id) {
+        Ok(ClaimStatus::Unknown) => {}
+        other => return Err(fail("an unfiled claim is unknown", &other)),
+    }
+    match backend.claim(claim) {
+        Ok(ClaimStatus::Paid { payout, .. }) if payout == claim.payout => {}
+        other => return Err(fail("a claim is paid its payout", &other)),
+    }
+    match backend.claim(claim) {
+        Err(InsuranceError::DuplicateClaim) => {}
+        other => return Err(fail("a claim is filed once", &other)),
+    }
+    match backend.claim_status(&claim.claim_id) {
+        Ok(ClaimStatus::Paid { payout, .. }) if payout == claim.payout => {}
+        other => return Err(fail("a filed claim reports as paid", &other)),
+    }
+
+    match backend.dispute(&Dispute {
+        claim_id: claim.claim_id,
+        fraud_detected: true,
+        evidence_hash: claim.evidence.proof_hash,
+    }) {
+        Ok(()) => {}
+        Err(e) if unsupported(&e, Operation::Dispute) => {}
+        other => return Err(fail("a filed claim can be disputed", &other)),
+    }
+
+    match backend.settle(&[claim.claim_id]) {
+        Ok(settlement) if settlement.claim_ids.contains(&claim.claim_id) => {
+            match backend.claim_status(&claim.claim_id) {
+                Ok(ClaimStatus::Settled { payout, .. }) if payout == claim.payout => {}
+                other => return Err(fail("a settled claim reports as settled", &other)),
+            }
+        }
+        Err(e) if unsupported(&e, Operation::Settle) => {}
+        other => return Err(fail("settle includes the claim", &other)),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryBackend;
+    use crate::{Evidence, InsuranceBackend};
+
+    #[test]
+    fn memory_backend_conforms() {
+        let claim = ClaimRequest {
+            claim_id: [1; 32],
+            merchant_id: [2; 32],
+            payout: 10_000,
+            evidence: Evidence {
+                fraud_detected: true,
+                http_status: 503,
+                body_length: 0,
+                proof_hash: [3; 32],
+                captured_at: 0,
+            },
+            refund_reference: vec![4; 64],
+        };
+
+        let mut backend = MemoryBackend::new(1_760_000_000, 150);
+        assert_eq!(check_backend(&mut backend, &claim), Ok(()));
+        assert_eq!(backend.disputes(&claim.claim_id).len(), 1);
+
+        // Filed already, so the lifecycle can't start over
+        let failure = check_backend(&mut backend, &claim).unwrap_err();
+        assert_eq!(failure.check, "an unfiled claim is unknown");
+
+        struct ClaimsOnly(MemoryBackend);
+        impl InsuranceBackend for ClaimsOnly {
+            fn name(&self) -> &str {
+                "claims-only"
+            }
+            fn claim(&mut self, claim: &ClaimRequest) -> Result<ClaimStatus, InsuranceError> {
+                self.0.claim(claim)
+            }
+            fn claim_status(&mut self, claim_id: &[u8; 32]) -> Result<ClaimStatus, InsuranceError> {
+                self.0.claim_status(claim_id)
+            }
+        }
+        let mut backend = ClaimsOnly(MemoryBackend::default());
+        assert_eq!(check_backend(&mut backend, &claim), Ok(()));
+    }
+}
@@ -0,0 +1,173 @@
+//! Claim lifecycle interface for x402 insurance backends
+//!
+//! [`InsuranceBackend`] describes what a backend does with a claim, from
+//! quoting coverage to settling and disputing paid claims, in terms that
+//! don't assume Solana: amounts are micro-USDC and references are bytes.
+//! The x402 attestation program implements it through
+//! `x402_attestation_client::backend::ProgramBackend`; EVM or custodial
+//! backends implement the same trait, and [`conformance::check_backend`]
+//! runs the same checks against any of them.
+//!
+//! Operations a backend doesn't offer return
+//! [`InsuranceError::Unsupported`], which is what the default methods do.
+
+pub mod conformance;
+pub mod memory;
+
+/// Claim identifier, shared by every backend for the same claim
+pub type ClaimId = [u8; 32];
+
+/// Lifecycle operations, for [`InsuranceError::Unsupported`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Quote,
+    Bind,
+    Claim,
+    Settle,
+    Dispute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InsuranceError {
+    #[error("{0:?} is not supported by this backend")]
+    Unsupported(Operation),
+
+    #[error("claim was already filed")]
+    DuplicateClaim,
+
+    #[error("claim is unknown to this backend")]
+    UnknownClaim,
+
+    #[error("quote has expired")]
+    QuoteExpired,
+
+    /// The backend refused the request, e.g. a failed policy check
+    #[error("rejected: {0}")]
+    Rejected(String),
+
+    /// The backend couldn't be reached; the request may be retried
+    #[error("backend unavailable: {0}")]
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteRequest {
+    /// SHA-256 of the merchant endpoint URL to cover
+    pub merchant_id: [u8; 32],
+    /// Coverage asked for (micro-USDC)
+    pub coverage: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    pub quote_id: [u8; 32],
+    pub merchant_id: [u8; 32],
+    /// Coverage offered (micro-USDC)
+    pub coverage: u64,
+    /// Premium for the coverage (micro-USDC)
+    pub premium: u64,
+    /// Unix timestamp after which the quote can't be bound
+    pub expires_at: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    pub policy_id: [u8; 32],
+    pub merchant_id: [u8; 32],
+    /// Coverage (micro-USDC)
+    pub coverage: u64,
+    /// Premium paid (micro-USDC)
+    pub premium: u64,
+    /// Unix timestamp of binding
+    pub bound_at: i64,
+}
+
+/// What the claim's fraud proof established
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evidence {
+    pub fraud_detected: bool,
+    pub http_status: u16,
+    pub body_length: u64,
+    /// Hash of the fraud proof
+    pub proof_hash: [u8; 32],
+    /// Where the evidence was captured on the backend's chain (slot or
+    /// block number), 0 off-chain
+    pub captured_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimRequest {
+    pub claim_id: ClaimId,
+    /// SHA-256 of the merchant endpoint URL the claim is filed against
+    pub merchant_id: [u8; 32],
+    /// Refund owed (micro-USDC)
+    pub payout: u64,
+    pub evidence: Evidence,
+    /// Reference of the refund payment (transaction signature or hash)
+    pub refund_reference: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimStatus {
+    /// Never filed with this backend
+    Unknown,
+    /// Accepted and refunded, not yet settled
+    Paid { payout: u64, paid_at: i64 },
+    /// Paid and included in a settlement
+    Settled { payout: u64, paid_at: i64 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settlement {
+    /// Backend's index of the settlement batch
+    pub batch: u64,
+    /// Backend's commitment to the batch, zero if it has none
+    pub root: [u8; 32],
+    /// Claims settled, in the backend's order
+    pub claim_ids: Vec<ClaimId>,
+}
+
+/// A verdict on whether a paid claim was fraudulent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dispute {
+    pub claim_id: ClaimId,
+    pub fraud_detected: bool,
+    /// Hash of the evidence the verdict is based on
+    pub evidence_hash: [u8; 32],
+}
+
+/// One insurance backend
+pub trait InsuranceBackend {
+    /// Short name for reports, e.g. `"solana"`
+    fn name(&self) -> &str;
+
+    /// Price coverage for a merchant
+    fn quote(&mut self, request: &QuoteRequest) -> Result<Quote, InsuranceError> {
+        let _ = request;
+        Err(InsuranceError::Unsupported(Operation::Quote))
+    }
+
+    /// Turn a quote into a policy
+    fn bind(&mut self, quote: &Quote) -> Result<Policy, InsuranceError> {
+        let _ = quote;
+        Err(InsuranceError::Unsupported(Operation::Bind))
+    }
+
+    /// File a claim; a claim is filed once, later attempts fail with
+    /// [`InsuranceError::DuplicateClaim`]
+    fn claim(&mut self, claim: &ClaimRequest) -> Result<ClaimStatus, InsuranceError>;
+
+    fn claim_status(&mut self, claim_id: &ClaimId) -> Result<ClaimStatus, InsuranceError>;
+
+    /// Settle paid claims as one batch
+    fn settle(&mut self, claim_ids: &[ClaimId]) -> Result<Settlement, InsuranceError> {
+        let _ = claim_ids;
+        Err(InsuranceError::Unsupported(Operation::Settle))
+    }
+
+    /// Record a verdict on a filed claim
+    fn dispute(&mut self, dispute: &Dispute) -> Result<(), InsuranceError> {
+        let _ = dispute;
+        Err(InsuranceError::Unsupported(Operation::Dispute))
+    }
+}
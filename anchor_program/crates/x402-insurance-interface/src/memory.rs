@@ -0,0 +1,137 @@
+//! In-memory backend
+//!
+//! Implements every operation with plain maps and a caller-set clock, for
+//! testing SDK code and harnesses without a chain. Premiums are a flat
+//! rate of the coverage; settlements carry no commitment.
+
+use std::collections::HashMap;
+
+use crate::{
+    ClaimId, ClaimRequest, ClaimStatus, Dispute, InsuranceBackend, InsuranceError, Policy, Quote,
+    QuoteRequest, Settlement,
+};
+
+/// Seconds a quote stays bindable
+pub const QUOTE_TTL_SECONDS: i64 = 300;
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    /// Unix timestamp used for every operation
+    pub now: i64,
+    /// Premium in basis points of the coverage
+    pub premium_bps: u64,
+    quotes: u64,
+    claims: HashMap<ClaimId, ClaimStatus>,
+    disputes: HashMap<ClaimId, Vec<Dispute>>,
+    batches: u64,
+}
+
+impl MemoryBackend {
+    pub fn new(now: i64, premium_bps: u64) -> MemoryBackend {
+        MemoryBackend {
+            now,
+            premium_bps,
+            ..MemoryBackend::default()
+        }
+    }
+
+    /// Verdicts recorded on `claim_id`, in order
+    pub fn disputes(&self, claim_id: &ClaimId) -> &[Dispute] {
+        self.disputes.get(claim_id).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl InsuranceBackend for MemoryBackend {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn quote(&mut self, request: &QuoteRequest) -> Result<Quote, InsuranceError> {
+        self.quotes += 1;
+        let mut quote_id = [0u8; 32];
+        quote_id[..8].copy_from_slice(&self.quotes.to_le_bytes());
+        let premium = request
+            .coverage
+            .checked_mul(self.premium_bps)
+            .ok_or_else(|| InsuranceError::Rejected("coverage too large".into()))?
+            / 10_000;
+
+        Ok(Quote {
+            quote_id,
+            merchant_id: request.merchant_id,
+            coverage: request.coverage,
+            premium,
+            expires_at: self.now + QUOTE_TTL_SECONDS,
+        })
+    }
+
+    fn bind(&mut self, quote: &Quote) -> Result<Policy, InsuranceError> {
+        if self.now > quote.expires_at {
+            return Err(InsuranceError::QuoteExpired);
+        }
+        Ok(Policy {
+            policy_id: quote.quote_id,
+            merchant_id: quote.merchant_id,
+            coverage: quote.coverage,
+            premium: quote.premium,
+            bound_at: self.now,
+        })
+    }
+
+    fn claim(&mut self, claim: &ClaimRequest) -> Result<ClaimStatus, InsuranceError> {
+        if self.claims.contains_key(&claim.claim_id) {
+            return Err(InsuranceError::DuplicateClaim);
+        }
+        if !claim.evidence.fraud_detected {
+            return Err(InsuranceError::Rejected("no fraud detected".into()));
+        }
+        let status = ClaimStatus::Paid {
+            payout: claim.payout,
+            paid_at: self.now,
+        };
+        self.claims.insert(claim.claim_id, status);
+        Ok(status)
+    }
+
+    fn claim_status(&mut self, claim_id: &ClaimId) -> Result<ClaimStatus, InsuranceError> {
+        Ok(self
+            .claims
+            .get(claim_id)
+            .copied()
+            .unwrap_or(ClaimStatus::Unknown))
+    }
+
+    fn settle(&mut self, claim_ids: &[ClaimId]) -> Result<Settlement, InsuranceError> {
+        let mut settled = Vec::with_capacity(claim_ids.len());
+        for claim_id in claim_ids {
+            match self.claims.get(claim_id) {
+                Some(&ClaimStatus::Paid { payout, paid_at }) => {
+                    settled.push((*claim_id, ClaimStatus::Settled { payout, paid_at }))
+                }
+                Some(ClaimStatus::Settled { .. }) => {
+                    return Err(InsuranceError::Rejected("claim already settled".into()))
+                }
+                _ => return Err(InsuranceError::UnknownClaim),
+            }
+        }
+        self.claims.extend(settled);
+
+        self.batches += 1;
+        Ok(Settlement {
+            batch: self.batches - 1,
+            root: [0; 32],
+            claim_ids: claim_ids.to_vec(),
+        })
+    }
+
+    fn dispute(&mut self, dispute: &Dispute) -> Result<(), InsuranceError> {
+        if !self.claims.contains_key(&dispute.claim_id) {
+            return Err(InsuranceError::UnknownClaim);
+        }
+        self.disputes
+            .entry(dispute.claim_id)
+            .or_default()
+            .push(dispute.clone());
+        Ok(())
+    }
+}
@@ -1,7 +1,325 @@
+// Instruction handlers mirror the on-chain argument list, and the code
+// generated by #[program] inherits their arity
+#![allow(clippy::too_many_arguments)]
+// The read-only build leaves the handlers' helpers and imports unused
+#![cfg_attr(feature = "read-only", allow(dead_code, unused_imports))]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
 
 declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
+/// Seed prefix for attestation PDAs: `[ATTESTATION_SEED, claim_id]`
+#[constant]
+pub const ATTESTATION_SEED: &[u8] = b"attestation";
+
+/// `proof_hash` algorithm: Blake3 (zkEngine default)
+#[constant]
+pub const HASH_ALGO_BLAKE3: u8 = 0;
+
+/// `proof_hash` algorithm: Poseidon (recursion-friendly)
+#[constant]
+pub const HASH_ALGO_POSEIDON: u8 = 1;
+
+/// `Config::enabled_instructions` bit: attest_claim_proof /
+/// attest_derived_claim_proof / materialize_from_batch
+#[constant]
+pub const IX_ATTEST: u32 = 1 << 0;
+
+/// `Config::enabled_instructions` bit: archive_attestation
+#[constant]
+pub const IX_ARCHIVE: u32 = 1 << 1;
+
+/// Seed for the singleton config PDA
+#[constant]
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed for the singleton recent-attestations ring buffer PDA
+#[constant]
+pub const RECENT_ATTESTATIONS_SEED: &[u8] = b"recent";
+
+/// Number of entries kept in the recent-attestations ring buffer
+pub const RECENT_ATTESTATIONS_LEN: usize = 64;
+
+/// Seed prefix for monthly archive PDAs: `[ARCHIVE_SEED, period_le_bytes]`
+#[constant]
+pub const ARCHIVE_SEED: &[u8] = b"archive";
+
+/// Seed prefix for billing period aggregate PDAs: `[BILLING_PERIOD_SEED, period_le_bytes]`
+#[constant]
+pub const BILLING_PERIOD_SEED: &[u8] = b"billing";
+
+/// Seed prefix for response fingerprint PDAs: `[FINGERPRINT_SEED, fingerprint]`
+#[constant]
+pub const FINGERPRINT_SEED: &[u8] = b"fingerprint";
+
+/// Seed prefix for proving job PDAs: `[PROOF_JOB_SEED, claim_id]`
+#[constant]
+pub const PROOF_JOB_SEED: &[u8] = b"proof_job";
+
+/// Seed for the singleton layout migration status PDA
+#[constant]
+pub const MIGRATION_SEED: &[u8] = b"migration";
+
+/// SPL Token program (`TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`)
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133,
+    237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169,
+]);
+
+/// Token-2022 program (`TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb`)
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218, 182, 26, 252, 77,
+    131, 185, 13, 39, 254, 189, 249, 40, 216, 161, 139, 252,
+]);
+
+/// Associated Token Account program (`ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL`)
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
+    140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153,
+    218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
+]);
+
+/// SPL Token `TransferChecked` instruction tag
+const TRANSFER_CHECKED_TAG: u8 = 12;
+
+/// System program `AdvanceNonceAccount` instruction tag
+const ADVANCE_NONCE_ACCOUNT_TAG: u32 = 4;
+
+/// Seed prefix for merchant claim-feed PDAs: `[NOTIFICATION_SEED, merchant_id]`
+#[constant]
+pub const NOTIFICATION_SEED: &[u8] = b"notify";
+
+/// Seed for the singleton webhook signing key registry PDA
+#[constant]
+pub const WEBHOOK_SIGNER_SEED: &[u8] = b"webhook_signer";
+
+/// Seed prefix for merchant category PDAs: `[MERCHANT_CATEGORY_SEED, merchant_id]`
+#[constant]
+pub const MERCHANT_CATEGORY_SEED: &[u8] = b"merchant_category";
+
+/// Seed prefix for per-category aggregate PDAs: `[CATEGORY_STATS_SEED, &[category]]`
+#[constant]
+pub const CATEGORY_STATS_SEED: &[u8] = b"category";
+
+/// Merchant category for inference APIs; codes not defined here are free for operator use
+#[constant]
+pub const CATEGORY_INFERENCE_API: u8 = 1;
+
+/// Merchant category for data feeds
+#[constant]
+pub const CATEGORY_DATA_FEED: u8 = 2;
+
+/// Merchant category for storage endpoints
+#[constant]
+pub const CATEGORY_STORAGE: u8 = 3;
+
+/// Seed prefix for per-merchant paid-claim filters: `[CLAIM_BLOOM_SEED, merchant_id]`
+#[constant]
+pub const CLAIM_BLOOM_SEED: &[u8] = b"claim_bloom";
+
+/// Maximum size in bytes of a claim filter's bit array
+#[constant]
+pub const MAX_CLAIM_BLOOM_BYTES: u32 = 4096;
+
+/// Maximum number of bits a claim filter sets per claim
+#[constant]
+pub const MAX_CLAIM_BLOOM_HASHES: u8 = 16;
+
+/// Seed prefix for per-day claim index PDAs: `[DAY_INDEX_SEED, unix_day_le_bytes]`
+#[constant]
+pub const DAY_INDEX_SEED: &[u8] = b"day";
+
+/// Seed prefix for merchant dashboard PDAs: `[MERCHANT_DASHBOARD_SEED, merchant_id]`
+#[constant]
+pub const MERCHANT_DASHBOARD_SEED: &[u8] = b"merchant_dashboard";
+
+/// Days covered by a merchant dashboard's rolling window
+pub const DASHBOARD_WINDOW_DAYS: usize = 30;
+
+/// Claim ids listed in a day index; later claims are only counted and hashed
+pub const MAX_DAY_INDEX_CLAIMS: usize = 128;
+
+/// Seed prefix for merchant maintenance schedules: `[MAINTENANCE_SEED, merchant_id]`
+#[constant]
+pub const MAINTENANCE_SEED: &[u8] = b"maintenance";
+
+/// Maximum number of pending or active maintenance windows per merchant
+pub const MAX_MAINTENANCE_WINDOWS: usize = 8;
+
+/// Seed prefix for registered auditor PDAs: `[AUDITOR_SEED, auditor]`
+#[constant]
+pub const AUDITOR_SEED: &[u8] = b"auditor";
+
+/// Seed prefix for per-claim annotation list PDAs: `[ANNOTATIONS_SEED, claim_id]`
+#[constant]
+pub const ANNOTATIONS_SEED: &[u8] = b"annotations";
+
+/// Maximum number of annotations anchored to one attestation
+pub const MAX_ANNOTATIONS: usize = 8;
+
+/// Seed prefix for attester liveness PDAs: `[ATTESTER_SEED, attester]`
+#[constant]
+pub const ATTESTER_SEED: &[u8] = b"attester";
+
+/// Seed prefix for rotated attester key links: `[ATTESTER_LINK_SEED, attester]`
+#[constant]
+pub const ATTESTER_LINK_SEED: &[u8] = b"attester_link";
+
+/// Seed prefix for registered verdict source PDAs: `[VERDICT_SOURCE_SEED, source]`
+#[constant]
+pub const VERDICT_SOURCE_SEED: &[u8] = b"verdict_source";
+
+/// Seed prefix for per-claim verdict list PDAs: `[CLAIM_VERDICTS_SEED, claim_id]`
+#[constant]
+pub const CLAIM_VERDICTS_SEED: &[u8] = b"verdicts";
+
+/// Maximum number of verdicts recorded on one claim
+pub const MAX_CLAIM_VERDICTS: usize = 8;
+
+/// Verdict source kind: a zk proof pipeline
+#[constant]
+pub const VERDICT_KIND_ZK_PROOF: u8 = 0;
+
+/// Verdict source kind: an oracle refetching the merchant response
+#[constant]
+pub const VERDICT_KIND_ORACLE: u8 = 1;
+
+/// Verdict source kind: a manual claims adjuster
+#[constant]
+pub const VERDICT_KIND_ADJUSTER: u8 = 2;
+
+/// Seed for the singleton verified build record PDA
+#[constant]
+pub const UPGRADE_INFO_SEED: &[u8] = b"upgrade_info";
+
+/// Seed prefix for proof archive location PDAs: `[PROOF_URI_SEED, claim_id]`
+#[constant]
+pub const PROOF_URI_SEED: &[u8] = b"proof_uri";
+
+/// Seed prefix for cross-chain attestation link PDAs: `[FOREIGN_LINK_SEED, claim_id]`
+#[constant]
+pub const FOREIGN_LINK_SEED: &[u8] = b"foreign_link";
+
+/// Seed for the singleton settlement batch ledger PDA
+#[constant]
+pub const SETTLEMENT_LEDGER_SEED: &[u8] = b"settlement";
+
+/// Seed for the singleton settlement queue PDA
+#[constant]
+pub const SETTLEMENT_QUEUE_SEED: &[u8] = b"settlement_queue";
+
+/// Seed prefix for settlement batch PDAs: `[SETTLEMENT_BATCH_SEED, batch_index_le_bytes]`
+#[constant]
+pub const SETTLEMENT_BATCH_SEED: &[u8] = b"settlement_batch";
+
+/// Seed prefix for aggregated proof PDAs: `[AGGREGATE_SEED, batch_root]`
+#[constant]
+pub const AGGREGATE_SEED: &[u8] = b"aggregate";
+
+/// Seed prefix for memo provenance PDAs: `[LEGACY_MEMO_SEED, claim_id]`
+#[constant]
+pub const LEGACY_MEMO_SEED: &[u8] = b"legacy_memo";
+
+/// Maximum length in bytes of a registered proof URI
+pub const MAX_PROOF_URI_LEN: usize = 200;
+
+/// Seed for the singleton proof-of-reserve attestation PDA
+#[constant]
+pub const RESERVES_SEED: &[u8] = b"reserves";
+
+/// Seed for the singleton registry of backend durable nonce accounts
+#[constant]
+pub const NONCE_REGISTRY_SEED: &[u8] = b"nonces";
+
+/// Maximum number of nonce accounts in the registry
+pub const MAX_NONCE_ACCOUNTS: usize = 16;
+
+/// Size of a system program nonce account
+pub const NONCE_ACCOUNT_LEN: usize = 80;
+
+/// Current `ProofAttestation` account layout version
+///
+/// Version 1 is the original layout (`ProofAttestationV1`). Later
+/// versions only append fields, see `attestation_layout_version`.
+#[constant]
+pub const ATTESTATION_LAYOUT_VERSION: u8 = 8;
+
+/// Prefix of a proof URI replaced by `unlink_claimant`, followed by the
+/// base58 SHA-256 of salt ‖ original URI
+#[constant]
+pub const UNLINKED_URI_PREFIX: &[u8] = b"unlinked:";
+
+/// Seed prefix for public-inputs schema PDAs: `[SCHEMA_SEED, schema_id]`
+#[constant]
+pub const SCHEMA_SEED: &[u8] = b"schema";
+
+/// Schema id of the built-in layout `[fraud_detected, http_status, body_length]`,
+/// which needs no registry account
+#[constant]
+pub const DEFAULT_SCHEMA_ID: u16 = 0;
+
+/// Evidence inputs a schema can describe; the last public input is
+/// always the payout amount
+pub const MAX_SCHEMA_FIELDS: usize = 3;
+
+/// Maximum length in bytes of a schema field name
+pub const MAX_SCHEMA_FIELD_NAME_LEN: usize = 32;
+
+/// Seed for the singleton program statistics PDA
+#[constant]
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// Seed for the singleton cluster identity PDA
+#[constant]
+pub const CLUSTER_SEED: &[u8] = b"cluster";
+
+/// Seed prefix for audit report checksum PDAs: `[REPORT_HASH_SEED, kind, period]`
+#[constant]
+pub const REPORT_HASH_SEED: &[u8] = b"report_hash";
+
+/// Audit report kind: one row per attested claim
+#[constant]
+pub const REPORT_KIND_CLAIMS: u8 = 0;
+
+/// Audit report kind: one row per payout
+#[constant]
+pub const REPORT_KIND_PAYOUTS: u8 = 1;
+
+/// Audit report kind: one row per recorded fraud verdict
+#[constant]
+pub const REPORT_KIND_VERDICTS: u8 = 2;
+
+/// Layout version buckets in `ProgramStats` (indexed by version)
+pub const STATS_LAYOUT_BUCKETS: usize = 16;
+
+/// Layout version buckets of `ProgramStatsV1`, before `migrate_program_stats`
+pub const STATS_V1_LAYOUT_BUCKETS: usize = 8;
+
+/// Circuit version buckets in `ProgramStats`; the last holds all higher versions
+pub const STATS_CIRCUIT_BUCKETS: usize = 16;
+
+/// First attestation layout counted into the `ProgramStats` circuit buckets
+#[constant]
+pub const STATS_CIRCUIT_LAYOUT_VERSION: u8 = 3;
+
+/// `ProofAttestation::sequence` of records migrated from layout 1,
+/// which predates sequence numbers, and of imported historical records
+#[constant]
+pub const LEGACY_SEQUENCE: u64 = u64::MAX;
+
+/// Domain tag for `historical_leaf`
+pub const HISTORICAL_LEAF_DOMAIN: &[u8] = b"x402-historical-claim:v1";
+
+/// Domain tag for `aggregate_leaf`
+pub const AGGREGATE_LEAF_DOMAIN: &[u8] = b"x402-aggregate-claim:v1";
+
+#[cfg(not(feature = "read-only"))]
 #[program]
 pub mod x402_attestation {
     use super::*;
@@ -10,36 +328,507 @@ pub mod x402_attestation {
     ///
     /// This stores the proof hash and metadata permanently on Solana,
     /// making it publicly auditable without storing the full proof.
+    ///
+    /// `related_claim_id` optionally links the claim to an earlier attested
+    /// incident (e.g., the same merchant/user pair); the referenced
+    /// attestation must be passed as `related_attestation`.
+    ///
+    /// `proof_hash_secondary` is the proof hash from the independent
+    /// secondary pipeline. It is required at or above
+    /// `Config::dual_proof_threshold`, and whenever given the configured
+    /// secondary prover must co-sign, approving the same public inputs.
+    ///
+    /// `circuit_version` identifies the zkEngine circuit that produced the
+    /// proof, so old circuits can be phased out once `ProgramStats` shows
+    /// no new attestations from them.
+    ///
+    /// `schema_id` names the layout of the evidence inputs
+    /// (`public_inputs[..3]`). Any id other than `DEFAULT_SCHEMA_ID` needs
+    /// its registered `schema`, and the inputs must fit its field count
+    /// and ranges.
+    ///
+    /// The response fingerprint is recorded separately, with
+    /// `record_response_fingerprint`.
+    ///
+    /// The attestation is stamped with its billing period (`month_id` of
+    /// the cluster time, UTC) and counted into that period's
+    /// `BillingPeriod`, which is created on the period's first attestation.
+    ///
+    /// Passing the `day_index` of the current UTC day (`unix_day` of the
+    /// cluster time) adds the claim to it; like the billing period, it
+    /// fails its seeds check across midnight and must be rebuilt.
+    ///
+    /// The merchant's `maintenance_schedule` PDA is always passed, and
+    /// evidence captured inside one of its declared windows is rejected
+    /// with `MaintenanceWindowActive`, since downtime announced in advance
+    /// is outside coverage. A merchant that never declared a window has
+    /// no schedule account yet, which counts as no windows.
+    ///
+    /// With `Config::verdict_threshold` set, the claim's `claim_verdicts`
+    /// must be passed and its fraud verdicts must weigh at least the
+    /// threshold; the verdicts stay on the claim for audit.
+    ///
+    /// Passing the claim's `proof_job` closes it and emits
+    /// `ProofJobCompleted` with the end-to-end latency.
+    ///
+    /// With `Config::require_refund_transfer` set, the transaction must
+    /// also carry a `TransferChecked` of the payout amount in
+    /// `Config::payout_mint` from `Config::refund_source` to the
+    /// associated token account of the passed `claimant`, so the refund
+    /// and its attestation land atomically while the funds stay in the
+    /// integrator's custody. The destination is stored on the attestation.
+    ///
+    /// A transaction sent with a durable nonce must advance one of the
+    /// `NonceRegistry` accounts, passed as `nonce_registry`.
     pub fn attest_claim_proof(
         ctx: Context<AttestProof>,
         claim_id: [u8; 32],
         proof_hash: [u8; 32],
+        hash_algo: u8,
+        public_inputs: [u64; 4],
+        evidence_slot: u64,
+        refund_signature: [u8; 64],
+        merchant_id: [u8; 32],
+        related_claim_id: Option<[u8; 32]>,
+        proof_hash_secondary: Option<[u8; 32]>,
+        circuit_version: u16,
+        schema_id: u16,
+    ) -> Result<()> {
+        record_attestation(
+            ctx,
+            claim_id,
+            proof_hash,
+            hash_algo,
+            public_inputs,
+            evidence_slot,
+            refund_signature,
+            merchant_id,
+            related_claim_id,
+            proof_hash_secondary,
+            circuit_version,
+            schema_id,
+            None,
+        )
+    }
+
+    /// Attest a claim proof with an on-chain checked claim_id
+    ///
+    /// Same as `attest_claim_proof`, but the claim_id must equal
+    /// `derive_claim_id(payment_signature, endpoint_hash, claimant)`, so one
+    /// incident can never be attested under two different IDs. A refund
+    /// transfer required by the config must go to this claimant.
+    pub fn attest_derived_claim_proof(
+        ctx: Context<AttestProof>,
+        claim_id: [u8; 32],
+        payment_signature: [u8; 64],
+        endpoint_hash: [u8; 32],
+        claimant: Pubkey,
+        proof_hash: [u8; 32],
+        hash_algo: u8,
         public_inputs: [u64; 4],
+        evidence_slot: u64,
         refund_signature: [u8; 64],
+        merchant_id: [u8; 32],
+        related_claim_id: Option<[u8; 32]>,
+        proof_hash_secondary: Option<[u8; 32]>,
+        circuit_version: u16,
+        schema_id: u16,
+    ) -> Result<()> {
+        require!(
+            claim_id == derive_claim_id(&payment_signature, &endpoint_hash, &claimant),
+            ErrorCode::ClaimIdMismatch
+        );
+
+        record_attestation(
+            ctx,
+            claim_id,
+            proof_hash,
+            hash_algo,
+            public_inputs,
+            evidence_slot,
+            refund_signature,
+            merchant_id,
+            related_claim_id,
+            proof_hash_secondary,
+            circuit_version,
+            schema_id,
+            Some(claimant),
+        )
+    }
+
+    /// Record the response fingerprint of an attested claim (its attester only)
+    ///
+    /// `response_fingerprint` is a hash of the fraudulent response body,
+    /// stored on the attestation and counted in its `FingerprintIndex`,
+    /// which is created on first use. The claim that brings a fingerprint
+    /// to `Config::fingerprint_threshold` emits `RepeatFailureDetected`,
+    /// telling a systemic merchant failure apart from one-off incidents.
+    /// Sent after the attestation, usually in the same transaction; a
+    /// fingerprint can be recorded once per claim.
+    pub fn record_response_fingerprint(
+        ctx: Context<RecordResponseFingerprint>,
+        claim_id: [u8; 32],
+        response_fingerprint: [u8; 32],
     ) -> Result<()> {
+        require!(
+            ctx.accounts.config.is_enabled(IX_ATTEST),
+            ErrorCode::InstructionDisabled
+        );
         let attestation = &mut ctx.accounts.attestation;
+        require!(
+            response_fingerprint != [0; 32] && attestation.response_fingerprint == [0; 32],
+            ErrorCode::InvalidResponseFingerprint
+        );
+        attestation.response_fingerprint = response_fingerprint;
+
+        let index = &mut ctx.accounts.fingerprint_index;
+        if index.count == 0 {
+            index.fingerprint = response_fingerprint;
+            index.first_claim_id = claim_id;
+            index.bump = ctx.bumps.fingerprint_index;
+        }
+        index.count = index
+            .count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        index.last_attested_at = index.last_attested_at.max(attestation.attested_at);
+
+        let threshold = ctx.accounts.config.fingerprint_threshold;
+        if threshold > 0 && index.count == threshold {
+            emit!(RepeatFailureDetected {
+                fingerprint: response_fingerprint,
+                count: index.count,
+                first_claim_id: index.first_claim_id,
+                merchant_id: attestation.merchant_id,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Record that a claim entered the proving pipeline
+    ///
+    /// Creates a `ProofJob` holding the current slot and time, paid for by
+    /// the attester who will later attest the claim and close the job, so
+    /// settlement latency is measured from cluster time alone.
+    pub fn start_proof_job(ctx: Context<StartProofJob>, claim_id: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let job = &mut ctx.accounts.proof_job;
+        job.claim_id = claim_id;
+        job.attester = ctx.accounts.attester.key();
+        job.submitted_slot = clock.slot;
+        job.submitted_at = clock.unix_timestamp;
+        job.bump = ctx.bumps.proof_job;
+
+        Ok(())
+    }
+
+    /// Attest one recursive proof covering a batch of claims
+    ///
+    /// `batch_root` is the `merkle_root` over the claims' `aggregate_leaf`s;
+    /// `count` and `total_payout` bound what `materialize_from_batch` can
+    /// later create from it. Every claim of the batch is proven by circuit
+    /// `circuit_version` under schema `schema_id`.
+    pub fn attest_aggregated_proof(
+        ctx: Context<AttestAggregatedProof>,
+        batch_root: [u8; 32],
+        proof_hash: [u8; 32],
+        hash_algo: u8,
+        count: u32,
+        total_payout: u64,
+        circuit_version: u16,
+        schema_id: u16,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.is_enabled(IX_ATTEST), ErrorCode::InstructionDisabled);
+        require!(config.allows_hash_algo(hash_algo), ErrorCode::HashAlgoNotAllowed);
+        require!(
+            count > 0 && batch_root != [0; 32],
+            ErrorCode::InvalidPublicInputs
+        );
+
+        let clock = Clock::get()?;
+        let aggregate = &mut ctx.accounts.aggregate_proof;
+        aggregate.batch_root = batch_root;
+        aggregate.proof_hash = proof_hash;
+        aggregate.hash_algo = hash_algo;
+        aggregate.count = count;
+        aggregate.total_payout = total_payout;
+        aggregate.attester = ctx.accounts.authority.key();
+        aggregate.attested_at = clock.unix_timestamp;
+        aggregate.attested_slot = clock.slot;
+        aggregate.circuit_version = circuit_version;
+        aggregate.schema_id = schema_id;
+        aggregate.bump = ctx.bumps.aggregate_proof;
 
+        emit!(AggregatedProofAttested {
+            batch_root,
+            proof_hash,
+            count,
+            total_payout,
+            attester: aggregate.attester,
+        });
+
+        Ok(())
+    }
+
+    /// Create the attestation of one claim of an aggregated proof (permissionless)
+    ///
+    /// `inclusion_proof` is the Merkle path of the claim's `aggregate_leaf`
+    /// to the batch root. The record takes the batch's proof hash,
+    /// attester, circuit, schema and `attested_at`, and a fresh sequence
+    /// number. The config's per-claim rules apply as in `attest_claim_proof`
+    /// (see `check_claim_rules`): a payout at or above `dual_proof_threshold`
+    /// can't come from a batch, the evidence age is measured at the batch's
+    /// attestation, and a refund transfer required by the config must be
+    /// in this transaction. Disabling `IX_ATTEST` disables this too.
+    pub fn materialize_from_batch(
+        ctx: Context<MaterializeFromBatch>,
+        claim_id: [u8; 32],
+        public_inputs: [u64; 4],
+        evidence_slot: u64,
+        refund_signature: [u8; 64],
+        merchant_id: [u8; 32],
+        inclusion_proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.is_enabled(IX_ATTEST), ErrorCode::InstructionDisabled);
+        let aggregate = &ctx.accounts.aggregate_proof;
+        let payout = public_inputs[3];
+
+        let leaf = aggregate_leaf(
+            &claim_id,
+            &public_inputs,
+            evidence_slot,
+            &refund_signature,
+            &merchant_id,
+        );
+        require!(
+            merkle_root(leaf, &inclusion_proof) == aggregate.batch_root,
+            ErrorCode::InvalidInclusionProof
+        );
+        require!(
+            aggregate.materialized < aggregate.count
+                && aggregate
+                    .materialized_payout
+                    .checked_add(payout)
+                    .is_some_and(|total| total <= aggregate.total_payout),
+            ErrorCode::AggregateExhausted
+        );
+
+        let refund_destination = check_claim_rules(
+            config,
+            ClaimRuleAccounts {
+                attester: aggregate.attester,
+                secondary_prover: None,
+                attester_status: None,
+                secondary_prover_status: None,
+                schema: ctx.accounts.schema.as_ref(),
+                claim_verdicts: ctx.accounts.claim_verdicts.as_deref(),
+                maintenance_schedule: &ctx.accounts.maintenance_schedule,
+                claimant: ctx.accounts.claimant.as_deref(),
+                instructions: ctx.accounts.instructions.as_deref(),
+            },
+            &claim_id,
+            aggregate.hash_algo,
+            &public_inputs,
+            evidence_slot,
+            aggregate.attested_slot,
+            &merchant_id,
+            false,
+            aggregate.schema_id,
+            None,
+        )?;
+
+        let clock = Clock::get()?;
+        let sequence = ctx.accounts.recent_attestations.load_mut()?.push(RecentAttestation {
+            claim_id,
+            payout_amount: payout,
+            slot: clock.slot,
+        })?;
+
+        enqueue_settlement(
+            ctx.accounts.settlement_queue.as_deref_mut(),
+            config,
+            sequence,
+            clock.unix_timestamp,
+        )?;
+
+        let aggregate = &mut ctx.accounts.aggregate_proof;
+        aggregate.materialized += 1;
+        aggregate.materialized_payout += payout;
+
+        let attestation = &mut ctx.accounts.attestation;
         attestation.claim_id = claim_id;
-        attestation.proof_hash = proof_hash;
+        attestation.proof_hash = aggregate.proof_hash;
+        attestation.hash_algo = aggregate.hash_algo;
         attestation.public_inputs = public_inputs;
+        attestation.evidence_slot = evidence_slot;
         attestation.refund_tx_sig = refund_signature;
-        attestation.attested_at = Clock::get()?.unix_timestamp;
-        attestation.attester = ctx.accounts.authority.key();
+        attestation.merchant_id = merchant_id;
+        attestation.attested_at = aggregate.attested_at;
+        attestation.attester = aggregate.attester;
+        attestation.sequence = sequence;
         attestation.bump = ctx.bumps.attestation;
+        attestation.evm_digest = attestation.evm_struct_hash();
+        attestation.circuit_version = aggregate.circuit_version;
+        attestation.schema_id = aggregate.schema_id;
+        attestation.billing_period_id = month_id(aggregate.attested_at);
+        attestation.refund_destination = refund_destination;
+
+        let billing = &mut ctx.accounts.billing_period;
+        if billing.count == 0 {
+            billing.period = attestation.billing_period_id;
+            billing.bump = ctx.bumps.billing_period;
+        }
+        billing.count = billing
+            .count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        billing.total_payout = billing
+            .total_payout
+            .checked_add(payout)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let stats = &mut ctx.accounts.program_stats;
+        stats.add(StatsBucket::Layout, u16::from(ATTESTATION_LAYOUT_VERSION))?;
+        stats.add(StatsBucket::Circuit, aggregate.circuit_version)?;
 
         emit!(ProofAttested {
             claim_id,
-            proof_hash,
-            payout_amount: public_inputs[3],
+            proof_hash: attestation.proof_hash,
+            hash_algo: attestation.hash_algo,
+            payout_amount: payout,
             attested_at: attestation.attested_at,
+            sequence,
+            evm_digest: attestation.evm_digest,
+        });
+        emit!(ClaimMaterialized {
+            claim_id,
+            batch_root: aggregate.batch_root,
+            materialized: aggregate.materialized,
         });
 
-        msg!(
-            "Proof attested: claim_id={}, payout={} micro-USDC",
-            bs58::encode(&claim_id).into_string(),
-            public_inputs[3]
+        Ok(())
+    }
+
+    /// Import a claim paid before the program existed (admin only)
+    ///
+    /// Open until `Config::import_until`, and only for records of the
+    /// dataset committed to by `Config::import_root`: `proof` is the
+    /// Merkle path of the record's `historical_leaf`. The attestation
+    /// keeps its original `attested_at` and is marked `imported`. It gets
+    /// `LEGACY_SEQUENCE`, so it stays out of the recent-attestations feed,
+    /// and is counted into the `BillingPeriod` of its original month.
+    pub fn import_historical_attestation(
+        mut ctx: Context<ImportHistoricalAttestation>,
+        claim_id: [u8; 32],
+        attested_at: i64,
+        proof_hash: [u8; 32],
+        hash_algo: u8,
+        public_inputs: [u64; 4],
+        refund_signature: [u8; 64],
+        merchant_id: [u8; 32],
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < config.import_until, ErrorCode::ImportClosed);
+        require!(attested_at <= now, ErrorCode::InvalidConfig);
+
+        let leaf = historical_leaf(
+            &claim_id,
+            attested_at,
+            &proof_hash,
+            hash_algo,
+            &public_inputs,
+            &refund_signature,
+            &merchant_id,
+        );
+        require!(
+            merkle_root(leaf, &proof) == config.import_root,
+            ErrorCode::InvalidImportProof
         );
 
+        let accounts = &mut ctx.accounts;
+        record_import(
+            &mut accounts.attestation,
+            ctx.bumps.attestation,
+            &mut accounts.billing_period,
+            ctx.bumps.billing_period,
+            &mut accounts.program_stats,
+            accounts.admin.key(),
+            claim_id,
+            attested_at,
+            proof_hash,
+            hash_algo,
+            public_inputs,
+            refund_signature,
+            merchant_id,
+        )?;
+
+        emit!(HistoricalAttestationImported {
+            claim_id,
+            attested_at,
+            payout_amount: public_inputs[3],
+        });
+
+        Ok(())
+    }
+
+    /// Import a claim recorded as an SPL Memo before the program existed (admin only)
+    ///
+    /// The admin parses the memo off-chain and vouches for the payload by
+    /// signing; there is no dataset root to check it against. Open until
+    /// `Config::import_until`, and otherwise recorded like
+    /// `import_historical_attestation`. The memo's transaction signature
+    /// is kept in a `LegacyMemo` next to the attestation, which marks the
+    /// record as memo-sourced.
+    pub fn claim_legacy_attestation(
+        mut ctx: Context<ClaimLegacyAttestation>,
+        memo_signature: [u8; 64],
+        claim_id: [u8; 32],
+        attested_at: i64,
+        proof_hash: [u8; 32],
+        hash_algo: u8,
+        public_inputs: [u64; 4],
+        refund_signature: [u8; 64],
+        merchant_id: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(now < ctx.accounts.config.import_until, ErrorCode::ImportClosed);
+        require!(attested_at <= now, ErrorCode::InvalidConfig);
+
+        let accounts = &mut ctx.accounts;
+        record_import(
+            &mut accounts.attestation,
+            ctx.bumps.attestation,
+            &mut accounts.billing_period,
+            ctx.bumps.billing_period,
+            &mut accounts.program_stats,
+            accounts.admin.key(),
+            claim_id,
+            attested_at,
+            proof_hash,
+            hash_algo,
+            public_inputs,
+            refund_signature,
+            merchant_id,
+        )?;
+
+        let legacy = &mut accounts.legacy_memo;
+        legacy.claim_id = claim_id;
+        legacy.memo_signature = memo_signature;
+        legacy.claimed_at = now;
+        legacy.bump = ctx.bumps.legacy_memo;
+
+        emit!(LegacyAttestationClaimed {
+            claim_id,
+            memo_signature,
+            attested_at,
+            payout_amount: public_inputs[3],
+        });
+
         Ok(())
     }
 
@@ -49,76 +838,5353 @@ pub mod x402_attestation {
     pub fn query_attestation(
         ctx: Context<QueryAttestation>,
     ) -> Result<ProofAttestation> {
-        let attestation = &ctx.accounts.attestation;
-
-        msg!(
-            "Attestation found: claim_id={}, proof_hash={}, payout={}",
-            bs58::encode(&attestation.claim_id).into_string(),
-            bs58::encode(&attestation.proof_hash).into_string(),
-            attestation.public_inputs[3]
-        );
-
-        Ok(attestation.clone())
+        load_attestation(ctx)
     }
-}
 
-#[derive(Accounts)]
-#[instruction(claim_id: [u8; 32])]
-pub struct AttestProof<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + ProofAttestation::INIT_SPACE,
-        seeds = [b"attestation", claim_id.as_ref()],
-        bump
-    )]
-    pub attestation: Account<'info, ProofAttestation>,
+    /// Initialize the program config
+    ///
+    /// The signer becomes the admin for privileged maintenance instructions.
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        archive_ttl_seconds: i64,
+    ) -> Result<()> {
+        require!(archive_ttl_seconds >= 0, ErrorCode::InvalidConfig);
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        let config = &mut ctx.accounts.config;
+        config.set_inner(Config::new(
+            ctx.accounts.admin.key(),
+            archive_ttl_seconds,
+            ctx.bumps.config,
+        ));
 
-    pub system_program: Program<'info, System>,
-}
+        msg!("Config initialized: admin={}", config.admin);
 
-#[derive(Accounts)]
-pub struct QueryAttestation<'info> {
-    pub attestation: Account<'info, ProofAttestation>,
-}
+        Ok(())
+    }
 
-#[account]
-#[derive(InitSpace)]
-pub struct ProofAttestation {
-    /// Unique claim identifier (32 bytes)
-    pub claim_id: [u8; 32],
+    /// Create the recent-attestations ring buffer (admin only)
+    ///
+    /// Must exist before the first attestation; it assigns sequence numbers.
+    pub fn initialize_recent_attestations(
+        ctx: Context<InitializeRecentAttestations>,
+    ) -> Result<()> {
+        ctx.accounts.recent_attestations.load_init()?.bump = ctx.bumps.recent_attestations;
+        Ok(())
+    }
 
-    /// Blake3 hash of the zkEngine proof (32 bytes)
-    pub proof_hash: [u8; 32],
+    /// Grow a recent-attestations buffer created before it was zero-copy (admin only)
+    ///
+    /// The zero-copy layout pads the buffer to 8-byte alignment. Only the
+    /// padding is added; a buffer already at full size is left untouched.
+    pub fn resize_recent_attestations(_ctx: Context<ResizeRecentAttestations>) -> Result<()> {
+        Ok(())
+    }
 
-    /// Public inputs to the proof verification:
-    /// [fraud_detected, http_status, body_length, payout_amount]
-    pub public_inputs: [u64; 4],
+    /// Create the settlement queue (admin only)
+    ///
+    /// Starts from the attestations not yet in a settlement batch. Their
+    /// age is unknown, so the oldest is taken to be pending since now.
+    pub fn initialize_settlement_queue(ctx: Context<InitializeSettlementQueue>) -> Result<()> {
+        let enqueued = ctx.accounts.recent_attestations.load()?.total;
+        let settled = ctx
+            .accounts
+            .settlement_ledger
+            .as_ref()
+            .map_or(0, |ledger| ledger.next_sequence);
 
-    /// Solana transaction signature of the USDC refund (64 bytes)
-    pub refund_tx_sig: [u8; 64],
+        let queue = &mut ctx.accounts.settlement_queue;
+        queue.enqueued = enqueued;
+        queue.settled = settled;
+        queue.pending = enqueued.saturating_sub(settled);
+        queue.oldest_pending_at = if queue.pending > 0 {
+            Clock::get()?.unix_timestamp
+        } else {
+            0
+        };
+        queue.high_water_mark = queue.pending;
+        queue.bump = ctx.bumps.settlement_queue;
+        Ok(())
+    }
 
-    /// Unix timestamp when attestation was created
-    pub attested_at: i64,
+    /// Create the program statistics account (admin only)
+    ///
+    /// Must exist before the first attestation. Attestations that predate
+    /// it are counted into their layout bucket by `scan_and_tag`.
+    pub fn initialize_program_stats(ctx: Context<InitializeProgramStats>) -> Result<()> {
+        ctx.accounts.program_stats.bump = ctx.bumps.program_stats;
+        Ok(())
+    }
 
-    /// Public key of the attester (backend wallet)
-    pub attester: Pubkey,
+    /// Grow program statistics created with `STATS_V1_LAYOUT_BUCKETS` (admin only)
+    ///
+    /// Counts are kept and the added layout buckets start at zero; the
+    /// admin tops up rent. Run it right after upgrading the program;
+    /// statistics already in the current layout are left untouched.
+    pub fn migrate_program_stats(ctx: Context<MigrateProgramStats>) -> Result<()> {
+        let account = ctx.accounts.program_stats.to_account_info();
+        let space = 8 + ProgramStats::INIT_SPACE;
+        let stats = {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.starts_with(&ProgramStats::DISCRIMINATOR),
+                ErrorCode::UnknownAccountLayout
+            );
+            if data.len() == space {
+                return Ok(());
+            }
+            require!(
+                data.len() == 8 + ProgramStatsV1::INIT_SPACE,
+                ErrorCode::UnknownAccountLayout
+            );
+            ProgramStatsV1::deserialize(&mut &data[8..])?.upgrade()
+        };
 
-    /// PDA bump seed
-    pub bump: u8,
-}
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        account.realloc(space, true)?;
+        stats.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
 
-#[event]
-pub struct ProofAttested {
+        Ok(())
+    }
+
+    /// Record which cluster this deployment runs on (admin only, once)
+    ///
+    /// Programs can't read the genesis hash, so the admin states it;
+    /// `assert_cluster` then holds transactions to it.
+    pub fn initialize_cluster_info(
+        ctx: Context<InitializeClusterInfo>,
+        genesis_hash: [u8; 32],
+    ) -> Result<()> {
+        let info = &mut ctx.accounts.cluster_info;
+        info.genesis_hash = genesis_hash;
+        info.recorded_at = Clock::get()?.unix_timestamp;
+        info.bump = ctx.bumps.cluster_info;
+        Ok(())
+    }
+
+    /// Fail unless this deployment's cluster has `genesis_hash` (permissionless)
+    ///
+    /// Tooling prepends it with the genesis hash of the cluster it signs
+    /// for, so a transaction signed for devnet, e.g. against a durable
+    /// nonce, fails as a whole if it is submitted to mainnet.
+    pub fn assert_cluster(ctx: Context<AssertCluster>, genesis_hash: [u8; 32]) -> Result<()> {
+        check_cluster(ctx, genesis_hash)
+    }
+
+    /// Anchor the checksum of an exported audit report (admin only)
+    ///
+    /// `kind` is one of the `REPORT_KIND_*` codes and `period` the month
+    /// covered (YYYYMM). One report per kind and month: a re-export must
+    /// reproduce the anchored checksum to be accepted by its readers.
+    pub fn report_hash(
+        ctx: Context<ReportHash>,
+        kind: u8,
+        period: u32,
+        checksum: [u8; 32],
+        row_count: u64,
+    ) -> Result<()> {
+        require!(kind <= REPORT_KIND_VERDICTS, ErrorCode::InvalidConfig);
+        let now = Clock::get()?.unix_timestamp;
+        require!(period < month_id(now), ErrorCode::ReportPeriodOpen);
+
+        let report = &mut ctx.accounts.report;
+        report.kind = kind;
+        report.period = period;
+        report.checksum = checksum;
+        report.row_count = row_count;
+        report.reported_by = ctx.accounts.admin.key();
+        report.reported_at = now;
+        report.bump = ctx.bumps.report;
+
+        emit!(ReportHashAnchored {
+            kind,
+            period,
+            checksum,
+            row_count,
+        });
+
+        Ok(())
+    }
+
+    /// Grow a config created by an earlier program version (admin only)
+    ///
+    /// Config fields are appended before `bump`, so an older config is a
+    /// run of the current leading fields followed by its bump, and doesn't
+    /// deserialize until migrated. Those fields are kept, the ones it lacks
+    /// take their `initialize_config` values and the bump moves to the
+    /// end; the admin tops up rent. Run it right after upgrading the
+    /// program; a config already in the current layout is left untouched.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let account = ctx.accounts.config.to_account_info();
+        let space = 8 + Config::INIT_SPACE;
+        let (config, previous) = {
+            let data = account.try_borrow_data()?;
+            require!(
+                data.starts_with(&Config::DISCRIMINATOR),
+                ErrorCode::UnknownAccountLayout
+            );
+            if data.len() == space {
+                return Ok(());
+            }
+            let fields = data.len().saturating_sub(9);
+            require!(
+                data.len() < space && is_config_prefix(fields),
+                ErrorCode::UnknownAccountLayout
+            );
+
+            let mut current =
+                Config::new(Pubkey::default(), 0, data[data.len() - 1]).try_to_vec()?;
+            current[..fields].copy_from_slice(&data[8..8 + fields]);
+            (Config::deserialize(&mut &current[..])?, data.len())
+        };
+        require_keys_eq!(
+            config.admin,
+            ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        account.realloc(space, false)?;
+        config.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+        msg!("Config migrated: {} -> {} bytes", previous, space);
+
+        Ok(())
+    }
+
+    /// Update config parameters (admin only)
+    ///
+    /// Parameters passed as `None` are left unchanged.
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        new_admin: Option<Pubkey>,
+        archive_ttl_seconds: Option<i64>,
+        emit_evidence_events: Option<bool>,
+        allowed_hash_algos: Option<u8>,
+        max_claim_age_slots: Option<u64>,
+        enabled_instructions: Option<u32>,
+        dual_proof_threshold: Option<u64>,
+        secondary_prover: Option<Pubkey>,
+        reserve_auditor: Option<Pubkey>,
+        reserve_interval_seconds: Option<i64>,
+        attester_offline_after_slots: Option<u64>,
+        require_refund_transfer: Option<bool>,
+        payout_mint: Option<Pubkey>,
+        import_root: Option<[u8; 32]>,
+        import_until: Option<i64>,
+        fingerprint_threshold: Option<u64>,
+        retention_seconds: Option<i64>,
+        require_region_diversity: Option<bool>,
+        verdict_threshold: Option<u64>,
+        max_pending_settlements: Option<u64>,
+        refund_source: Option<Pubkey>,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        if let Some(admin) = new_admin {
+            config.admin = admin;
+        }
+        if let Some(ttl) = archive_ttl_seconds {
+            require!(ttl >= 0, ErrorCode::InvalidConfig);
+            config.archive_ttl_seconds = ttl;
+        }
+        if let Some(enabled) = emit_evidence_events {
+            config.emit_evidence_events = enabled;
+        }
+        if let Some(mask) = allowed_hash_algos {
+            require!(mask != 0, ErrorCode::InvalidConfig);
+            config.allowed_hash_algos = mask;
+        }
+        if let Some(slots) = max_claim_age_slots {
+            config.max_claim_age_slots = slots;
+        }
+        if let Some(flags) = enabled_instructions {
+            config.enabled_instructions = flags;
+        }
+        if let Some(threshold) = dual_proof_threshold {
+            config.dual_proof_threshold = threshold;
+        }
+        if let Some(prover) = secondary_prover {
+            config.secondary_prover = prover;
+        }
+        if let Some(auditor) = reserve_auditor {
+            config.reserve_auditor = auditor;
+        }
+        if let Some(interval) = reserve_interval_seconds {
+            require!(interval >= 0, ErrorCode::InvalidConfig);
+            config.reserve_interval_seconds = interval;
+        }
+        if let Some(slots) = attester_offline_after_slots {
+            config.attester_offline_after_slots = slots;
+        }
+        if let Some(mint) = payout_mint {
+            config.payout_mint = mint;
+        }
+        if let Some(source) = refund_source {
+            config.refund_source = source;
+        }
+        if let Some(required) = require_refund_transfer {
+            require!(
+                !required
+                    || (config.payout_mint != Pubkey::default()
+                        && config.refund_source != Pubkey::default()),
+                ErrorCode::InvalidConfig
+            );
+            config.require_refund_transfer = required;
+        }
+        if let Some(root) = import_root {
+            config.import_root = root;
+        }
+        if let Some(until) = import_until {
+            require!(until >= 0, ErrorCode::InvalidConfig);
+            config.import_until = until;
+        }
+        if let Some(threshold) = fingerprint_threshold {
+            config.fingerprint_threshold = threshold;
+        }
+        if let Some(retention) = retention_seconds {
+            require!(retention >= 0, ErrorCode::InvalidConfig);
+            config.retention_seconds = retention;
+        }
+        if let Some(required) = require_region_diversity {
+            config.require_region_diversity = required;
+        }
+        if let Some(threshold) = verdict_threshold {
+            config.verdict_threshold = threshold;
+        }
+        if let Some(limit) = max_pending_settlements {
+            config.max_pending_settlements = limit;
+        }
+
+        Ok(())
+    }
+
+    /// Archive an old attestation into its monthly summary (admin only)
+    ///
+    /// Folds the record into the `ArchiveSummary` for the month it was
+    /// attested in and shrinks it to an `ArchivedAttestation` tombstone,
+    /// returning the rest of the rent to the admin. The tombstone keeps
+    /// the claim_id's PDA taken, so the claim can't be attested again. The
+    /// summary keeps a count, total payout and rolling hash of archived
+    /// claim_ids, so the archived records remain auditable as a digest chain.
+    pub fn archive_attestation(
+        ctx: Context<ArchiveAttestation>,
+        period: u32,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.config.is_enabled(IX_ARCHIVE),
+            ErrorCode::InstructionDisabled
+        );
+
+        let attestation = ProofAttestation::try_deserialize(
+            &mut &ctx.accounts.attestation.try_borrow_data()?[..],
+        )?;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(
+            period == month_id(attestation.attested_at),
+            ErrorCode::PeriodMismatch
+        );
+        require!(
+            now.saturating_sub(attestation.attested_at)
+                >= ctx.accounts.config.archive_ttl_seconds,
+            ErrorCode::AttestationTooRecent
+        );
+
+        let summary = &mut ctx.accounts.archive_summary;
+        if summary.count == 0 {
+            summary.period = period;
+            summary.bump = ctx.bumps.archive_summary;
+        }
+        summary.count = summary
+            .count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        summary.total_payout = summary
+            .total_payout
+            .checked_add(attestation.public_inputs[3])
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        summary.digest = hashv(&[&summary.digest, &attestation.claim_id]).to_bytes();
+        summary.last_archived_at = now;
+
+        let stats = &mut ctx.accounts.program_stats;
+        stats.remove(StatsBucket::Layout, u16::from(ATTESTATION_LAYOUT_VERSION));
+        stats.remove(StatsBucket::Circuit, attestation.circuit_version);
+
+        let account = ctx.accounts.attestation.to_account_info();
+        let space = 8 + ArchivedAttestation::INIT_SPACE;
+        let excess = account
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(space));
+        account.sub_lamports(excess)?;
+        ctx.accounts.admin.add_lamports(excess)?;
+        account.realloc(space, false)?;
+        ArchivedAttestation {
+            claim_id: attestation.claim_id,
+            period,
+            archived_at: now,
+        }
+        .try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+        emit!(AttestationArchived {
+            claim_id: attestation.claim_id,
+            period,
+            count: summary.count,
+            digest: summary.digest,
+        });
+
+        msg!(
+            "Attestation archived: claim_id={}, period={}",
+            bs58::encode(&attestation.claim_id).into_string(),
+            period
+        );
+
+        Ok(())
+    }
+
+    /// Open a merchant's dashboard account (permissionless)
+    ///
+    /// Whoever pays the rent opens it, typically the merchant's own
+    /// wallet; the figures are public anyway. Attestations against
+    /// `merchant_id` then update it when it is passed.
+    pub fn open_merchant_dashboard(
+        ctx: Context<OpenMerchantDashboard>,
+        merchant_id: [u8; 32],
+    ) -> Result<()> {
+        let dashboard = &mut ctx.accounts.merchant_dashboard;
+        dashboard.merchant_id = merchant_id;
+        dashboard.opened_by = ctx.accounts.payer.key();
+        dashboard.bump = ctx.bumps.merchant_dashboard;
+
+        emit!(MerchantDashboardOpened {
+            merchant_id,
+            opened_by: dashboard.opened_by,
+        });
+
+        Ok(())
+    }
+
+    /// Close a merchant dashboard, returning the rent to whoever opened it
+    pub fn close_merchant_dashboard(
+        _ctx: Context<CloseMerchantDashboard>,
+        merchant_id: [u8; 32],
+    ) -> Result<()> {
+        emit!(MerchantDashboardClosed { merchant_id });
+
+        Ok(())
+    }
+
+    /// Register where a merchant's claims feed is delivered (admin only)
+    ///
+    /// `channel_hash` commits to the merchant's webhook endpoint and
+    /// signing key, kept off-chain; relayers look it up by `merchant_id`.
+    /// Merchants have no on-chain keys, so the admin registers channels
+    /// after verifying endpoint ownership. Calling again replaces it.
+    pub fn register_notification_channel(
+        ctx: Context<RegisterNotificationChannel>,
+        merchant_id: [u8; 32],
+        channel_hash: [u8; 32],
+    ) -> Result<()> {
+        let channel = &mut ctx.accounts.notification_channel;
+        channel.merchant_id = merchant_id;
+        channel.channel_hash = channel_hash;
+        channel.updated_at = Clock::get()?.unix_timestamp;
+        channel.bump = ctx.bumps.notification_channel;
+
+        emit!(NotificationChannelRegistered {
+            merchant_id,
+            channel_hash,
+        });
+
+        Ok(())
+    }
+
+    /// Assign a merchant endpoint to a category (admin only)
+    ///
+    /// Creates the category's aggregate if needed. Attestations against the
+    /// merchant then count into it when both accounts are passed. Calling
+    /// again moves the merchant; earlier claims stay with the old category.
+    pub fn register_merchant_category(
+        ctx: Context<RegisterMerchantCategory>,
+        merchant_id: [u8; 32],
+        category: u8,
+    ) -> Result<()> {
+        let merchant = &mut ctx.accounts.merchant_category;
+        merchant.merchant_id = merchant_id;
+        merchant.category = category;
+        merchant.bump = ctx.bumps.merchant_category;
+
+        let stats = &mut ctx.accounts.category_stats;
+        stats.category = category;
+        stats.bump = ctx.bumps.category_stats;
+
+        emit!(MerchantCategoryRegistered {
+            merchant_id,
+            category,
+        });
+
+        Ok(())
+    }
+
+    /// Create a merchant's paid-claim Bloom filter (admin only)
+    ///
+    /// Attestations against the merchant set the claim's bits when the
+    /// filter is passed, so merchant programs can check whether a claim
+    /// was paid from one account instead of deriving and loading its
+    /// attestation. `size_bytes` and `hash_count` fix the false-positive
+    /// rate for the number of claims expected; a negative answer is
+    /// always exact.
+    pub fn create_claim_bloom(
+        ctx: Context<CreateClaimBloom>,
+        merchant_id: [u8; 32],
+        size_bytes: u32,
+        hash_count: u8,
+    ) -> Result<()> {
+        require!(
+            (1..=MAX_CLAIM_BLOOM_BYTES).contains(&size_bytes)
+                && (1..=MAX_CLAIM_BLOOM_HASHES).contains(&hash_count),
+            ErrorCode::InvalidClaimBloom
+        );
+
+        let bloom = &mut ctx.accounts.claim_bloom;
+        bloom.merchant_id = merchant_id;
+        bloom.hash_count = hash_count;
+        bloom.count = 0;
+        bloom.bits = vec![0; size_bytes as usize];
+        bloom.bump = ctx.bumps.claim_bloom;
+
+        emit!(ClaimBloomCreated {
+            merchant_id,
+            size_bytes,
+            hash_count,
+        });
+
+        Ok(())
+    }
+
+    /// Declare a merchant maintenance window ahead of time (admin only)
+    ///
+    /// The window covers `start_slot..=end_slot` and must start after the
+    /// current slot, so it can't be declared over downtime that already
+    /// happened. Merchants have no on-chain keys; the admin declares
+    /// windows on their behalf. Windows that have ended are dropped to
+    /// make room.
+    pub fn declare_maintenance(
+        ctx: Context<DeclareMaintenance>,
+        merchant_id: [u8; 32],
+        start_slot: u64,
+        end_slot: u64,
+    ) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        require!(
+            start_slot > slot && end_slot >= start_slot,
+            ErrorCode::InvalidMaintenanceWindow
+        );
+
+        let schedule = &mut ctx.accounts.maintenance_schedule;
+        schedule.merchant_id = merchant_id;
+        schedule.bump = ctx.bumps.maintenance_schedule;
+        schedule.windows.retain(|window| window.end_slot >= slot);
+        require!(
+            schedule.windows.len() < MAX_MAINTENANCE_WINDOWS,
+            ErrorCode::MaintenanceScheduleFull
+        );
+        schedule.windows.push(MaintenanceWindow {
+            start_slot,
+            end_slot,
+            declared_slot: slot,
+        });
+
+        emit!(MaintenanceDeclared {
+            merchant_id,
+            start_slot,
+            end_slot,
+        });
+
+        Ok(())
+    }
+
+    /// Remove a merchant's claims feed registration (admin only)
+    ///
+    /// Closes the channel account, returning its rent to the admin.
+    pub fn remove_notification_channel(
+        _ctx: Context<RemoveNotificationChannel>,
+        merchant_id: [u8; 32],
+    ) -> Result<()> {
+        emit!(NotificationChannelRemoved { merchant_id });
+
+        Ok(())
+    }
+
+    /// Rotate the key relayers sign webhook payloads with (admin only)
+    ///
+    /// The outgoing key stays valid for `overlap_seconds`, so deliveries
+    /// signed just before the rotation still verify; pass 0 to revoke it
+    /// at once, e.g. after a compromise. The first call sets the key.
+    pub fn rotate_webhook_signer(
+        ctx: Context<RotateWebhookSigner>,
+        new_key: Pubkey,
+        overlap_seconds: i64,
+    ) -> Result<()> {
+        require!(overlap_seconds >= 0, ErrorCode::InvalidConfig);
+        let now = Clock::get()?.unix_timestamp;
+        let signer = &mut ctx.accounts.webhook_signer;
+        require!(
+            new_key != Pubkey::default() && new_key != signer.key,
+            ErrorCode::InvalidWebhookSigner
+        );
+
+        if signer.key != Pubkey::default() {
+            signer.previous_key = signer.key;
+            signer.previous_valid_until = now.saturating_add(overlap_seconds);
+        }
+        signer.key = new_key;
+        signer.activated_at = now;
+        signer.bump = ctx.bumps.webhook_signer;
+
+        emit!(WebhookSignerRotated {
+            key: new_key,
+            previous_key: signer.previous_key,
+            previous_valid_until: signer.previous_valid_until,
+        });
+
+        Ok(())
+    }
+
+    /// Register a key allowed to annotate attestations (admin only)
+    pub fn register_auditor(ctx: Context<RegisterAuditor>, auditor: Pubkey) -> Result<()> {
+        let registration = &mut ctx.accounts.auditor_registration;
+        registration.auditor = auditor;
+        registration.registered_at = Clock::get()?.unix_timestamp;
+        registration.annotations = 0;
+        registration.bump = ctx.bumps.auditor_registration;
+
+        msg!("Auditor registered: {}", auditor);
+
+        Ok(())
+    }
+
+    /// Revoke an auditor's registration (admin only)
+    ///
+    /// Annotations it already made stay in place.
+    pub fn remove_auditor(_ctx: Context<RemoveAuditor>, auditor: Pubkey) -> Result<()> {
+        msg!("Auditor removed: {}", auditor);
+
+        Ok(())
+    }
+
+    /// Anchor an audit finding to an attestation (registered auditors only)
+    ///
+    /// `note_hash` commits to the off-chain finding and `category` is an
+    /// operator-defined code for its kind. Up to `MAX_ANNOTATIONS` are
+    /// kept per attestation, in order, with the auditor and time.
+    pub fn annotate_attestation(
+        ctx: Context<AnnotateAttestation>,
+        note_hash: [u8; 32],
+        category: u8,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let claim_id = ctx.accounts.attestation.claim_id;
+        let auditor = ctx.accounts.auditor.key();
+
+        let list = &mut ctx.accounts.annotations;
+        if list.entries.is_empty() {
+            list.claim_id = claim_id;
+            list.bump = ctx.bumps.annotations;
+        }
+        require!(
+            list.entries.len() < MAX_ANNOTATIONS,
+            ErrorCode::AnnotationsFull
+        );
+        list.entries.push(Annotation {
+            note_hash,
+            category,
+            auditor,
+            annotated_at: now,
+        });
+
+        let registration = &mut ctx.accounts.auditor_registration;
+        registration.annotations = registration
+            .annotations
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(AttestationAnnotated {
+            claim_id,
+            note_hash,
+            category,
+            auditor,
+            annotated_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Register a fraud verdict source, or change its weight (admin only)
+    ///
+    /// `kind` is one of the `VERDICT_KIND_*` codes. A verdict counts with
+    /// the weight its source had when it was recorded, so reweighting
+    /// never changes past claims.
+    pub fn register_verdict_source(
+        ctx: Context<RegisterVerdictSource>,
+        source: Pubkey,
+        kind: u8,
+        weight: u64,
+    ) -> Result<()> {
+        require!(weight > 0, ErrorCode::InvalidConfig);
+
+        let registration = &mut ctx.accounts.verdict_source;
+        registration.source = source;
+        registration.kind = kind;
+        registration.weight = weight;
+        registration.registered_at = Clock::get()?.unix_timestamp;
+        registration.bump = ctx.bumps.verdict_source;
+
+        emit!(VerdictSourceRegistered {
+            source,
+            kind,
+            weight,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a verdict source (admin only)
+    ///
+    /// Verdicts it already recorded keep counting.
+    pub fn remove_verdict_source(
+        _ctx: Context<RemoveVerdictSource>,
+        source: Pubkey,
+    ) -> Result<()> {
+        emit!(VerdictSourceRemoved { source });
+
+        Ok(())
+    }
+
+    /// Record a source's verdict on a claim (registered verdict sources only)
+    ///
+    /// Each source records once per claim; verdicts recorded after the
+    /// claim was attested count for audit only.
+    /// `evidence_hash` commits to what the verdict is based on (proof
+    /// hash, refetched response, adjuster notes). Only fraud verdicts add
+    /// to `ClaimVerdicts::fraud_weight`; the others are kept for audit.
+    pub fn record_verdict(
+        ctx: Context<RecordVerdict>,
+        claim_id: [u8; 32],
+        fraud_detected: bool,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let registration = &ctx.accounts.verdict_source;
+
+        let verdicts = &mut ctx.accounts.claim_verdicts;
+        if verdicts.entries.is_empty() {
+            verdicts.claim_id = claim_id;
+            verdicts.bump = ctx.bumps.claim_verdicts;
+        }
+        require!(
+            verdicts.entries.len() < MAX_CLAIM_VERDICTS,
+            ErrorCode::ClaimVerdictsFull
+        );
+        require!(
+            !verdicts
+                .entries
+                .iter()
+                .any(|verdict| verdict.source == registration.source),
+            ErrorCode::VerdictAlreadyRecorded
+        );
+        verdicts.entries.push(Verdict {
+            source: registration.source,
+            kind: registration.kind,
+            weight: registration.weight,
+            fraud_detected,
+            evidence_hash,
+            recorded_at: now,
+        });
+        if fraud_detected {
+            verdicts.fraud_weight = verdicts
+                .fraud_weight
+                .checked_add(registration.weight)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        emit!(VerdictRecorded {
+            claim_id,
+            source: registration.source,
+            fraud_detected,
+            weight: registration.weight,
+            fraud_weight: verdicts.fraud_weight,
+        });
+
+        Ok(())
+    }
+
+    /// Register an attester for liveness tracking (admin only)
+    ///
+    /// Registration counts as the first heartbeat.
+    pub fn register_attester(ctx: Context<RegisterAttester>, attester: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        let status = &mut ctx.accounts.attester_status;
+        status.attester = attester;
+        status.registered_at = clock.unix_timestamp;
+        status.last_heartbeat_slot = clock.slot;
+        status.heartbeats = 0;
+        status.offline = false;
+        status.bump = ctx.bumps.attester_status;
+        status.region = [0; 2];
+
+        msg!("Attester registered: {}", attester);
+
+        Ok(())
+    }
+
+    /// Report that a registered attester is alive (attester only)
+    ///
+    /// Clears the offline flag if it was set.
+    pub fn attester_heartbeat(ctx: Context<AttesterHeartbeat>) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let status = &mut ctx.accounts.attester_status;
+        status.last_heartbeat_slot = slot;
+        status.heartbeats = status
+            .heartbeats
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        if status.offline {
+            status.offline = false;
+            emit!(AttesterOnline {
+                attester: status.attester,
+                slot,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Record the region a registered attester operates from (admin only)
+    ///
+    /// `region` is an ISO 3166-1 alpha-2 code, e.g. `*b"DE"`; zeros clear
+    /// it. With `Config::require_region_diversity`, the primary and
+    /// secondary signers of a dual-proof attestation must be in different
+    /// regions. Registrations that predate the field are grown to hold it.
+    pub fn set_attester_region(
+        ctx: Context<SetAttesterRegion>,
+        attester: Pubkey,
+        region: [u8; 2],
+    ) -> Result<()> {
+        require!(
+            region == [0; 2] || region.iter().all(u8::is_ascii_uppercase),
+            ErrorCode::InvalidRegion
+        );
+
+        let account = ctx.accounts.attester_status.to_account_info();
+        let space = 8 + AttesterStatus::INIT_SPACE;
+        let mut status = {
+            let mut extended = account.try_borrow_data()?.to_vec();
+            extended.resize(extended.len().max(space), 0);
+            AttesterStatus::try_deserialize(&mut &extended[..])?
+        };
+        status.region = region;
+
+        let shortfall = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(account.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        if account.data_len() < space {
+            account.realloc(space, true)?;
+        }
+        status.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+        msg!(
+            "Attester region set: {} {}",
+            attester,
+            String::from_utf8_lossy(&region)
+        );
+
+        Ok(())
+    }
+
+    /// Replace a registered attester's key, keeping its identity (admin only)
+    ///
+    /// `new` must sign. Its registration takes over the old one's
+    /// registration time, heartbeat count and region, and the old
+    /// registration is closed to the admin. An `AttesterLink` at the new
+    /// key names the operator, the first key in the rotation chain, so
+    /// attestations signed by any key in the chain resolve to the same
+    /// operator. Keys never rotated have no link and are their own
+    /// operator; the old key's link PDA is always passed, so a link can't
+    /// be left out.
+    ///
+    /// Registrations that predate the region field must be migrated with
+    /// `set_attester_region` first.
+    pub fn rotate_attester_key(
+        ctx: Context<RotateAttesterKey>,
+        old: Pubkey,
+        new: Pubkey,
+    ) -> Result<()> {
+        require_keys_neq!(old, new, ErrorCode::InvalidAttesterRotation);
+
+        let clock = Clock::get()?;
+        let old_link = &ctx.accounts.old_link;
+        let operator = if old_link.data_is_empty() {
+            old
+        } else {
+            require_keys_eq!(
+                *old_link.owner,
+                crate::ID,
+                ErrorCode::InvalidAttesterRotation
+            );
+            AttesterLink::try_deserialize(&mut &old_link.try_borrow_data()?[..])?.operator
+        };
+
+        let previous = &ctx.accounts.old_status;
+        let status = &mut ctx.accounts.new_status;
+        status.attester = new;
+        status.registered_at = previous.registered_at;
+        status.last_heartbeat_slot = clock.slot;
+        status.heartbeats = previous.heartbeats;
+        status.offline = false;
+        status.bump = ctx.bumps.new_status;
+        status.region = previous.region;
+
+        let link = &mut ctx.accounts.new_link;
+        link.attester = new;
+        link.operator = operator;
+        link.previous = old;
+        link.rotated_at = clock.unix_timestamp;
+        link.bump = ctx.bumps.new_link;
+
+        emit!(AttesterKeyRotated {
+            operator,
+            old,
+            new,
+            rotated_at: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Flag an attester whose heartbeats stopped as offline (permissionless)
+    ///
+    /// Allowed once more than `Config::attester_offline_after_slots` slots
+    /// have passed since the last heartbeat.
+    pub fn mark_attester_offline(ctx: Context<MarkAttesterOffline>) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let gap = ctx.accounts.config.attester_offline_after_slots;
+        let status = &mut ctx.accounts.attester_status;
+
+        require!(status.is_stale(slot, gap), ErrorCode::AttesterNotStale);
+        if status.offline {
+            return Ok(());
+        }
+        status.offline = true;
+
+        emit!(AttesterOffline {
+            attester: status.attester,
+            last_heartbeat_slot: status.last_heartbeat_slot,
+            slot,
+        });
+
+        Ok(())
+    }
+
+    /// Record the verified build of the deployed program (admin only)
+    ///
+    /// `commit_hash` is the git commit the program was built from and
+    /// `verified_build_hash` the executable hash reported by
+    /// `solana-verify` for that commit. The deployment slot is read from
+    /// the program's ProgramData account, so integrators can tell whether
+    /// the record still describes the bytecode currently deployed.
+    pub fn record_build_hash(
+        ctx: Context<RecordBuildHash>,
+        commit_hash: [u8; 20],
+        verified_build_hash: [u8; 32],
+    ) -> Result<()> {
+        let deployed_slot = program_data_slot(&ctx.accounts.program_data)?;
+
+        let info = &mut ctx.accounts.upgrade_info;
+        info.commit_hash = commit_hash;
+        info.verified_build_hash = verified_build_hash;
+        info.deployed_slot = deployed_slot;
+        info.recorded_at = Clock::get()?.unix_timestamp;
+        info.bump = ctx.bumps.upgrade_info;
+
+        emit!(BuildHashRecorded {
+            commit_hash,
+            verified_build_hash,
+            deployed_slot,
+        });
+
+        msg!("Build recorded: deployed_slot={}", deployed_slot);
+
+        Ok(())
+    }
+
+    /// Register a public-inputs schema (admin only)
+    ///
+    /// Each field names one evidence input, in order, with its type and
+    /// inclusive range. Schemas are immutable once registered, so past
+    /// attestations keep their meaning; a changed layout gets a new id.
+    pub fn register_schema(
+        ctx: Context<RegisterSchema>,
+        schema_id: u16,
+        fields: Vec<SchemaField>,
+    ) -> Result<()> {
+        require!(schema_id != DEFAULT_SCHEMA_ID, ErrorCode::InvalidSchema);
+        require!(
+            !fields.is_empty() && fields.len() <= MAX_SCHEMA_FIELDS,
+            ErrorCode::InvalidSchema
+        );
+        for field in &fields {
+            require!(
+                !field.name.is_empty()
+                    && field.name.len() <= MAX_SCHEMA_FIELD_NAME_LEN
+                    && field.min <= field.max
+                    && (field.kind != SchemaFieldKind::Bool || field.max <= 1),
+                ErrorCode::InvalidSchema
+            );
+        }
+
+        let schema = &mut ctx.accounts.schema;
+        schema.schema_id = schema_id;
+        schema.fields = fields;
+        schema.registered_at = Clock::get()?.unix_timestamp;
+        schema.bump = ctx.bumps.schema;
+
+        emit!(SchemaRegistered {
+            schema_id,
+            field_count: schema.fields.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Link an attestation to its counterpart on another chain (attester only)
+    ///
+    /// zkEngine proofs are also attested by a verifier contract on an EVM
+    /// chain; `foreign_attestation_ref` is that contract's reference to
+    /// the same proof. The link is kept beside the attestation, like its
+    /// proof URI, so neither digest changes. It is set once: either record
+    /// is only as good as the pair agreeing.
+    pub fn link_foreign_attestation(
+        ctx: Context<LinkForeignAttestation>,
+        foreign_chain_id: u64,
+        foreign_attestation_ref: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            foreign_chain_id != 0 && foreign_attestation_ref != [0; 32],
+            ErrorCode::InvalidForeignAttestation
+        );
+
+        let link = &mut ctx.accounts.foreign_link;
+        link.claim_id = ctx.accounts.attestation.claim_id;
+        link.foreign_chain_id = foreign_chain_id;
+        link.foreign_attestation_ref = foreign_attestation_ref;
+        link.linked_at = Clock::get()?.unix_timestamp;
+        link.bump = ctx.bumps.foreign_link;
+
+        emit!(ForeignAttestationLinked {
+            claim_id: link.claim_id,
+            foreign_chain_id,
+            foreign_attestation_ref,
+        });
+
+        Ok(())
+    }
+
+    /// Commit a batch of settled claims to one Merkle root (admin only)
+    ///
+    /// Attestations are passed as remaining accounts in ascending
+    /// `sequence` order, all after the previous batch, so no claim lands in
+    /// two batches. The leaves are their `evm_digest`s in that order and
+    /// the root is `settlement_root`, which an EVM notary contract can
+    /// recompute with keccak256 alone. A relayer posts the root from the
+    /// `SettlementBatch` or the `SettlementBatchCommitted` event, which
+    /// also lists the leaves. Imported records have no sequence and can't
+    /// be batched.
+    pub fn commit_settlement_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CommitSettlementBatch<'info>>,
+        batch_index: u64,
+    ) -> Result<()> {
+        let ledger = &mut ctx.accounts.settlement_ledger;
+        if ledger.batch_count == 0 {
+            ledger.bump = ctx.bumps.settlement_ledger;
+        }
+        require!(
+            batch_index == ledger.batch_count && !ctx.remaining_accounts.is_empty(),
+            ErrorCode::InvalidSettlementBatch
+        );
+
+        let first_sequence = ledger.next_sequence;
+        let mut leaves = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut last_attested_at = 0;
+        for account in ctx.remaining_accounts {
+            require!(
+                account_layout_version(account)? == ATTESTATION_LAYOUT_VERSION,
+                ErrorCode::UnknownAccountLayout
+            );
+            let record = ProofAttestation::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+            require!(
+                record.sequence != LEGACY_SEQUENCE && record.sequence >= ledger.next_sequence,
+                ErrorCode::InvalidSettlementBatch
+            );
+            ledger.next_sequence = record.sequence + 1;
+            last_attested_at = record.attested_at;
+            leaves.push(record.evm_digest);
+        }
+        ledger.batch_count = ledger
+            .batch_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let batch = &mut ctx.accounts.settlement_batch;
+        batch.batch_index = batch_index;
+        batch.first_sequence = first_sequence;
+        batch.last_sequence = ledger.next_sequence - 1;
+        batch.leaf_count = leaves.len() as u32;
+        batch.root = settlement_root(&leaves);
+        batch.committed_at = now;
+        batch.bump = ctx.bumps.settlement_batch;
+
+        if let Some(queue) = ctx.accounts.settlement_queue.as_mut() {
+            queue.settle(ledger.next_sequence, last_attested_at);
+        }
+
+        emit!(SettlementBatchCommitted {
+            batch_index,
+            root: batch.root,
+            first_sequence,
+            last_sequence: batch.last_sequence,
+            leaves,
+            committed_at: now,
+        });
+
+        msg!(
+            "Settlement batch committed: index={}, leaves={}",
+            batch_index,
+            batch.leaf_count
+        );
+
+        Ok(())
+    }
+
+    /// Record where the full proof for an attestation is archived (attester only)
+    ///
+    /// `uri` locates the zkEngine proof bytes (Arweave, IPFS, S3, ...),
+    /// `size` is their length and `checksum` their hash, so auditors can
+    /// fetch and re-verify the proof long after the claim. For Blake3
+    /// records the checksum must equal the attested `proof_hash`. Calling
+    /// again replaces the location, e.g. after moving the archive.
+    pub fn register_proof_uri(
+        ctx: Context<RegisterProofUri>,
+        uri: String,
+        size: u64,
+        checksum: [u8; 32],
+    ) -> Result<()> {
+        let attestation = &ctx.accounts.attestation;
+        require!(
+            !uri.is_empty()
+                && uri.len() <= MAX_PROOF_URI_LEN
+                && !uri.as_bytes().starts_with(UNLINKED_URI_PREFIX),
+            ErrorCode::InvalidProofUri
+        );
+        require!(
+            !ctx.accounts
+                .proof_uri
+                .uri
+                .as_bytes()
+                .starts_with(UNLINKED_URI_PREFIX),
+            ErrorCode::AlreadyUnlinked
+        );
+        if attestation.hash_algo == HASH_ALGO_BLAKE3 {
+            require!(
+                checksum == attestation.proof_hash,
+                ErrorCode::ProofChecksumMismatch
+            );
+        }
+
+        let proof_uri = &mut ctx.accounts.proof_uri;
+        proof_uri.claim_id = attestation.claim_id;
+        proof_uri.uri = uri;
+        proof_uri.size = size;
+        proof_uri.checksum = checksum;
+        proof_uri.registered_at = Clock::get()?.unix_timestamp;
+        proof_uri.bump = ctx.bumps.proof_uri;
+
+        emit!(ProofUriRegistered {
+            claim_id: proof_uri.claim_id,
+            uri: proof_uri.uri.clone(),
+            size,
+            checksum,
+        });
+
+        Ok(())
+    }
+
+    /// Unlink a claimant from an attestation's archived proof (admin only)
+    ///
+    /// Honors deletion requests once `Config::retention_seconds` have passed
+    /// since attestation. The proof URI, which locates evidence about the
+    /// claimant's request, is replaced by a salted commitment to it;
+    /// whoever keeps the salt can still prove the link for an audit. So is
+    /// the attestation's `refund_destination`, the claimant's token
+    /// account, if set. Its financial fields, which `canonical_digest` and
+    /// `evm_digest` commit to, are untouched. The URI can't be registered
+    /// again afterwards.
+    pub fn unlink_claimant(ctx: Context<UnlinkClaimant>, salt: [u8; 32]) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let attestation = &mut ctx.accounts.attestation;
+        require!(
+            now.saturating_sub(attestation.attested_at) >= ctx.accounts.config.retention_seconds,
+            ErrorCode::RetentionNotElapsed
+        );
+        if attestation.refund_destination != Pubkey::default() {
+            attestation.refund_destination = Pubkey::new_from_array(
+                hashv(&[&salt, attestation.refund_destination.as_ref()]).to_bytes(),
+            );
+        }
+
+        let proof_uri = &mut ctx.accounts.proof_uri;
+        require!(
+            !proof_uri.uri.as_bytes().starts_with(UNLINKED_URI_PREFIX),
+            ErrorCode::AlreadyUnlinked
+        );
+        let commitment = hashv(&[&salt, proof_uri.uri.as_bytes()]).to_bytes();
+        let mut uri = String::from_utf8_lossy(UNLINKED_URI_PREFIX).into_owned();
+        uri.push_str(&bs58::encode(commitment).into_string());
+        proof_uri.uri = uri;
+
+        emit!(ClaimantUnlinked {
+            claim_id: attestation.claim_id,
+            uri_commitment: commitment,
+        });
+
+        Ok(())
+    }
+
+    /// Publish a proof-of-reserve statement (reserve auditor only)
+    ///
+    /// The auditor's signature on the transaction is the statement:
+    /// payout vault balance and reserved exposure in micro-USDC, plus a
+    /// hash of the off-chain bank reserves report. A statement arriving
+    /// later than `Config::reserve_interval_seconds` after the previous
+    /// one counts as a missed attestation, unless `flag_missed_reserves`
+    /// already counted it.
+    pub fn attest_reserves(
+        ctx: Context<AttestReserves>,
+        vault_balance: u64,
+        reserved_exposure: u64,
+        bank_reserves_hash: [u8; 32],
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require_keys_eq!(
+            ctx.accounts.auditor.key(),
+            config.reserve_auditor,
+            ErrorCode::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let reserves = &mut ctx.accounts.reserve_attestation;
+        if reserves.count == 0 {
+            reserves.bump = ctx.bumps.reserve_attestation;
+        } else if !reserves.missed && reserves.is_overdue(now, config.reserve_interval_seconds) {
+            reserves.missed_count = reserves
+                .missed_count
+                .checked_add(1)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        reserves.auditor = ctx.accounts.auditor.key();
+        reserves.vault_balance = vault_balance;
+        reserves.reserved_exposure = reserved_exposure;
+        reserves.bank_reserves_hash = bank_reserves_hash;
+        reserves.attested_at = now;
+        reserves.count = reserves
+            .count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        reserves.missed = false;
+
+        emit!(ReservesAttested {
+            auditor: reserves.auditor,
+            vault_balance,
+            reserved_exposure,
+            bank_reserves_hash,
+            attested_at: now,
+            count: reserves.count,
+        });
+
+        msg!(
+            "Reserves attested: vault={}, exposure={} micro-USDC",
+            vault_balance,
+            reserved_exposure
+        );
+
+        Ok(())
+    }
+
+    /// Flag an overdue proof-of-reserve statement (permissionless)
+    ///
+    /// Sets `ReserveAttestation::missed` once the interval has passed
+    /// without a new statement; the next `attest_reserves` clears it.
+    pub fn flag_missed_reserves(ctx: Context<FlagMissedReserves>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let interval = ctx.accounts.config.reserve_interval_seconds;
+        let reserves = &mut ctx.accounts.reserve_attestation;
+
+        require!(
+            reserves.is_overdue(now, interval),
+            ErrorCode::ReservesNotOverdue
+        );
+        if reserves.missed {
+            return Ok(());
+        }
+
+        reserves.missed = true;
+        reserves.missed_count = reserves
+            .missed_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(ReserveAttestationMissed {
+            last_attested_at: reserves.attested_at,
+            missed_count: reserves.missed_count,
+        });
+
+        Ok(())
+    }
+
+    /// Check the program's core invariants (permissionless)
+    ///
+    /// Meant for monitoring bots to simulate on a schedule: success
+    /// returns nothing, and a violation fails with an error naming it.
+    /// Checked are the config's cross-field rules, the current layout's
+    /// stats bucket against the circuit buckets (both count every live
+    /// attestation in the current layout), and, when passed, reserve
+    /// coverage of the latest proof-of-reserve statement and the shape
+    /// of the nonce registry.
+    pub fn assert_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+        check_invariants(ctx)
+    }
+
+    /// Register a backend durable nonce account (admin only)
+    ///
+    /// The account must be an initialized system nonce account. Queued
+    /// attestations signed against a registered nonce stay valid until
+    /// submitted; an attestation whose transaction advances any other
+    /// nonce is rejected, so a leaked attester key can't pre-sign
+    /// long-lived attestations. Registering an account twice is a no-op.
+    pub fn register_nonce_account(ctx: Context<RegisterNonceAccount>) -> Result<()> {
+        let nonce = &ctx.accounts.nonce_account;
+        let authority = nonce_account_authority(nonce)?;
+
+        let registry = &mut ctx.accounts.nonce_registry;
+        if registry.count == 0 {
+            registry.bump = ctx.bumps.nonce_registry;
+        }
+        if registry.contains(&nonce.key()) {
+            return Ok(());
+        }
+        let index = usize::from(registry.count);
+        require!(index < MAX_NONCE_ACCOUNTS, ErrorCode::NonceRegistryFull);
+        registry.accounts[index] = nonce.key();
+        registry.count += 1;
+
+        msg!(
+            "Nonce account registered: {} (authority {})",
+            nonce.key(),
+            authority
+        );
+
+        Ok(())
+    }
+
+    /// Remove a nonce account from the registry (admin only)
+    pub fn remove_nonce_account(
+        ctx: Context<RemoveNonceAccount>,
+        nonce_account: Pubkey,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.nonce_registry;
+        let count = usize::from(registry.count);
+        let index = registry.accounts[..count]
+            .iter()
+            .position(|key| *key == nonce_account)
+            .ok_or(ErrorCode::InvalidNonceAccount)?;
+
+        registry.accounts[index] = registry.accounts[count - 1];
+        registry.accounts[count - 1] = Pubkey::default();
+        registry.count -= 1;
+
+        Ok(())
+    }
+
+    /// Count a batch of attestations towards the layout migration (admin only)
+    ///
+    /// Attestation accounts are passed as remaining accounts, sorted by
+    /// address and all after `MigrationStatus::last_scanned`, so a scan can
+    /// resume across transactions without counting a record twice. Each
+    /// old-layout record emits `LegacyAttestationTagged` and is counted
+    /// into its `ProgramStats` layout bucket. `restart` resets the counters
+    /// (and those buckets) to scan again from the lowest address.
+    pub fn scan_and_tag<'info>(
+        ctx: Context<'_, '_, '_, 'info, ScanAndTag<'info>>,
+        restart: bool,
+    ) -> Result<()> {
+        let status = &mut ctx.accounts.migration_status;
+        let stats = &mut ctx.accounts.program_stats;
+        if restart || status.target_version != ATTESTATION_LAYOUT_VERSION {
+            status.set_inner(MigrationStatus {
+                target_version: ATTESTATION_LAYOUT_VERSION,
+                bump: ctx.bumps.migration_status,
+                ..MigrationStatus::default()
+            });
+            // Legacy layouts are never created any more, so their buckets
+            // are exactly what this scan finds
+            for version in 0..ATTESTATION_LAYOUT_VERSION {
+                stats.clear(StatsBucket::Layout, u16::from(version));
+            }
+        }
+
+        for account in ctx.remaining_accounts {
+            require!(
+                account.key() > status.last_scanned,
+                ErrorCode::MigrationOutOfOrder
+            );
+            let version = account_layout_version(account)?;
+
+            status.scanned = status
+                .scanned
+                .checked_add(1)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            if version < ATTESTATION_LAYOUT_VERSION {
+                status.legacy_found = status
+                    .legacy_found
+                    .checked_add(1)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+                emit!(LegacyAttestationTagged {
+                    attestation: account.key(),
+                    layout_version: version,
+                });
+                stats.add(StatsBucket::Layout, u16::from(version))?;
+            }
+            status.last_scanned = account.key();
+        }
+        status.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Migration scan: scanned={}, legacy={}",
+            status.scanned,
+            status.legacy_found
+        );
+
+        Ok(())
+    }
+
+    /// Rewrite a batch of old-layout attestations in the current layout (admin only)
+    ///
+    /// Legacy accounts are passed as writable remaining accounts; records
+    /// already in the current layout are skipped, so a retried batch is
+    /// harmless. Each account is grown in place, with the admin topping
+    /// up rent. Fields the old layout lacks take their neutral values:
+    /// Blake3 proof hash, no secondary proof or related claim, evidence
+    /// slot 0, zero merchant_id, `LEGACY_SEQUENCE`, circuit version 0
+    /// (unknown) and `DEFAULT_SCHEMA_ID`. The billing period is backfilled
+    /// from `attested_at`, but records from before layout 5 are not
+    /// counted into `BillingPeriod` aggregates.
+    pub fn upgrade_layout<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpgradeLayout<'info>>,
+    ) -> Result<()> {
+        let space = 8 + ProofAttestation::INIT_SPACE;
+        let rent = Rent::get()?;
+
+        for account in ctx.remaining_accounts {
+            let version = account_layout_version(account)?;
+            if version == ATTESTATION_LAYOUT_VERSION {
+                continue;
+            }
+            require!(account.is_writable, ErrorCode::UnknownAccountLayout);
+
+            // Layout 1 is converted; later layouts read as zero-extended
+            let upgraded = {
+                let data = account.try_borrow_data()?;
+                if version == 1 {
+                    ProofAttestationV1::deserialize(&mut &data[8..])?.upgrade()
+                } else {
+                    let mut extended = data.to_vec();
+                    extended.resize(space, 0);
+                    let mut record = ProofAttestation::try_deserialize(&mut &extended[..])?;
+                    record.billing_period_id = month_id(record.attested_at);
+                    record
+                }
+            };
+
+            let shortfall = rent.minimum_balance(space).saturating_sub(account.lamports());
+            if shortfall > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.admin.to_account_info(),
+                            to: account.clone(),
+                        },
+                    ),
+                    shortfall,
+                )?;
+            }
+            account.realloc(space, true)?;
+            upgraded.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+            ctx.accounts.program_stats.upgrade(version)?;
+
+            let status = &mut ctx.accounts.migration_status;
+            status.migrated = status
+                .migrated
+                .checked_add(1)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        let status = &mut ctx.accounts.migration_status;
+        status.updated_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "Migration upgrade: migrated={} of {}",
+            status.migrated,
+            status.legacy_found
+        );
+
+        Ok(())
+    }
+}
+
+/// Query-only build of the program (`read-only` feature)
+///
+/// Same program ID, account layouts and instruction discriminators as the
+/// full program, but only the instructions that write no account. Meant
+/// for partners deploying a replica of the layouts to their own clusters
+/// or simulations.
+///
+/// Marked through `cfg_attr` so Anchor's IDL parser, which allows one
+/// `#[program]` module per crate, only sees the full program.
+#[cfg(feature = "read-only")]
+#[cfg_attr(feature = "read-only", program)]
+pub mod x402_attestation {
+    use super::*;
+
+    /// Query an existing proof attestation
+    pub fn query_attestation(
+        ctx: Context<QueryAttestation>,
+    ) -> Result<ProofAttestation> {
+        load_attestation(ctx)
+    }
+
+    /// Check the program's core invariants (permissionless)
+    pub fn assert_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+        check_invariants(ctx)
+    }
+
+    /// Fail unless this deployment's cluster has `genesis_hash` (permissionless)
+    pub fn assert_cluster(ctx: Context<AssertCluster>, genesis_hash: [u8; 32]) -> Result<()> {
+        check_cluster(ctx, genesis_hash)
+    }
+}
+
+fn load_attestation(ctx: Context<QueryAttestation>) -> Result<ProofAttestation> {
+    let attestation = &ctx.accounts.attestation;
+
+    msg!(
+        "Attestation found: claim_id={}, proof_hash={}, payout={}",
+        bs58::encode(&attestation.claim_id).into_string(),
+        bs58::encode(&attestation.proof_hash).into_string(),
+        attestation.public_inputs[3]
+    );
+
+    Ok(attestation.clone().into_inner())
+}
+
+/// Count attestation `sequence` into the settlement queue
+///
+/// Fails with `Config::max_pending_settlements` set and either no queue
+/// or the queue full.
+fn enqueue_settlement(
+    queue: Option<&mut Account<SettlementQueue>>,
+    config: &Config,
+    sequence: u64,
+    now: i64,
+) -> Result<()> {
+    let limit = config.max_pending_settlements;
+    match queue {
+        Some(queue) => {
+            queue.enqueue(sequence, now);
+            require!(
+                limit == 0 || queue.pending <= limit,
+                ErrorCode::SettlementBacklog
+            );
+        }
+        None => require!(limit == 0, ErrorCode::SettlementBacklog),
+    }
+    Ok(())
+}
+
+fn check_cluster(ctx: Context<AssertCluster>, genesis_hash: [u8; 32]) -> Result<()> {
+    require!(
+        ctx.accounts.cluster_info.genesis_hash == genesis_hash,
+        ErrorCode::ClusterMismatch
+    );
+    Ok(())
+}
+
+fn check_invariants(ctx: Context<AssertInvariants>) -> Result<()> {
+    require!(
+        ctx.accounts.config.is_consistent(),
+        ErrorCode::ConfigInvariantViolated
+    );
+    require!(
+        ctx.accounts.program_stats.is_consistent(),
+        ErrorCode::StatsInvariantViolated
+    );
+    if let Some(reserves) = &ctx.accounts.reserve_attestation {
+        require!(reserves.is_covered(), ErrorCode::ReservesNotCovered);
+    }
+    if let Some(registry) = &ctx.accounts.nonce_registry {
+        require!(
+            registry.is_consistent(),
+            ErrorCode::NonceRegistryInvariantViolated
+        );
+    }
+
+    Ok(())
+}
+
+/// Destination of the refund transfer backing the current attestation
+///
+/// Any instruction of the transaction may be the refund: a
+/// `TransferChecked` of `amount` in `Config::payout_mint` from
+/// `Config::refund_source` to `claimant`'s associated token account. The
+/// attestation must be a top-level instruction, and no other instruction
+/// of this program in the transaction may name `claimant`, so one
+/// transfer can't back two attestations. `None` without such a transfer.
+fn find_refund_transfer(
+    instructions: &AccountInfo,
+    config: &Config,
+    claimant: &Pubkey,
+    amount: u64,
+) -> Result<Option<Pubkey>> {
+    let current = usize::from(sysvar_instructions::load_current_index_checked(
+        instructions,
+    )?);
+    let mut destination = None;
+    let mut index = 0;
+    while let Ok(instruction) =
+        sysvar_instructions::load_instruction_at_checked(index, instructions)
+    {
+        if instruction.program_id == crate::ID {
+            let names_claimant = instruction
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == *claimant);
+            if index != current && names_claimant {
+                return Ok(None);
+            }
+        } else if index == current {
+            return Ok(None);
+        } else if destination.is_none() && is_refund_transfer(&instruction, config, amount) {
+            let expected =
+                associated_token_address(claimant, &instruction.program_id, &config.payout_mint);
+            if instruction.accounts[2].pubkey == expected {
+                destination = Some(expected);
+            }
+        }
+        index += 1;
+    }
+    Ok(destination)
+}
+
+/// Nonce account the transaction advances, if it uses a durable nonce
+///
+/// The runtime requires a durable nonce transaction to start with the
+/// System program's `AdvanceNonceAccount`, whose first account is the
+/// nonce.
+fn durable_nonce_account(instructions: &AccountInfo) -> Result<Option<Pubkey>> {
+    let first = sysvar_instructions::load_instruction_at_checked(0, instructions)?;
+    let advances_nonce = first.program_id == system_program::ID
+        && first.data == ADVANCE_NONCE_ACCOUNT_TAG.to_le_bytes()
+        && !first.accounts.is_empty();
+    Ok(advances_nonce.then(|| first.accounts[0].pubkey))
+}
+
+/// Whether `instruction` moves `amount` of the payout mint out of the refund source
+fn is_refund_transfer(instruction: &Instruction, config: &Config, amount: u64) -> bool {
+    let is_token_program = instruction.program_id == TOKEN_PROGRAM_ID
+        || instruction.program_id == TOKEN_2022_PROGRAM_ID;
+    // TransferChecked: [12, amount u64, decimals u8]; accounts [source, mint, destination, authority]
+    is_token_program
+        && instruction.data.len() == 10
+        && instruction.data[0] == TRANSFER_CHECKED_TAG
+        && instruction.data[1..9] == amount.to_le_bytes()
+        && instruction.accounts.len() >= 4
+        && instruction.accounts[0].pubkey == config.refund_source
+        && instruction.accounts[1].pubkey == config.payout_mint
+}
+
+/// Associated token account of `owner` for `mint` under `token_program`
+pub fn associated_token_address(owner: &Pubkey, token_program: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Write an imported record and count it into its billing period and the stats
+///
+/// Shared by `import_historical_attestation` and `claim_legacy_attestation`.
+fn record_import(
+    attestation: &mut ProofAttestation,
+    attestation_bump: u8,
+    billing: &mut BillingPeriod,
+    billing_bump: u8,
+    stats: &mut ProgramStats,
+    admin: Pubkey,
+    claim_id: [u8; 32],
+    attested_at: i64,
+    proof_hash: [u8; 32],
+    hash_algo: u8,
+    public_inputs: [u64; 4],
+    refund_signature: [u8; 64],
+    merchant_id: [u8; 32],
+) -> Result<()> {
+    attestation.claim_id = claim_id;
+    attestation.proof_hash = proof_hash;
+    attestation.hash_algo = hash_algo;
+    attestation.public_inputs = public_inputs;
+    attestation.refund_tx_sig = refund_signature;
+    attestation.merchant_id = merchant_id;
+    attestation.attested_at = attested_at;
+    attestation.attester = admin;
+    attestation.sequence = LEGACY_SEQUENCE;
+    attestation.bump = attestation_bump;
+    attestation.evm_digest = attestation.evm_struct_hash();
+    attestation.billing_period_id = month_id(attested_at);
+    attestation.imported = true;
+
+    if billing.count == 0 {
+        billing.period = attestation.billing_period_id;
+        billing.bump = billing_bump;
+    }
+    billing.count = billing
+        .count
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    billing.total_payout = billing
+        .total_payout
+        .checked_add(public_inputs[3])
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    stats.add(StatsBucket::Layout, u16::from(ATTESTATION_LAYOUT_VERSION))?;
+    stats.add(StatsBucket::Circuit, 0)
+}
+
+/// Deployment slot from an upgradeable loader ProgramData account
+///
+/// Layout (bincode): variant u32 (3 = ProgramData), slot u64, then the
+/// optional upgrade authority.
+fn program_data_slot(account: &AccountInfo) -> Result<u64> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 12 && data[..4] == 3u32.to_le_bytes(),
+        ErrorCode::InvalidProgramData
+    );
+    Ok(u64::from_le_bytes(data[4..12].try_into().expect("range is 8 bytes")))
+}
+
+/// Authority of an initialized system nonce account
+fn nonce_account_authority(account: &AccountInfo) -> Result<Pubkey> {
+    require_keys_eq!(*account.owner, system_program::ID, ErrorCode::InvalidNonceAccount);
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() == NONCE_ACCOUNT_LEN && is_initialized_nonce(&data),
+        ErrorCode::InvalidNonceAccount
+    );
+    Ok(Pubkey::new_from_array(data[8..40].try_into().expect("range is 32 bytes")))
+}
+
+/// Whether raw nonce account data is `Versions::Current(State::Initialized(_))`
+///
+/// Layout (bincode): version u32, state u32, authority, durable nonce,
+/// lamports per signature.
+pub fn is_initialized_nonce(data: &[u8]) -> bool {
+    data.len() >= 8 && data[..4] == 1u32.to_le_bytes() && data[4..8] == 1u32.to_le_bytes()
+}
+
+/// Billing period of the current cluster time
+fn current_billing_period() -> Result<u32> {
+    Ok(month_id(Clock::get()?.unix_timestamp))
+}
+
+/// UTC day of the current cluster time
+fn current_unix_day() -> Result<u32> {
+    Ok(unix_day(Clock::get()?.unix_timestamp))
+}
+
+/// Reject evidence captured in one of the merchant's maintenance windows
+///
+/// `schedule` must be the merchant's schedule PDA; while it is empty the
+/// merchant has declared no windows.
+fn check_maintenance(
+    schedule: &AccountInfo,
+    merchant_id: &[u8; 32],
+    evidence_slot: u64,
+) -> Result<()> {
+    let (address, _) = Pubkey::find_program_address(&[MAINTENANCE_SEED, merchant_id], &crate::ID);
+    require_keys_eq!(
+        schedule.key(),
+        address,
+        ErrorCode::MaintenanceScheduleMismatch
+    );
+    if schedule.data_is_empty() {
+        return Ok(());
+    }
+    require_keys_eq!(
+        *schedule.owner,
+        crate::ID,
+        ErrorCode::MaintenanceScheduleMismatch
+    );
+    let schedule = MaintenanceSchedule::try_deserialize(&mut &schedule.try_borrow_data()?[..])?;
+    require!(
+        !schedule.covers(evidence_slot),
+        ErrorCode::MaintenanceWindowActive
+    );
+    Ok(())
+}
+
+/// Layout version of a program-owned attestation account
+fn account_layout_version(account: &AccountInfo) -> Result<u8> {
+    require_keys_eq!(*account.owner, crate::ID, ErrorCode::UnknownAccountLayout);
+    attestation_layout_version(&account.try_borrow_data()?)
+        .ok_or_else(|| error!(ErrorCode::UnknownAccountLayout))
+}
+
+/// Accounts `check_claim_rules` reads, from the instruction creating the attestation
+struct ClaimRuleAccounts<'a, 'info> {
+    attester: Pubkey,
+    secondary_prover: Option<&'a Signer<'info>>,
+    attester_status: Option<&'a Account<'info, AttesterStatus>>,
+    secondary_prover_status: Option<&'a Account<'info, AttesterStatus>>,
+    schema: Option<&'a Account<'info, PublicInputsSchema>>,
+    claim_verdicts: Option<&'a Account<'info, ClaimVerdicts>>,
+    maintenance_schedule: &'a AccountInfo<'info>,
+    claimant: Option<&'a AccountInfo<'info>>,
+    instructions: Option<&'a AccountInfo<'info>>,
+}
+
+/// Check the config's per-claim rules for a new attestation
+///
+/// Shared by `record_attestation` and `materialize_from_batch`, so a claim
+/// meets the same rules whichever way it is attested. `attested_slot` is
+/// when its proof was attested, which the evidence age is measured
+/// against; `dual_proof` tells whether a secondary proof comes with it.
+/// Returns the refund transfer's destination, or the default key when
+/// the config doesn't require one.
+fn check_claim_rules(
+    config: &Config,
+    accounts: ClaimRuleAccounts,
+    claim_id: &[u8; 32],
+    hash_algo: u8,
+    public_inputs: &[u64; 4],
+    evidence_slot: u64,
+    attested_slot: u64,
+    merchant_id: &[u8; 32],
+    dual_proof: bool,
+    schema_id: u16,
+    claimant: Option<Pubkey>,
+) -> Result<Pubkey> {
+    require!(config.is_enabled(IX_ATTEST), ErrorCode::InstructionDisabled);
+    require!(
+        config.allows_hash_algo(hash_algo),
+        ErrorCode::HashAlgoNotAllowed
+    );
+
+    if config.dual_proof_threshold > 0 && public_inputs[3] >= config.dual_proof_threshold {
+        require!(dual_proof, ErrorCode::SecondaryProofRequired);
+    }
+    if dual_proof {
+        let cosigner = accounts
+            .secondary_prover
+            .ok_or(ErrorCode::SecondaryProverRequired)?;
+        require_keys_eq!(
+            cosigner.key(),
+            config.secondary_prover,
+            ErrorCode::SecondaryProverRequired
+        );
+
+        if config.require_region_diversity {
+            let region_of = |status: Option<&Account<AttesterStatus>>, signer: Pubkey| {
+                status
+                    .filter(|status| status.attester == signer && status.region != [0; 2])
+                    .map(|status| status.region)
+            };
+            let primary = region_of(accounts.attester_status, accounts.attester);
+            let secondary = region_of(accounts.secondary_prover_status, cosigner.key());
+            require!(
+                matches!((primary, secondary), (Some(a), Some(b)) if a != b),
+                ErrorCode::RegionDiversityRequired
+            );
+        }
+    }
+
+    if schema_id != DEFAULT_SCHEMA_ID {
+        let schema = accounts.schema.ok_or(ErrorCode::SchemaMismatch)?;
+        require!(schema.schema_id == schema_id, ErrorCode::SchemaMismatch);
+        require!(schema.accepts(public_inputs), ErrorCode::InvalidPublicInputs);
+    }
+
+    if config.verdict_threshold > 0 {
+        let verdicts = accounts
+            .claim_verdicts
+            .ok_or(ErrorCode::VerdictThresholdNotMet)?;
+        require!(
+            verdicts.claim_id == *claim_id,
+            ErrorCode::VerdictThresholdNotMet
+        );
+        require!(
+            verdicts.fraud_weight >= config.verdict_threshold,
+            ErrorCode::VerdictThresholdNotMet
+        );
+    }
+
+    require!(
+        evidence_slot <= attested_slot,
+        ErrorCode::InvalidEvidenceSlot
+    );
+    require!(
+        config.max_claim_age_slots == 0
+            || attested_slot - evidence_slot <= config.max_claim_age_slots,
+        ErrorCode::EvidenceTooOld
+    );
+    check_maintenance(accounts.maintenance_schedule, merchant_id, evidence_slot)?;
+
+    if !config.require_refund_transfer {
+        return Ok(Pubkey::default());
+    }
+    let instructions = accounts
+        .instructions
+        .ok_or(ErrorCode::RefundTransferRequired)?;
+    let refund_claimant = accounts
+        .claimant
+        .ok_or(ErrorCode::RefundTransferRequired)?
+        .key();
+    if let Some(claimant) = claimant {
+        require_keys_eq!(claimant, refund_claimant, ErrorCode::RefundTransferRequired);
+    }
+    find_refund_transfer(instructions, config, &refund_claimant, public_inputs[3])?
+        .ok_or_else(|| error!(ErrorCode::RefundTransferRequired))
+}
+
+fn record_attestation(
+    ctx: Context<AttestProof>,
+    claim_id: [u8; 32],
+    proof_hash: [u8; 32],
+    hash_algo: u8,
+    public_inputs: [u64; 4],
+    evidence_slot: u64,
+    refund_signature: [u8; 64],
+    merchant_id: [u8; 32],
+    related_claim_id: Option<[u8; 32]>,
+    proof_hash_secondary: Option<[u8; 32]>,
+    circuit_version: u16,
+    schema_id: u16,
+    claimant: Option<Pubkey>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let refund_destination = check_claim_rules(
+        &ctx.accounts.config,
+        ClaimRuleAccounts {
+            attester: ctx.accounts.authority.key(),
+            secondary_prover: ctx.accounts.secondary_prover.as_ref(),
+            attester_status: ctx.accounts.authority_status.as_ref(),
+            secondary_prover_status: ctx.accounts.secondary_prover_status.as_ref(),
+            schema: ctx.accounts.schema.as_ref(),
+            claim_verdicts: ctx.accounts.claim_verdicts.as_deref(),
+            maintenance_schedule: &ctx.accounts.maintenance_schedule,
+            claimant: ctx.accounts.claimant.as_deref(),
+            instructions: Some(&ctx.accounts.instructions),
+        },
+        &claim_id,
+        hash_algo,
+        &public_inputs,
+        evidence_slot,
+        clock.slot,
+        &merchant_id,
+        proof_hash_secondary.is_some(),
+        schema_id,
+        claimant,
+    )?;
+
+    if let Some(nonce_account) = durable_nonce_account(&ctx.accounts.instructions)? {
+        let registry = ctx
+            .accounts
+            .nonce_registry
+            .as_ref()
+            .ok_or(ErrorCode::InvalidNonceAccount)?;
+        require!(
+            registry.contains(&nonce_account),
+            ErrorCode::InvalidNonceAccount
+        );
+    }
+
+    match (related_claim_id, &ctx.accounts.related_attestation) {
+        (None, None) => {}
+        (Some(related), Some(record)) => {
+            require!(record.claim_id == related, ErrorCode::RelatedClaimMismatch);
+        }
+        _ => return err!(ErrorCode::RelatedClaimMismatch),
+    }
+
+    let sequence = ctx.accounts.recent_attestations.load_mut()?.push(RecentAttestation {
+        claim_id,
+        payout_amount: public_inputs[3],
+        slot: clock.slot,
+    })?;
+
+    enqueue_settlement(
+        ctx.accounts.settlement_queue.as_deref_mut(),
+        &ctx.accounts.config,
+        sequence,
+        clock.unix_timestamp,
+    )?;
+
+    let attestation = &mut ctx.accounts.attestation;
+
+    attestation.claim_id = claim_id;
+    attestation.proof_hash = proof_hash;
+    attestation.proof_hash_secondary = proof_hash_secondary;
+    attestation.hash_algo = hash_algo;
+    attestation.public_inputs = public_inputs;
+    attestation.evidence_slot = evidence_slot;
+    attestation.merchant_id = merchant_id;
+    attestation.related_claim_id = related_claim_id;
+    attestation.refund_tx_sig = refund_signature;
+    attestation.attested_at = clock.unix_timestamp;
+    attestation.attester = ctx.accounts.authority.key();
+    attestation.sequence = sequence;
+    attestation.bump = ctx.bumps.attestation;
+    attestation.evm_digest = attestation.evm_struct_hash();
+    attestation.circuit_version = circuit_version;
+    attestation.schema_id = schema_id;
+    attestation.billing_period_id = month_id(clock.unix_timestamp);
+    attestation.imported = false;
+    attestation.refund_destination = refund_destination;
+
+    match (
+        ctx.accounts.merchant_category.as_ref(),
+        ctx.accounts.category_stats.as_mut(),
+    ) {
+        (Some(merchant), Some(stats)) => {
+            require!(
+                merchant.merchant_id == merchant_id && stats.category == merchant.category,
+                ErrorCode::CategoryMismatch
+            );
+            stats.count = stats
+                .count
+                .checked_add(1)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            stats.total_payout = stats
+                .total_payout
+                .checked_add(public_inputs[3])
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        (None, None) => {}
+        _ => return err!(ErrorCode::CategoryMismatch),
+    }
+
+    if let Some(dashboard) = ctx.accounts.merchant_dashboard.as_mut() {
+        require!(
+            dashboard.merchant_id == merchant_id,
+            ErrorCode::MerchantDashboardMismatch
+        );
+        dashboard.record(unix_day(clock.unix_timestamp), public_inputs[3])?;
+        dashboard.last_claim_at = clock.unix_timestamp;
+    }
+
+    if let Some(bloom) = ctx.accounts.claim_bloom.as_mut() {
+        require!(
+            bloom.merchant_id == merchant_id,
+            ErrorCode::ClaimBloomMismatch
+        );
+        bloom.insert(&claim_id);
+        bloom.count = bloom
+            .count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+    }
+
+    let billing = &mut ctx.accounts.billing_period;
+    if billing.count == 0 {
+        billing.period = attestation.billing_period_id;
+        billing.bump = ctx.bumps.billing_period;
+    }
+    billing.count = billing
+        .count
+        .checked_add(1)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    billing.total_payout = billing
+        .total_payout
+        .checked_add(public_inputs[3])
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+    if let Some(index) = ctx.accounts.day_index.as_mut() {
+        if index.count == 0 {
+            index.day = unix_day(clock.unix_timestamp);
+            index.bump = ctx.bumps.day_index;
+        }
+        index.count = index
+            .count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        index.claims_hash = DayIndex::chain(&index.claims_hash, &claim_id);
+        if index.claim_ids.len() < MAX_DAY_INDEX_CLAIMS {
+            index.claim_ids.push(claim_id);
+        }
+    }
+
+    let stats = &mut ctx.accounts.program_stats;
+    stats.add(StatsBucket::Layout, u16::from(ATTESTATION_LAYOUT_VERSION))?;
+    stats.add(StatsBucket::Circuit, circuit_version)?;
+
+    if let Some(job) = &ctx.accounts.proof_job {
+        emit!(ProofJobCompleted {
+            claim_id,
+            submitted_slot: job.submitted_slot,
+            attested_slot: clock.slot,
+            proving_slots: clock.slot.saturating_sub(job.submitted_slot),
+            duration_seconds: clock.unix_timestamp.saturating_sub(job.submitted_at),
+        });
+    }
+
+    emit!(ProofAttested {
+        claim_id,
+        proof_hash,
+        hash_algo,
+        payout_amount: public_inputs[3],
+        attested_at: attestation.attested_at,
+        sequence,
+        evm_digest: attestation.evm_digest,
+    });
+
+    if ctx.accounts.config.emit_evidence_events {
+        emit!(ProofAttestedV2 {
+            claim_id,
+            proof_hash,
+            hash_algo,
+            merchant_id,
+            fraud_detected: public_inputs[0],
+            http_status: public_inputs[1],
+            body_length: public_inputs[2],
+            payout_amount: public_inputs[3],
+            attested_at: attestation.attested_at,
+        });
+    }
+
+    msg!(
+        "Proof attested: claim_id={}, payout={} micro-USDC",
+        bs58::encode(&claim_id).into_string(),
+        public_inputs[3]
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(claim_id: [u8; 32])]
+pub struct AttestProof<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProofAttestation::INIT_SPACE,
+        seeds = [ATTESTATION_SEED, claim_id.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    /// Earlier attestation referenced by `related_claim_id`, if any
+    pub related_attestation: Option<Account<'info, ProofAttestation>>,
+
+    /// Registered schema of `schema_id`, unless it is `DEFAULT_SCHEMA_ID`
+    pub schema: Option<Account<'info, PublicInputsSchema>>,
+
+    #[account(
+        mut,
+        seeds = [RECENT_ATTESTATIONS_SEED],
+        bump = recent_attestations.load()?.bump
+    )]
+    pub recent_attestations: AccountLoader<'info, RecentAttestations>,
+
+    #[account(mut, seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + BillingPeriod::INIT_SPACE,
+        seeds = [BILLING_PERIOD_SEED, current_billing_period()?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub billing_period: Box<Account<'info, BillingPeriod>>,
+
+    /// Claims attested today, if the attester indexes by day
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + DayIndex::INIT_SPACE,
+        seeds = [DAY_INDEX_SEED, current_unix_day()?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub day_index: Option<Box<Account<'info, DayIndex>>>,
+
+    /// Proving job of the claim, if one was started; closed to its attester
+    #[account(
+        mut,
+        close = authority,
+        seeds = [PROOF_JOB_SEED, claim_id.as_ref()],
+        bump = proof_job.bump,
+        constraint = proof_job.attester == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub proof_job: Option<Account<'info, ProofJob>>,
+
+    /// Category of `merchant_id`, if registered; passed with `category_stats`
+    pub merchant_category: Option<Account<'info, MerchantCategory>>,
+
+    /// Aggregate of the merchant's category
+    #[account(mut)]
+    pub category_stats: Option<Account<'info, CategoryStats>>,
+
+    /// Paid-claim filter of `merchant_id`, if one was created
+    #[account(mut)]
+    pub claim_bloom: Option<Account<'info, ClaimBloom>>,
+
+    /// CHECK: maintenance schedule PDA of `merchant_id`, always passed and
+    /// checked by `check_maintenance`; empty if no window was ever declared
+    pub maintenance_schedule: UncheckedAccount<'info>,
+
+    /// Dashboard of `merchant_id`, if one was opened
+    #[account(mut)]
+    pub merchant_dashboard: Option<Box<Account<'info, MerchantDashboard>>>,
+
+    /// Verdicts recorded on the claim, required with `Config::verdict_threshold`
+    #[account(seeds = [CLAIM_VERDICTS_SEED, claim_id.as_ref()], bump = claim_verdicts.bump)]
+    pub claim_verdicts: Option<Box<Account<'info, ClaimVerdicts>>>,
+
+    /// Settlement queue, required with `Config::max_pending_settlements`
+    #[account(mut, seeds = [SETTLEMENT_QUEUE_SEED], bump = settlement_queue.bump)]
+    pub settlement_queue: Option<Box<Account<'info, SettlementQueue>>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Secondary proof pipeline co-signer, required with `proof_hash_secondary`
+    pub secondary_prover: Option<Signer<'info>>,
+
+    /// Registry entries of both signers, required for dual-proof
+    /// attestations with `Config::require_region_diversity`
+    pub authority_status: Option<Account<'info, AttesterStatus>>,
+    pub secondary_prover_status: Option<Account<'info, AttesterStatus>>,
+
+    /// Claimant whose associated token account receives the refund,
+    /// required with `Config::require_refund_transfer`
+    /// CHECK: only used to derive the refund transfer's destination
+    pub claimant: Option<UncheckedAccount<'info>>,
+
+    /// Registry of the backend's nonce accounts, required when the
+    /// transaction advances a durable nonce
+    #[account(seeds = [NONCE_REGISTRY_SEED], bump = nonce_registry.bump)]
+    pub nonce_registry: Option<Box<Account<'info, NonceRegistry>>>,
+
+    /// CHECK: instructions sysvar, read for the refund transfer and the
+    /// durable nonce
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_root: [u8; 32])]
+pub struct AttestAggregatedProof<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + AggregateProof::INIT_SPACE,
+        seeds = [AGGREGATE_SEED, batch_root.as_ref()],
+        bump
+    )]
+    pub aggregate_proof: Account<'info, AggregateProof>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(claim_id: [u8; 32])]
+pub struct MaterializeFromBatch<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [AGGREGATE_SEED, aggregate_proof.batch_root.as_ref()],
+        bump = aggregate_proof.bump
+    )]
+    pub aggregate_proof: Account<'info, AggregateProof>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ProofAttestation::INIT_SPACE,
+        seeds = [ATTESTATION_SEED, claim_id.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(
+        mut,
+        seeds = [RECENT_ATTESTATIONS_SEED],
+        bump = recent_attestations.load()?.bump
+    )]
+    pub recent_attestations: AccountLoader<'info, RecentAttestations>,
+
+    #[account(mut, seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + BillingPeriod::INIT_SPACE,
+        seeds = [
+            BILLING_PERIOD_SEED,
+            month_id(aggregate_proof.attested_at).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub billing_period: Box<Account<'info, BillingPeriod>>,
+
+    /// Registered schema of the batch's `schema_id`, unless it is
+    /// `DEFAULT_SCHEMA_ID`
+    pub schema: Option<Account<'info, PublicInputsSchema>>,
+
+    /// CHECK: maintenance schedule PDA of `merchant_id`, always passed and
+    /// checked by `check_maintenance`; empty if no window was ever declared
+    pub maintenance_schedule: UncheckedAccount<'info>,
+
+    /// Verdicts recorded on the claim, required with `Config::verdict_threshold`
+    #[account(seeds = [CLAIM_VERDICTS_SEED, claim_id.as_ref()], bump = claim_verdicts.bump)]
+    pub claim_verdicts: Option<Box<Account<'info, ClaimVerdicts>>>,
+
+    /// Settlement queue, required with `Config::max_pending_settlements`
+    #[account(mut, seeds = [SETTLEMENT_QUEUE_SEED], bump = settlement_queue.bump)]
+    pub settlement_queue: Option<Box<Account<'info, SettlementQueue>>>,
+
+    /// Claimant whose associated token account receives the refund,
+    /// required with `Config::require_refund_transfer`
+    /// CHECK: only used to derive the refund transfer's destination
+    pub claimant: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: instructions sysvar, required with `Config::require_refund_transfer`
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: Option<UncheckedAccount<'info>>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(claim_id: [u8; 32], response_fingerprint: [u8; 32])]
+pub struct RecordResponseFingerprint<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [ATTESTATION_SEED, claim_id.as_ref()],
+        bump = attestation.bump,
+        constraint = attestation.attester == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + FingerprintIndex::INIT_SPACE,
+        seeds = [FINGERPRINT_SEED, response_fingerprint.as_ref()],
+        bump
+    )]
+    pub fingerprint_index: Account<'info, FingerprintIndex>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(claim_id: [u8; 32])]
+pub struct StartProofJob<'info> {
+    #[account(
+        init,
+        payer = attester,
+        space = 8 + ProofJob::INIT_SPACE,
+        seeds = [PROOF_JOB_SEED, claim_id.as_ref()],
+        bump
+    )]
+    pub proof_job: Account<'info, ProofJob>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(claim_id: [u8; 32], attested_at: i64)]
+pub struct ImportHistoricalAttestation<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProofAttestation::INIT_SPACE,
+        seeds = [ATTESTATION_SEED, claim_id.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(mut, seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + BillingPeriod::INIT_SPACE,
+        seeds = [BILLING_PERIOD_SEED, month_id(attested_at).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub billing_period: Box<Account<'info, BillingPeriod>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(memo_signature: [u8; 64], claim_id: [u8; 32], attested_at: i64)]
+pub struct ClaimLegacyAttestation<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProofAttestation::INIT_SPACE,
+        seeds = [ATTESTATION_SEED, claim_id.as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + LegacyMemo::INIT_SPACE,
+        seeds = [LEGACY_MEMO_SEED, claim_id.as_ref()],
+        bump
+    )]
+    pub legacy_memo: Account<'info, LegacyMemo>,
+
+    #[account(mut, seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + BillingPeriod::INIT_SPACE,
+        seeds = [BILLING_PERIOD_SEED, month_id(attested_at).to_le_bytes().as_ref()],
+        bump
+    )]
+    pub billing_period: Box<Account<'info, BillingPeriod>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct QueryAttestation<'info> {
+    pub attestation: Account<'info, ProofAttestation>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRecentAttestations<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RECENT_ATTESTATIONS_SPACE,
+        seeds = [RECENT_ATTESTATIONS_SEED],
+        bump
+    )]
+    pub recent_attestations: AccountLoader<'info, RecentAttestations>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSettlementQueue<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + SettlementQueue::INIT_SPACE,
+        seeds = [SETTLEMENT_QUEUE_SEED],
+        bump
+    )]
+    pub settlement_queue: Account<'info, SettlementQueue>,
+
+    #[account(seeds = [RECENT_ATTESTATIONS_SEED], bump = recent_attestations.load()?.bump)]
+    pub recent_attestations: AccountLoader<'info, RecentAttestations>,
+
+    /// Settlement ledger, once the first batch was committed
+    #[account(seeds = [SETTLEMENT_LEDGER_SEED], bump = settlement_ledger.bump)]
+    pub settlement_ledger: Option<Account<'info, SettlementLedger>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResizeRecentAttestations<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [RECENT_ATTESTATIONS_SEED],
+        bump,
+        realloc = 8 + RECENT_ATTESTATIONS_SPACE,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub recent_attestations: AccountLoader<'info, RecentAttestations>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramStats<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProgramStats::INIT_SPACE,
+        seeds = [STATS_SEED],
+        bump
+    )]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateProgramStats<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: the statistics in the current or the first layout, decoded
+    /// by the instruction
+    #[account(mut, owner = crate::ID, seeds = [STATS_SEED], bump)]
+    pub program_stats: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: the config in the current or an earlier layout; decoded
+    /// by the instruction, which checks `admin` against it
+    #[account(mut, owner = crate::ID, seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(period: u32)]
+pub struct ArchiveAttestation<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: deserialized as a `ProofAttestation` by the handler, which
+    /// then rewrites it as an `ArchivedAttestation`
+    #[account(mut, owner = crate::ID)]
+    pub attestation: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + ArchiveSummary::INIT_SPACE,
+        seeds = [ARCHIVE_SEED, period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub archive_summary: Account<'info, ArchiveSummary>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merchant_id: [u8; 32])]
+pub struct OpenMerchantDashboard<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MerchantDashboard::INIT_SPACE,
+        seeds = [MERCHANT_DASHBOARD_SEED, merchant_id.as_ref()],
+        bump
+    )]
+    pub merchant_dashboard: Account<'info, MerchantDashboard>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merchant_id: [u8; 32])]
+pub struct CloseMerchantDashboard<'info> {
+    #[account(
+        mut,
+        close = opened_by,
+        seeds = [MERCHANT_DASHBOARD_SEED, merchant_id.as_ref()],
+        bump = merchant_dashboard.bump,
+        has_one = opened_by @ ErrorCode::Unauthorized
+    )]
+    pub merchant_dashboard: Account<'info, MerchantDashboard>,
+
+    #[account(mut)]
+    pub opened_by: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(merchant_id: [u8; 32])]
+pub struct RegisterNotificationChannel<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + NotificationChannel::INIT_SPACE,
+        seeds = [NOTIFICATION_SEED, merchant_id.as_ref()],
+        bump
+    )]
+    pub notification_channel: Account<'info, NotificationChannel>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merchant_id: [u8; 32], size_bytes: u32)]
+pub struct CreateClaimBloom<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = ClaimBloom::space(size_bytes),
+        seeds = [CLAIM_BLOOM_SEED, merchant_id.as_ref()],
+        bump
+    )]
+    pub claim_bloom: Account<'info, ClaimBloom>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merchant_id: [u8; 32])]
+pub struct DeclareMaintenance<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MaintenanceSchedule::INIT_SPACE,
+        seeds = [MAINTENANCE_SEED, merchant_id.as_ref()],
+        bump
+    )]
+    pub maintenance_schedule: Account<'info, MaintenanceSchedule>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merchant_id: [u8; 32], category: u8)]
+pub struct RegisterMerchantCategory<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MerchantCategory::INIT_SPACE,
+        seeds = [MERCHANT_CATEGORY_SEED, merchant_id.as_ref()],
+        bump
+    )]
+    pub merchant_category: Account<'info, MerchantCategory>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + CategoryStats::INIT_SPACE,
+        seeds = [CATEGORY_STATS_SEED, &[category]],
+        bump
+    )]
+    pub category_stats: Account<'info, CategoryStats>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RotateWebhookSigner<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + WebhookSigner::INIT_SPACE,
+        seeds = [WEBHOOK_SIGNER_SEED],
+        bump
+    )]
+    pub webhook_signer: Account<'info, WebhookSigner>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(merchant_id: [u8; 32])]
+pub struct RemoveNotificationChannel<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [NOTIFICATION_SEED, merchant_id.as_ref()],
+        bump = notification_channel.bump
+    )]
+    pub notification_channel: Account<'info, NotificationChannel>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(auditor: Pubkey)]
+pub struct RegisterAuditor<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AuditorRegistration::INIT_SPACE,
+        seeds = [AUDITOR_SEED, auditor.as_ref()],
+        bump
+    )]
+    pub auditor_registration: Account<'info, AuditorRegistration>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(auditor: Pubkey)]
+pub struct RemoveAuditor<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [AUDITOR_SEED, auditor.as_ref()],
+        bump = auditor_registration.bump
+    )]
+    pub auditor_registration: Account<'info, AuditorRegistration>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AnnotateAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [AUDITOR_SEED, auditor.key().as_ref()],
+        bump = auditor_registration.bump,
+        has_one = auditor @ ErrorCode::Unauthorized
+    )]
+    pub auditor_registration: Account<'info, AuditorRegistration>,
+
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(
+        init_if_needed,
+        payer = auditor,
+        space = 8 + AttestationAnnotations::INIT_SPACE,
+        seeds = [ANNOTATIONS_SEED, attestation.claim_id.as_ref()],
+        bump
+    )]
+    pub annotations: Account<'info, AttestationAnnotations>,
+
+    #[account(mut)]
+    pub auditor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: Pubkey)]
+pub struct RegisterVerdictSource<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + VerdictSource::INIT_SPACE,
+        seeds = [VERDICT_SOURCE_SEED, source.as_ref()],
+        bump
+    )]
+    pub verdict_source: Account<'info, VerdictSource>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source: Pubkey)]
+pub struct RemoveVerdictSource<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [VERDICT_SOURCE_SEED, source.as_ref()],
+        bump = verdict_source.bump
+    )]
+    pub verdict_source: Account<'info, VerdictSource>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(claim_id: [u8; 32])]
+pub struct RecordVerdict<'info> {
+    #[account(
+        seeds = [VERDICT_SOURCE_SEED, source.key().as_ref()],
+        bump = verdict_source.bump,
+        has_one = source @ ErrorCode::Unauthorized
+    )]
+    pub verdict_source: Account<'info, VerdictSource>,
+
+    #[account(
+        init_if_needed,
+        payer = source,
+        space = 8 + ClaimVerdicts::INIT_SPACE,
+        seeds = [CLAIM_VERDICTS_SEED, claim_id.as_ref()],
+        bump
+    )]
+    pub claim_verdicts: Account<'info, ClaimVerdicts>,
+
+    #[account(mut)]
+    pub source: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(attester: Pubkey)]
+pub struct RegisterAttester<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AttesterStatus::INIT_SPACE,
+        seeds = [ATTESTER_SEED, attester.as_ref()],
+        bump
+    )]
+    pub attester_status: Account<'info, AttesterStatus>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(old: Pubkey, new: Pubkey)]
+pub struct RotateAttesterKey<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [ATTESTER_SEED, old.as_ref()],
+        bump = old_status.bump
+    )]
+    pub old_status: Account<'info, AttesterStatus>,
+
+    /// CHECK: the link PDA of `old`, always passed; it only holds an
+    /// `AttesterLink` if `old` was itself rotated in
+    #[account(seeds = [ATTESTER_LINK_SEED, old.as_ref()], bump)]
+    pub old_link: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AttesterStatus::INIT_SPACE,
+        seeds = [ATTESTER_SEED, new.as_ref()],
+        bump
+    )]
+    pub new_status: Account<'info, AttesterStatus>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AttesterLink::INIT_SPACE,
+        seeds = [ATTESTER_LINK_SEED, new.as_ref()],
+        bump
+    )]
+    pub new_link: Account<'info, AttesterLink>,
+
+    #[account(address = new @ ErrorCode::InvalidAttesterRotation)]
+    pub new_attester: Signer<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttesterHeartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [ATTESTER_SEED, attester.key().as_ref()],
+        bump = attester_status.bump,
+        has_one = attester @ ErrorCode::Unauthorized
+    )]
+    pub attester_status: Account<'info, AttesterStatus>,
+
+    pub attester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(attester: Pubkey)]
+pub struct SetAttesterRegion<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    /// CHECK: a registration of `attester`, possibly in the layout before
+    /// `region`; decoded zero-extended by the instruction
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [ATTESTER_SEED, attester.as_ref()],
+        bump
+    )]
+    pub attester_status: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MarkAttesterOffline<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [ATTESTER_SEED, attester_status.attester.as_ref()],
+        bump = attester_status.bump
+    )]
+    pub attester_status: Account<'info, AttesterStatus>,
+}
+
+#[derive(Accounts)]
+pub struct RecordBuildHash<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + UpgradeInfo::INIT_SPACE,
+        seeds = [UPGRADE_INFO_SEED],
+        bump
+    )]
+    pub upgrade_info: Account<'info, UpgradeInfo>,
+
+    /// CHECK: this program's ProgramData account, parsed in the handler
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID
+    )]
+    pub program_data: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(schema_id: u16)]
+pub struct RegisterSchema<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PublicInputsSchema::INIT_SPACE,
+        seeds = [SCHEMA_SEED, schema_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub schema: Account<'info, PublicInputsSchema>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterProofUri<'info> {
+    #[account(has_one = attester @ ErrorCode::Unauthorized)]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(
+        init_if_needed,
+        payer = attester,
+        space = 8 + ProofUri::INIT_SPACE,
+        seeds = [PROOF_URI_SEED, attestation.claim_id.as_ref()],
+        bump
+    )]
+    pub proof_uri: Account<'info, ProofUri>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LinkForeignAttestation<'info> {
+    #[account(has_one = attester @ ErrorCode::Unauthorized)]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(
+        init,
+        payer = attester,
+        space = 8 + ForeignLink::INIT_SPACE,
+        seeds = [FOREIGN_LINK_SEED, attestation.claim_id.as_ref()],
+        bump
+    )]
+    pub foreign_link: Account<'info, ForeignLink>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(batch_index: u64)]
+pub struct CommitSettlementBatch<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + SettlementLedger::INIT_SPACE,
+        seeds = [SETTLEMENT_LEDGER_SEED],
+        bump
+    )]
+    pub settlement_ledger: Account<'info, SettlementLedger>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + SettlementBatch::INIT_SPACE,
+        seeds = [SETTLEMENT_BATCH_SEED, batch_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub settlement_batch: Account<'info, SettlementBatch>,
+
+    /// Settlement queue, drained by the batch if it exists
+    #[account(mut, seeds = [SETTLEMENT_QUEUE_SEED], bump = settlement_queue.bump)]
+    pub settlement_queue: Option<Account<'info, SettlementQueue>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnlinkClaimant<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut)]
+    pub attestation: Account<'info, ProofAttestation>,
+
+    #[account(
+        mut,
+        seeds = [PROOF_URI_SEED, attestation.claim_id.as_ref()],
+        bump = proof_uri.bump
+    )]
+    pub proof_uri: Account<'info, ProofUri>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestReserves<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = auditor,
+        space = 8 + ReserveAttestation::INIT_SPACE,
+        seeds = [RESERVES_SEED],
+        bump
+    )]
+    pub reserve_attestation: Account<'info, ReserveAttestation>,
+
+    #[account(mut)]
+    pub auditor: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FlagMissedReserves<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [RESERVES_SEED],
+        bump = reserve_attestation.bump
+    )]
+    pub reserve_attestation: Account<'info, ReserveAttestation>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeClusterInfo<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ClusterInfo::INIT_SPACE,
+        seeds = [CLUSTER_SEED],
+        bump
+    )]
+    pub cluster_info: Account<'info, ClusterInfo>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: u8, period: u32)]
+pub struct ReportHash<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AuditReport::INIT_SPACE,
+        seeds = [REPORT_HASH_SEED, [kind].as_ref(), period.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub report: Account<'info, AuditReport>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AssertCluster<'info> {
+    #[account(seeds = [CLUSTER_SEED], bump = cluster_info.bump)]
+    pub cluster_info: Account<'info, ClusterInfo>,
+}
+
+#[derive(Accounts)]
+pub struct AssertInvariants<'info> {
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    /// Latest proof-of-reserve statement, to check its coverage
+    #[account(seeds = [RESERVES_SEED], bump = reserve_attestation.bump)]
+    pub reserve_attestation: Option<Account<'info, ReserveAttestation>>,
+
+    /// Nonce registry, to check its entries
+    #[account(seeds = [NONCE_REGISTRY_SEED], bump = nonce_registry.bump)]
+    pub nonce_registry: Option<Account<'info, NonceRegistry>>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterNonceAccount<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + NonceRegistry::INIT_SPACE,
+        seeds = [NONCE_REGISTRY_SEED],
+        bump
+    )]
+    pub nonce_registry: Account<'info, NonceRegistry>,
+
+    /// CHECK: validated as an initialized system nonce account
+    pub nonce_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveNonceAccount<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [NONCE_REGISTRY_SEED],
+        bump = nonce_registry.bump
+    )]
+    pub nonce_registry: Account<'info, NonceRegistry>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ScanAndTag<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + MigrationStatus::INIT_SPACE,
+        seeds = [MIGRATION_SEED],
+        bump
+    )]
+    pub migration_status: Account<'info, MigrationStatus>,
+
+    #[account(mut, seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpgradeLayout<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [MIGRATION_SEED],
+        bump = migration_status.bump
+    )]
+    pub migration_status: Account<'info, MigrationStatus>,
+
+    #[account(mut, seeds = [STATS_SEED], bump = program_stats.bump)]
+    pub program_stats: Account<'info, ProgramStats>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProofAttestation {
+    /// Unique claim identifier (32 bytes)
+    pub claim_id: [u8; 32],
+
+    /// Hash of the zkEngine proof (32 bytes), computed with `hash_algo`
+    pub proof_hash: [u8; 32],
+
+    /// Hash algorithm of `proof_hash` (`HASH_ALGO_*`)
+    pub hash_algo: u8,
+
+    /// Proof hash from the independent secondary pipeline, if dual-attested
+    pub proof_hash_secondary: Option<[u8; 32]>,
+
+    /// Public inputs to the proof verification:
+    /// [fraud_detected, http_status, body_length, payout_amount]
+    pub public_inputs: [u64; 4],
+
+    /// Slot at which the fraud evidence was captured
+    pub evidence_slot: u64,
+
+    /// Solana transaction signature of the USDC refund (64 bytes)
+    pub refund_tx_sig: [u8; 64],
+
+    /// SHA-256 of the merchant endpoint URL the claim was filed against
+    pub merchant_id: [u8; 32],
+
+    /// Earlier related claim (same merchant/user incident chain), if any
+    pub related_claim_id: Option<[u8; 32]>,
+
+    /// Unix timestamp when attestation was created
+    pub attested_at: i64,
+
+    /// Public key of the attester (backend wallet)
+    pub attester: Pubkey,
+
+    /// Monotonic sequence number across all attestations (gap detection),
+    /// or `LEGACY_SEQUENCE` for records migrated from layout 1 or imported
+    pub sequence: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// EIP-712 `hashStruct` of the record (see `EVM_ATTESTATION_TYPE`)
+    pub evm_digest: [u8; 32],
+
+    /// zkEngine circuit version that produced the proof (0 = unknown);
+    /// added in layout 3 and not covered by `evm_digest`
+    pub circuit_version: u16,
+
+    /// Layout of the evidence inputs (`DEFAULT_SCHEMA_ID` for the
+    /// built-in one); added in layout 4 and not covered by `evm_digest`
+    pub schema_id: u16,
+
+    /// `month_id` of `attested_at`; added in layout 5 (backfilled on
+    /// upgrade) and not covered by `evm_digest`
+    pub billing_period_id: u32,
+
+    /// Imported from the pre-program claims history by
+    /// `import_historical_attestation`; added in layout 6 and not covered
+    /// by `evm_digest`
+    pub imported: bool,
+
+    /// Hash of the fraudulent response body (zero if unknown), set by
+    /// `record_response_fingerprint` after attestation; added in layout 7
+    /// and not covered by `evm_digest`
+    pub response_fingerprint: [u8; 32],
+
+    /// Claimant token account the refund transfer paid (default without
+    /// `Config::require_refund_transfer`), salted by `unlink_claimant`;
+    /// added in layout 8 and not covered by either digest
+    pub refund_destination: Pubkey,
+}
+
+/// Original attestation layout (version 1), kept for `upgrade_layout`
+///
+/// Shares the `ProofAttestation` discriminator; told apart by data length.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct ProofAttestationV1 {
+    pub claim_id: [u8; 32],
+    pub proof_hash: [u8; 32],
+    pub public_inputs: [u64; 4],
+    pub refund_tx_sig: [u8; 64],
+    pub attested_at: i64,
+    pub attester: Pubkey,
+    pub bump: u8,
+}
+
+impl ProofAttestationV1 {
+    /// The record in the current layout, with neutral values for new fields
+    pub fn upgrade(self) -> ProofAttestation {
+        let mut attestation = ProofAttestation {
+            claim_id: self.claim_id,
+            proof_hash: self.proof_hash,
+            hash_algo: HASH_ALGO_BLAKE3,
+            proof_hash_secondary: None,
+            public_inputs: self.public_inputs,
+            evidence_slot: 0,
+            refund_tx_sig: self.refund_tx_sig,
+            merchant_id: [0; 32],
+            related_claim_id: None,
+            attested_at: self.attested_at,
+            attester: self.attester,
+            sequence: LEGACY_SEQUENCE,
+            bump: self.bump,
+            evm_digest: [0; 32],
+            circuit_version: 0,
+            schema_id: DEFAULT_SCHEMA_ID,
+            billing_period_id: month_id(self.attested_at),
+            imported: false,
+            response_fingerprint: [0; 32],
+            refund_destination: Pubkey::default(),
+        };
+        attestation.evm_digest = attestation.evm_struct_hash();
+        attestation
+    }
+}
+
+/// Data size (without discriminator) of layout 2
+///
+/// From layout 2 on, new fields are only appended and default to zero
+/// bytes, so an older record is upgraded by zero-extending its account
+/// (`upgrade_layout` then backfills `billing_period_id`).
+const ATTESTATION_V2_SPACE: usize = ATTESTATION_V3_SPACE - 2;
+
+/// Data size (without discriminator) of layout 3
+const ATTESTATION_V3_SPACE: usize = ATTESTATION_V4_SPACE - 2;
+
+/// Data size (without discriminator) of layout 4
+const ATTESTATION_V4_SPACE: usize = ATTESTATION_V5_SPACE - 4;
+
+/// Data size (without discriminator) of layout 5
+const ATTESTATION_V5_SPACE: usize = ATTESTATION_V6_SPACE - 1;
+
+/// Data size (without discriminator) of layout 6
+const ATTESTATION_V6_SPACE: usize = ATTESTATION_V7_SPACE - 32;
+
+/// Data size (without discriminator) of layout 7
+const ATTESTATION_V7_SPACE: usize = ProofAttestation::INIT_SPACE - 32;
+
+/// Layout version of raw attestation account data (with discriminator)
+///
+/// `None` if the data is not a `ProofAttestation` in any known layout.
+pub fn attestation_layout_version(data: &[u8]) -> Option<u8> {
+    if data.len() < 8 || data[..8] != ProofAttestation::DISCRIMINATOR {
+        return None;
+    }
+    match data.len() - 8 {
+        ProofAttestationV1::INIT_SPACE => Some(1),
+        ATTESTATION_V2_SPACE => Some(2),
+        ATTESTATION_V3_SPACE => Some(3),
+        ATTESTATION_V4_SPACE => Some(4),
+        ATTESTATION_V5_SPACE => Some(5),
+        ATTESTATION_V6_SPACE => Some(6),
+        ATTESTATION_V7_SPACE => Some(7),
+        ProofAttestation::INIT_SPACE => Some(ATTESTATION_LAYOUT_VERSION),
+        _ => None,
+    }
+}
+
+/// EIP-712 type string behind `ProofAttestation::evm_digest`
+///
+/// Optional hashes encode as zero `bytes32`; `attester` is the raw
+/// 32-byte Solana public key.
+pub const EVM_ATTESTATION_TYPE: &str = "ProofAttestation(bytes32 claimId,bytes32 proofHash,uint8 hashAlgo,bytes32 proofHashSecondary,uint64 fraudDetected,uint64 httpStatus,uint64 bodyLength,uint64 payoutAmount,uint64 evidenceSlot,bytes refundTxSig,bytes32 merchantId,bytes32 relatedClaimId,int64 attestedAt,bytes32 attester,uint64 sequence)";
+
+/// Domain tag for `ProofAttestation::canonical_digest`
+///
+/// v1 stopped at `sequence`; v2 adds the fields appended since layout 3,
+/// so receipts bind `imported` and the other later fields too.
+pub const ATTESTATION_DIGEST_DOMAIN: &[u8] = b"x402-attestation:v2";
+
+impl ProofAttestation {
+    /// Canonical SHA-256 digest of the attested fields
+    ///
+    /// Covers every field except the PDA bump, the derived `evm_digest`
+    /// and `refund_destination`, which `unlink_claimant` replaces, in
+    /// declaration order, with integers little-endian, booleans as one
+    /// byte and optional hashes as a presence byte followed by 32 bytes
+    /// (zeroed when absent). Off-chain receipts sign over this digest, so
+    /// the encoding is versioned by its domain tag.
+    pub fn canonical_digest(&self) -> [u8; 32] {
+        let mut public_inputs = [0u8; 32];
+        for (chunk, input) in public_inputs.chunks_exact_mut(8).zip(self.public_inputs) {
+            chunk.copy_from_slice(&input.to_le_bytes());
+        }
+
+        hashv(&[
+            ATTESTATION_DIGEST_DOMAIN,
+            &self.claim_id,
+            &self.proof_hash,
+            &[self.hash_algo],
+            &optional_hash_bytes(&self.proof_hash_secondary),
+            &public_inputs,
+            &self.evidence_slot.to_le_bytes(),
+            &self.refund_tx_sig,
+            &self.merchant_id,
+            &optional_hash_bytes(&self.related_claim_id),
+            &self.attested_at.to_le_bytes(),
+            self.attester.as_ref(),
+            &self.sequence.to_le_bytes(),
+            &self.circuit_version.to_le_bytes(),
+            &self.schema_id.to_le_bytes(),
+            &self.billing_period_id.to_le_bytes(),
+            &[u8::from(self.imported)],
+            &self.response_fingerprint,
+        ])
+        .to_bytes()
+    }
+
+    /// EIP-712 `hashStruct` so EVM contracts can check bridged records
+    ///
+    /// `keccak256(typeHash ‖ encodeData)` per `EVM_ATTESTATION_TYPE`, with
+    /// integers ABI-encoded as 32-byte big-endian words. EVM verifiers
+    /// combine it with their own domain separator.
+    pub fn evm_struct_hash(&self) -> [u8; 32] {
+        let type_hash = keccak::hash(EVM_ATTESTATION_TYPE.as_bytes()).to_bytes();
+        let refund_sig_hash = keccak::hash(&self.refund_tx_sig).to_bytes();
+
+        keccak::hashv(&[
+            &type_hash,
+            &self.claim_id,
+            &self.proof_hash,
+            &abi_uint(u64::from(self.hash_algo)),
+            &self.proof_hash_secondary.unwrap_or_default(),
+            &abi_uint(self.public_inputs[0]),
+            &abi_uint(self.public_inputs[1]),
+            &abi_uint(self.public_inputs[2]),
+            &abi_uint(self.public_inputs[3]),
+            &abi_uint(self.evidence_slot),
+            &refund_sig_hash,
+            &self.merchant_id,
+            &self.related_claim_id.unwrap_or_default(),
+            &abi_int(self.attested_at),
+            self.attester.as_ref(),
+            &abi_uint(self.sequence),
+        ])
+        .to_bytes()
+    }
+}
+
+/// ABI-encode an unsigned integer as a 32-byte big-endian word
+fn abi_uint(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// ABI-encode a signed integer (two's complement, sign-extended)
+fn abi_int(value: i64) -> [u8; 32] {
+    let mut word = if value < 0 { [0xff; 32] } else { [0u8; 32] };
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+fn optional_hash_bytes(hash: &Option<[u8; 32]>) -> [u8; 33] {
+    let mut out = [0u8; 33];
+    if let Some(hash) = hash {
+        out[0] = 1;
+        out[1..].copy_from_slice(hash);
+    }
+    out
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    /// Admin allowed to run maintenance instructions
+    pub admin: Pubkey,
+
+    /// Minimum attestation age (seconds) before it may be archived
+    pub archive_ttl_seconds: i64,
+
+    /// Emit `ProofAttestedV2` with the full HTTP evidence on every attestation
+    pub emit_evidence_events: bool,
+
+    /// Bitmask of accepted `hash_algo` values (bit n allows algorithm n)
+    pub allowed_hash_algos: u8,
+
+    /// Maximum age of claim evidence in slots at attestation (0 = no limit)
+    pub max_claim_age_slots: u64,
+
+    /// Bitmask of enabled mutating instructions (`IX_*`); reads are never gated
+    pub enabled_instructions: u32,
+
+    /// Payout (micro-USDC) at or above which a secondary proof is required (0 = never)
+    pub dual_proof_threshold: u64,
+
+    /// Key of the independent secondary proof pipeline
+    pub secondary_prover: Pubkey,
+
+    /// Auditor allowed to publish proof-of-reserve statements
+    pub reserve_auditor: Pubkey,
+
+    /// Expected seconds between reserve statements (0 = no schedule)
+    pub reserve_interval_seconds: i64,
+
+    /// Heartbeat gap in slots after which an attester may be marked offline (0 = never)
+    pub attester_offline_after_slots: u64,
+
+    /// Require each attestation, batch claims included, to carry its
+    /// refund transfer in the same transaction
+    pub require_refund_transfer: bool,
+
+    /// Mint refunds are paid in, checked with `require_refund_transfer`
+    pub payout_mint: Pubkey,
+
+    /// Merkle root of the published historical claims dataset
+    pub import_root: [u8; 32],
+
+    /// Unix timestamp until which historical imports are accepted (0 = closed)
+    pub import_until: i64,
+
+    /// Claims sharing a response fingerprint that signal a repeat failure (0 = off)
+    pub fingerprint_threshold: u64,
+
+    /// Seconds after attestation before a claimant may be unlinked
+    pub retention_seconds: i64,
+
+    /// Whether dual-proof attestations need signers from two regions
+    pub require_region_diversity: bool,
+
+    /// Weight of fraud verdicts a claim needs before it is attested (0 = off)
+    pub verdict_threshold: u64,
+
+    /// Attestations awaiting settlement beyond which new ones are refused (0 = no limit)
+    pub max_pending_settlements: u64,
+
+    /// Token account refunds are paid from, checked with `require_refund_transfer`
+    pub refund_source: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Ring buffer of the most recent attestations, for on-chain consumers
+///
+/// Zero-copy: read in place rather than deserialized on every attestation.
+/// The field order matches the earlier Borsh layout, so buffers created
+/// before only need `resize_recent_attestations` to grow by the trailing
+/// padding.
+#[account(zero_copy)]
+pub struct RecentAttestations {
+    /// Total attestations ever recorded; also the next sequence number
+    pub total: u64,
+
+    /// Entry for sequence `n` lives at `entries[n % RECENT_ATTESTATIONS_LEN]`
+    pub entries: [RecentAttestation; RECENT_ATTESTATIONS_LEN],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    pub _padding: [u8; 7],
+}
+
+/// Size of [`RecentAttestations`], excluding the discriminator
+pub const RECENT_ATTESTATIONS_SPACE: usize = std::mem::size_of::<RecentAttestations>();
+
+impl RecentAttestations {
+    /// Append an entry, returning its sequence number
+    pub fn push(&mut self, entry: RecentAttestation) -> Result<u64> {
+        let sequence = self.total;
+        self.entries[(sequence % RECENT_ATTESTATIONS_LEN as u64) as usize] = entry;
+        self.total = sequence.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(sequence)
+    }
+
+    /// Entries with sequence >= `cursor` still held in the buffer, oldest first
+    ///
+    /// Consumers keep the last sequence they processed plus one as their
+    /// cursor; if it is older than the buffer, iteration starts at the
+    /// oldest retained entry and the skipped range is a gap.
+    pub fn since(&self, cursor: u64) -> impl Iterator<Item = (u64, &RecentAttestation)> {
+        let oldest = self.total.saturating_sub(RECENT_ATTESTATIONS_LEN as u64);
+        (cursor.max(oldest)..self.total).map(move |sequence| {
+            let index = (sequence % RECENT_ATTESTATIONS_LEN as u64) as usize;
+            (sequence, &self.entries[index])
+        })
+    }
+}
+
+#[zero_copy]
+#[derive(Default)]
+pub struct RecentAttestation {
+    pub claim_id: [u8; 32],
+    pub payout_amount: u64,
+    pub slot: u64,
+}
+
+/// Serialized size of each `Config` field before `bump`, in declaration order
+///
+/// Earlier layouts hold a run of these from the start; see `migrate_config`.
+pub const CONFIG_FIELD_SIZES: [usize; 21] = [
+    32, 8, 1, 1, 8, 4, 8, 32, 32, 8, 8, 1, 32, 32, 8, 8, 8, 1, 8, 8, 32,
+];
+
+/// Whether `len` bytes hold a whole run of leading `Config` fields
+fn is_config_prefix(len: usize) -> bool {
+    CONFIG_FIELD_SIZES
+        .iter()
+        .scan(0, |end, size| {
+            *end += size;
+            Some(*end)
+        })
+        .any(|end| end == len)
+}
+
+impl Config {
+    /// A fresh config, as `initialize_config` creates it
+    pub fn new(admin: Pubkey, archive_ttl_seconds: i64, bump: u8) -> Config {
+        Config {
+            admin,
+            archive_ttl_seconds,
+            emit_evidence_events: false,
+            allowed_hash_algos: 1 << HASH_ALGO_BLAKE3,
+            max_claim_age_slots: 0,
+            enabled_instructions: IX_ATTEST | IX_ARCHIVE,
+            dual_proof_threshold: 0,
+            secondary_prover: Pubkey::default(),
+            reserve_auditor: Pubkey::default(),
+            reserve_interval_seconds: 0,
+            attester_offline_after_slots: 0,
+            require_refund_transfer: false,
+            payout_mint: Pubkey::default(),
+            import_root: [0; 32],
+            import_until: 0,
+            fingerprint_threshold: 0,
+            retention_seconds: 0,
+            require_region_diversity: false,
+            verdict_threshold: 0,
+            max_pending_settlements: 0,
+            refund_source: Pubkey::default(),
+            bump,
+        }
+    }
+
+    pub fn is_enabled(&self, instruction: u32) -> bool {
+        self.enabled_instructions & instruction == instruction
+    }
+
+    pub fn allows_hash_algo(&self, hash_algo: u8) -> bool {
+        hash_algo < 8 && self.allowed_hash_algos & (1 << hash_algo) != 0
+    }
+
+    /// Whether the cross-field rules `update_config` enforces still hold
+    pub fn is_consistent(&self) -> bool {
+        self.allowed_hash_algos != 0
+            && self.archive_ttl_seconds >= 0
+            && self.reserve_interval_seconds >= 0
+            && self.retention_seconds >= 0
+            && self.import_until >= 0
+            && (!self.require_refund_transfer
+                || (self.payout_mint != Pubkey::default()
+                    && self.refund_source != Pubkey::default()))
+    }
+}
+
+/// A claim in the proving pipeline, closed when it is attested
+#[account]
+#[derive(InitSpace)]
+pub struct ProofJob {
+    pub claim_id: [u8; 32],
+
+    /// Attester that started the job and must attest the claim
+    pub attester: Pubkey,
+
+    /// Slot at which the claim entered proving
+    pub submitted_slot: u64,
+
+    /// Unix timestamp at which the claim entered proving
+    pub submitted_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Endpoint category of a merchant
+#[account]
+#[derive(InitSpace)]
+pub struct MerchantCategory {
+    /// SHA-256 of the merchant endpoint URL (as on attestations)
+    pub merchant_id: [u8; 32],
+
+    /// One of the `CATEGORY_*` codes, or an operator-defined one
+    pub category: u8,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Fraud payouts attested against merchants of one category
+#[account]
+#[derive(InitSpace)]
+pub struct CategoryStats {
+    pub category: u8,
+
+    /// Attestations counted into the category
+    pub count: u64,
+
+    /// Sum of their payout amounts, in micro-USDC
+    pub total_payout: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Claims against a merchant on one day of the dashboard window
+#[derive(
+    AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub struct DashboardDay {
+    /// Days since the unix epoch (see `unix_day`)
+    pub day: u32,
+    pub claims: u32,
+    /// Payouts in micro-USDC
+    pub payout: u64,
+}
+
+/// Claim figures of one merchant, for frontends to read in one fetch
+#[account]
+#[derive(InitSpace)]
+pub struct MerchantDashboard {
+    pub merchant_id: [u8; 32],
+
+    /// Payer of the rent, who can close it
+    pub opened_by: Pubkey,
+
+    /// Claims attested since the dashboard was opened
+    pub total_claims: u64,
+
+    /// Their payouts in micro-USDC
+    pub total_payout: u64,
+
+    /// Unix timestamp of the latest claim (0 if none)
+    pub last_claim_at: i64,
+
+    /// Ring of daily figures, indexed by day modulo `DASHBOARD_WINDOW_DAYS`
+    pub days: [DashboardDay; DASHBOARD_WINDOW_DAYS],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl MerchantDashboard {
+    /// Count a claim paid on `day` into the totals and the window
+    pub fn record(&mut self, day: u32, payout: u64) -> Result<()> {
+        self.total_claims = self
+            .total_claims
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.total_payout = self
+            .total_payout
+            .checked_add(payout)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let slot = &mut self.days[day as usize % DASHBOARD_WINDOW_DAYS];
+        if slot.day != day {
+            *slot = DashboardDay {
+                day,
+                ..DashboardDay::default()
+            };
+        }
+        slot.claims = slot.claims.saturating_add(1);
+        slot.payout = slot.payout.saturating_add(payout);
+        Ok(())
+    }
+
+    /// Claims and payout over the `DASHBOARD_WINDOW_DAYS` days ending on `today`
+    pub fn rolling(&self, today: u32) -> (u64, u64) {
+        self.days
+            .iter()
+            .filter(|slot| {
+                slot.claims > 0
+                    && slot.day <= today
+                    && today - slot.day < DASHBOARD_WINDOW_DAYS as u32
+            })
+            .fold((0, 0), |(claims, payout), slot| {
+                (claims + u64::from(slot.claims), payout + slot.payout)
+            })
+    }
+}
+
+/// Maintenance windows a merchant declared in advance
+#[account]
+#[derive(InitSpace)]
+pub struct MaintenanceSchedule {
+    pub merchant_id: [u8; 32],
+
+    /// Pending and active windows, in the order declared
+    #[max_len(MAX_MAINTENANCE_WINDOWS)]
+    pub windows: Vec<MaintenanceWindow>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl MaintenanceSchedule {
+    /// Whether evidence captured at `slot` falls inside a declared window
+    pub fn covers(&self, slot: u64) -> bool {
+        self.windows
+            .iter()
+            .any(|window| (window.start_slot..=window.end_slot).contains(&slot))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaintenanceWindow {
+    pub start_slot: u64,
+    pub end_slot: u64,
+
+    /// Slot the window was declared at, always before `start_slot`
+    pub declared_slot: u64,
+}
+
+/// Bloom filter of the claims attested against one merchant
+#[account]
+pub struct ClaimBloom {
+    /// SHA-256 of the merchant endpoint URL (as on attestations)
+    pub merchant_id: [u8; 32],
+
+    /// Bits set per claim
+    pub hash_count: u8,
+
+    /// Claims inserted, for estimating the current false-positive rate
+    pub count: u64,
+
+    /// Filter bits, bit `i` at `bits[i / 8] >> (i % 8)`
+    pub bits: Vec<u8>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ClaimBloom {
+    /// Account space, discriminator included, for a `size_bytes` bit array
+    pub fn space(size_bytes: u32) -> usize {
+        8 + 32 + 1 + 8 + 4 + size_bytes as usize + 1
+    }
+
+    /// Whether `claim_id` may have been attested; `false` is definite
+    pub fn contains(&self, claim_id: &[u8; 32]) -> bool {
+        self.bit_positions(claim_id)
+            .all(|bit| self.bits[bit / 8] & (1 << (bit % 8)) != 0)
+    }
+
+    fn insert(&mut self, claim_id: &[u8; 32]) {
+        for bit in self.bit_positions(claim_id) {
+            self.bits[bit / 8] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Double hashing over SHA-256 of the claim, so chosen claim ids
+    /// can't aim at particular bits
+    fn bit_positions(&self, claim_id: &[u8; 32]) -> impl Iterator<Item = usize> {
+        let digest = hashv(&[CLAIM_BLOOM_SEED, claim_id]).to_bytes();
+        let h1 = u64::from_le_bytes(digest[..8].try_into().expect("range is 8 bytes"));
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().expect("range is 8 bytes")) | 1;
+        let len = self.bits.len() as u64 * 8;
+        (0..u64::from(self.hash_count))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+}
+
+/// Claims attested with one response fingerprint
+#[account]
+#[derive(InitSpace)]
+pub struct FingerprintIndex {
+    pub fingerprint: [u8; 32],
+
+    /// Attestations carrying the fingerprint
+    pub count: u64,
+
+    /// First claim seen with the fingerprint
+    pub first_claim_id: [u8; 32],
+
+    /// Unix timestamp of the latest such attestation
+    pub last_attested_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Claims attested on one UTC day
+#[account]
+#[derive(InitSpace)]
+pub struct DayIndex {
+    /// Days since the unix epoch (see `unix_day`)
+    pub day: u32,
+
+    /// Attestations indexed on the day
+    pub count: u64,
+
+    /// Hash chain over every indexed claim id, in attestation order
+    pub claims_hash: [u8; 32],
+
+    /// The day's first `MAX_DAY_INDEX_CLAIMS` claim ids; past that,
+    /// claims are only counted and chained into `claims_hash`
+    #[max_len(MAX_DAY_INDEX_CLAIMS)]
+    pub claim_ids: Vec<[u8; 32]>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl DayIndex {
+    /// `claims_hash` after `claim_id` is appended to a chain ending in `prev`
+    pub fn chain(prev: &[u8; 32], claim_id: &[u8; 32]) -> [u8; 32] {
+        hashv(&[DAY_INDEX_SEED, prev, claim_id]).to_bytes()
+    }
+
+    /// Whether `claim_ids`, in order, are exactly the day's claims
+    ///
+    /// Lets a full list from an indexer be checked against the account
+    /// once the day has more claims than it lists.
+    pub fn matches(&self, claim_ids: &[[u8; 32]]) -> bool {
+        claim_ids.len() as u64 == self.count
+            && claim_ids
+                .iter()
+                .fold([0; 32], |hash, claim_id| DayIndex::chain(&hash, claim_id))
+                == self.claims_hash
+    }
+}
+
+/// Attestations stamped with one billing period, for monthly reconciliation
+#[account]
+#[derive(InitSpace)]
+pub struct BillingPeriod {
+    /// Month covered, encoded as YYYYMM (UTC)
+    pub period: u32,
+
+    /// Number of attestations in the period
+    pub count: u64,
+
+    /// Total payout attested in the period (micro-USDC)
+    pub total_payout: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// What an archived attestation's account is shrunk to
+///
+/// Holding the `[ATTESTATION_SEED, claim_id]` PDA keeps the claim from
+/// being attested (and refunded) again once archived.
+#[account]
+#[derive(InitSpace)]
+pub struct ArchivedAttestation {
+    pub claim_id: [u8; 32],
+
+    /// `ArchiveSummary` the record was folded into, as YYYYMM
+    pub period: u32,
+
+    /// Unix timestamp of archival
+    pub archived_at: i64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ArchiveSummary {
+    /// Month covered by this summary, encoded as YYYYMM
+    pub period: u32,
+
+    /// Number of attestations archived into this summary
+    pub count: u64,
+
+    /// Total payout of archived attestations (micro-USDC)
+    pub total_payout: u64,
+
+    /// Rolling SHA-256 of archived claim_ids: H(previous ‖ claim_id)
+    pub digest: [u8; 32],
+
+    /// Unix timestamp of the most recent archival
+    pub last_archived_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Claims feed delivery channel for a merchant
+#[account]
+#[derive(InitSpace)]
+pub struct NotificationChannel {
+    /// SHA-256 of the merchant endpoint URL (as on attestations)
+    pub merchant_id: [u8; 32],
+
+    /// Hash of the off-chain channel descriptor (webhook URL, signing key)
+    pub channel_hash: [u8; 32],
+
+    /// Unix timestamp of the latest registration
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Ed25519 key webhook payloads are signed with, and its predecessor
+#[account]
+#[derive(InitSpace)]
+pub struct WebhookSigner {
+    /// Current signing key
+    pub key: Pubkey,
+
+    /// Unix timestamp of the latest rotation
+    pub activated_at: i64,
+
+    /// Key replaced by the latest rotation, default if none
+    pub previous_key: Pubkey,
+
+    /// Unix time after which `previous_key` no longer verifies
+    pub previous_valid_until: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl WebhookSigner {
+    /// Whether `key` may sign deliveries received at unix time `now`
+    pub fn accepts(&self, key: &Pubkey, now: i64) -> bool {
+        *key != Pubkey::default()
+            && (*key == self.key || (*key == self.previous_key && now <= self.previous_valid_until))
+    }
+}
+
+/// Key allowed to annotate attestations
+#[account]
+#[derive(InitSpace)]
+pub struct AuditorRegistration {
+    pub auditor: Pubkey,
+
+    /// Unix timestamp of registration
+    pub registered_at: i64,
+
+    /// Annotations made since registration
+    pub annotations: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Audit findings anchored to one attestation
+#[account]
+#[derive(InitSpace)]
+pub struct AttestationAnnotations {
+    /// Claim of the annotated attestation
+    pub claim_id: [u8; 32],
+
+    /// In the order they were made
+    #[max_len(MAX_ANNOTATIONS)]
+    pub entries: Vec<Annotation>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// Hash of the off-chain finding
+    pub note_hash: [u8; 32],
+
+    /// Operator-defined kind of finding
+    pub category: u8,
+
+    pub auditor: Pubkey,
+
+    /// Unix timestamp of the annotation
+    pub annotated_at: i64,
+}
+
+/// Key allowed to record fraud verdicts, and the weight they carry
+#[account]
+#[derive(InitSpace)]
+pub struct VerdictSource {
+    pub source: Pubkey,
+
+    /// One of the `VERDICT_KIND_*` codes
+    pub kind: u8,
+
+    /// Weight of its verdicts towards `Config::verdict_threshold`
+    pub weight: u64,
+
+    /// Unix timestamp of the latest registration
+    pub registered_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Verdicts recorded on one claim
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimVerdicts {
+    pub claim_id: [u8; 32],
+
+    /// Total weight of the fraud verdicts in `entries`
+    pub fraud_weight: u64,
+
+    /// In the order they were recorded
+    #[max_len(MAX_CLAIM_VERDICTS)]
+    pub entries: Vec<Verdict>,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Verdict {
+    pub source: Pubkey,
+
+    /// The source's `VERDICT_KIND_*` code
+    pub kind: u8,
+
+    /// The source's weight when the verdict was recorded
+    pub weight: u64,
+
+    pub fraud_detected: bool,
+
+    /// Hash of the evidence the verdict is based on
+    pub evidence_hash: [u8; 32],
+
+    /// Unix timestamp of the verdict
+    pub recorded_at: i64,
+}
+
+/// Liveness of a registered attester
+#[account]
+#[derive(InitSpace)]
+pub struct AttesterStatus {
+    pub attester: Pubkey,
+
+    /// Unix timestamp of registration
+    pub registered_at: i64,
+
+    /// Slot of the latest heartbeat (or registration)
+    pub last_heartbeat_slot: u64,
+
+    /// Heartbeats received since registration
+    pub heartbeats: u64,
+
+    /// Set by `mark_attester_offline`, cleared by the next heartbeat
+    pub offline: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// ISO 3166-1 alpha-2 region code, zeros if not set
+    pub region: [u8; 2],
+}
+
+impl AttesterStatus {
+    /// Whether more than `gap_slots` have passed since the last heartbeat
+    pub fn is_stale(&self, slot: u64, gap_slots: u64) -> bool {
+        gap_slots > 0 && slot.saturating_sub(self.last_heartbeat_slot) > gap_slots
+    }
+}
+
+/// Identity an attester key was rotated from
+#[account]
+#[derive(InitSpace)]
+pub struct AttesterLink {
+    pub attester: Pubkey,
+
+    /// First key of the rotation chain, identifying the operator
+    pub operator: Pubkey,
+
+    /// Key this one replaced
+    pub previous: Pubkey,
+
+    /// Unix timestamp of the rotation
+    pub rotated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Verified build of the currently recorded deployment
+#[account]
+#[derive(InitSpace)]
+pub struct UpgradeInfo {
+    /// Git commit (SHA-1) the deployed program was built from
+    pub commit_hash: [u8; 20],
+
+    /// Executable hash from a `solana-verify` reproducible build
+    pub verified_build_hash: [u8; 32],
+
+    /// ProgramData deployment slot when the record was written
+    pub deployed_slot: u64,
+
+    /// Unix timestamp of the record
+    pub recorded_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Counterpart of an attestation on another chain
+#[account]
+#[derive(InitSpace)]
+pub struct ForeignLink {
+    /// Claim of the linked attestation
+    pub claim_id: [u8; 32],
+
+    /// EIP-155 chain id of the verifier contract's chain
+    pub foreign_chain_id: u64,
+
+    /// The verifier contract's reference to its attestation of the proof
+    pub foreign_attestation_ref: [u8; 32],
+
+    /// Unix timestamp of the link
+    pub linked_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Progress of settlement batching
+#[account]
+#[derive(InitSpace)]
+pub struct SettlementLedger {
+    /// Batches committed so far; also the index of the next batch
+    pub batch_count: u64,
+
+    /// Lowest attestation sequence the next batch may include
+    pub next_sequence: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Attestations not yet committed to a settlement batch
+///
+/// Counts by sequence number, so attestations recorded without the queue
+/// are still counted by the next one that passes it.
+#[account]
+#[derive(InitSpace)]
+pub struct SettlementQueue {
+    /// Sequence after the latest attestation counted in
+    pub enqueued: u64,
+
+    /// Lowest sequence not yet in a settlement batch
+    pub settled: u64,
+
+    /// `enqueued - settled`: the queue depth
+    pub pending: u64,
+
+    /// Unix timestamp at or before which the oldest pending attestation
+    /// was recorded (0 when the queue is empty)
+    pub oldest_pending_at: i64,
+
+    /// Deepest the queue has been
+    pub high_water_mark: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl SettlementQueue {
+    /// Count attestation `sequence`, recorded at `now`, in
+    pub fn enqueue(&mut self, sequence: u64, now: i64) {
+        if self.pending == 0 {
+            self.oldest_pending_at = now;
+        }
+        self.enqueued = self.enqueued.max(sequence + 1);
+        self.pending = self.enqueued.saturating_sub(self.settled);
+        self.high_water_mark = self.high_water_mark.max(self.pending);
+    }
+
+    /// Drop sequences below `next_sequence`, the last of them recorded at
+    /// `last_settled_at`
+    ///
+    /// The oldest remaining attestation was recorded at or after
+    /// `last_settled_at`, so it stands in for its exact time.
+    pub fn settle(&mut self, next_sequence: u64, last_settled_at: i64) {
+        self.settled = self.settled.max(next_sequence);
+        self.enqueued = self.enqueued.max(self.settled);
+        self.pending = self.enqueued - self.settled;
+        self.oldest_pending_at = if self.pending == 0 {
+            0
+        } else {
+            self.oldest_pending_at.max(last_settled_at)
+        };
+    }
+}
+
+/// Merkle commitment to one batch of settled claims
+#[account]
+#[derive(InitSpace)]
+pub struct SettlementBatch {
+    pub batch_index: u64,
+
+    /// Sequences covered; attestations in the range may have been left out
+    pub first_sequence: u64,
+    pub last_sequence: u64,
+
+    /// Number of leaves (batched attestations)
+    pub leaf_count: u32,
+
+    /// `settlement_root` of the batched `evm_digest`s, in sequence order
+    pub root: [u8; 32],
+
+    /// Unix timestamp of the commitment
+    pub committed_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// One recursive proof covering a batch of claims
+#[account]
+#[derive(InitSpace)]
+pub struct AggregateProof {
+    /// `merkle_root` over the claims' `aggregate_leaf`s
+    pub batch_root: [u8; 32],
+
+    /// Hash of the recursive proof, computed with `hash_algo`
+    pub proof_hash: [u8; 32],
+
+    /// Hash algorithm of `proof_hash` (`HASH_ALGO_*`)
+    pub hash_algo: u8,
+
+    /// Claims in the batch
+    pub count: u32,
+
+    /// Sum of the claims' payouts in micro-USDC
+    pub total_payout: u64,
+
+    /// Claims materialized so far
+    pub materialized: u32,
+
+    /// Payout of the claims materialized so far
+    pub materialized_payout: u64,
+
+    /// Signer of `attest_aggregated_proof`, recorded as each claim's attester
+    pub attester: Pubkey,
+
+    /// Unix timestamp and slot of the batch attestation
+    pub attested_at: i64,
+    pub attested_slot: u64,
+
+    /// Circuit and public inputs schema of every claim in the batch
+    pub circuit_version: u16,
+    pub schema_id: u16,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// SPL Memo an attestation was claimed from by `claim_legacy_attestation`
+#[account]
+#[derive(InitSpace)]
+pub struct LegacyMemo {
+    /// Claim of the attestation
+    pub claim_id: [u8; 32],
+
+    /// Signature of the transaction that carried the memo
+    pub memo_signature: [u8; 64],
+
+    /// Unix timestamp of the claim
+    pub claimed_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Archive location of the full proof behind an attestation
+#[account]
+#[derive(InitSpace)]
+pub struct ProofUri {
+    /// Claim of the attestation the proof belongs to
+    pub claim_id: [u8; 32],
+
+    /// Where the proof bytes are archived
+    #[max_len(MAX_PROOF_URI_LEN)]
+    pub uri: String,
+
+    /// Proof size in bytes
+    pub size: u64,
+
+    /// Hash of the proof bytes (equals `proof_hash` for Blake3 records)
+    pub checksum: [u8; 32],
+
+    /// Unix timestamp of the latest registration
+    pub registered_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Registered layout of a schema's evidence inputs
+#[account]
+#[derive(InitSpace)]
+pub struct PublicInputsSchema {
+    pub schema_id: u16,
+
+    /// One entry per evidence input, in `public_inputs` order
+    #[max_len(MAX_SCHEMA_FIELDS)]
+    pub fields: Vec<SchemaField>,
+
+    /// Unix timestamp of the registration
+    pub registered_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug, PartialEq, Eq)]
+pub struct SchemaField {
+    #[max_len(MAX_SCHEMA_FIELD_NAME_LEN)]
+    pub name: String,
+
+    pub kind: SchemaFieldKind,
+
+    /// Inclusive bounds of the input
+    pub min: u64,
+    pub max: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaFieldKind {
+    /// Flag, 0 or 1
+    Bool,
+    /// Unsigned integer (status codes, lengths, latencies, ...)
+    Uint,
+}
+
+impl PublicInputsSchema {
+    /// Whether the evidence inputs fit the schema
+    ///
+    /// Inputs past the declared fields must be zero; the payout amount
+    /// (`public_inputs[3]`) is not constrained by the schema.
+    pub fn accepts(&self, public_inputs: &[u64; 4]) -> bool {
+        public_inputs[..MAX_SCHEMA_FIELDS]
+            .iter()
+            .enumerate()
+            .all(|(index, &input)| match self.fields.get(index) {
+                Some(field) => (field.min..=field.max).contains(&input),
+                None => input == 0,
+            })
+    }
+}
+
+/// Latest proof-of-reserve statement for the payout vault
+#[account]
+#[derive(InitSpace)]
+pub struct ReserveAttestation {
+    /// Auditor that signed the statement
+    pub auditor: Pubkey,
+
+    /// Payout vault balance (micro-USDC)
+    pub vault_balance: u64,
+
+    /// Payout exposure reserved against open claims (micro-USDC)
+    pub reserved_exposure: u64,
+
+    /// SHA-256 of the off-chain bank reserves report
+    pub bank_reserves_hash: [u8; 32],
+
+    /// Unix timestamp of the statement
+    pub attested_at: i64,
+
+    /// Statements published so far
+    pub count: u64,
+
+    /// The current statement is overdue (set by `flag_missed_reserves`)
+    pub missed: bool,
+
+    /// Scheduled statements that were missed or late
+    pub missed_count: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ReserveAttestation {
+    /// Whether the next statement is past due under `interval_seconds`
+    pub fn is_overdue(&self, now: i64, interval_seconds: i64) -> bool {
+        interval_seconds > 0 && now.saturating_sub(self.attested_at) > interval_seconds
+    }
+
+    /// Whether the reported vault balance covers the reserved exposure
+    pub fn is_covered(&self) -> bool {
+        self.vault_balance >= self.reserved_exposure
+    }
+}
+
+/// Durable nonce accounts the backend submits attestations with; the
+/// only ones attestations may be sent with
+#[account]
+#[derive(InitSpace)]
+pub struct NonceRegistry {
+    /// Registered nonce accounts; only the first `count` are set
+    pub accounts: [Pubkey; MAX_NONCE_ACCOUNTS],
+
+    pub count: u8,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl NonceRegistry {
+    pub fn registered(&self) -> &[Pubkey] {
+        &self.accounts[..usize::from(self.count)]
+    }
+
+    pub fn contains(&self, nonce_account: &Pubkey) -> bool {
+        self.registered().contains(nonce_account)
+    }
+
+    /// Whether registered entries are set and distinct, and the rest unset
+    pub fn is_consistent(&self) -> bool {
+        let count = usize::from(self.count);
+        count <= MAX_NONCE_ACCOUNTS
+            && self.accounts[count..].iter().all(|key| *key == Pubkey::default())
+            && self.accounts[..count].iter().enumerate().all(|(index, key)| {
+                *key != Pubkey::default() && !self.accounts[..index].contains(key)
+            })
+    }
+}
+
+/// Cluster a deployment runs on, as recorded by the admin
+#[account]
+#[derive(InitSpace)]
+pub struct ClusterInfo {
+    /// Genesis hash of the cluster
+    pub genesis_hash: [u8; 32],
+
+    /// Unix timestamp of the record
+    pub recorded_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Checksum of an exported audit report, anchored by `report_hash`
+#[account]
+#[derive(InitSpace)]
+pub struct AuditReport {
+    /// One of the `REPORT_KIND_*` codes
+    pub kind: u8,
+
+    /// Month covered, encoded as YYYYMM (UTC)
+    pub period: u32,
+
+    /// SHA-256 of the report file
+    pub checksum: [u8; 32],
+
+    /// Data rows in the report (header excluded)
+    pub row_count: u64,
+
+    pub reported_by: Pubkey,
+
+    /// Unix timestamp of the anchoring
+    pub reported_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// Live attestation counts by account layout and proof circuit version
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramStats {
+    /// Attestations per layout version (index = version)
+    pub by_layout: [u64; STATS_LAYOUT_BUCKETS],
+
+    /// Attestations per circuit version; the last bucket holds all higher ones
+    pub by_circuit: [u64; STATS_CIRCUIT_BUCKETS],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+/// `ProgramStats` as created before layout 8, with
+/// `STATS_V1_LAYOUT_BUCKETS` layout buckets; read by `migrate_program_stats`
+///
+/// Shares the `ProgramStats` discriminator; told apart by data length.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct ProgramStatsV1 {
+    pub by_layout: [u64; STATS_V1_LAYOUT_BUCKETS],
+    pub by_circuit: [u64; STATS_CIRCUIT_BUCKETS],
+    pub bump: u8,
+}
+
+impl ProgramStatsV1 {
+    /// The statistics in the current layout, with empty added buckets
+    pub fn upgrade(self) -> ProgramStats {
+        let mut by_layout = [0; STATS_LAYOUT_BUCKETS];
+        by_layout[..STATS_V1_LAYOUT_BUCKETS].copy_from_slice(&self.by_layout);
+        ProgramStats {
+            by_layout,
+            by_circuit: self.by_circuit,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Bucket family in `ProgramStats`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum StatsBucket {
+    Layout,
+    Circuit,
+}
+
+impl ProgramStats {
+    fn bucket(&mut self, kind: StatsBucket, version: u16) -> (&mut u64, u16) {
+        match kind {
+            StatsBucket::Layout => {
+                let index = usize::from(version).min(STATS_LAYOUT_BUCKETS - 1);
+                (&mut self.by_layout[index], index as u16)
+            }
+            StatsBucket::Circuit => {
+                let index = usize::from(version).min(STATS_CIRCUIT_BUCKETS - 1);
+                (&mut self.by_circuit[index], index as u16)
+            }
+        }
+    }
+
+    /// Count one attestation into a bucket
+    pub fn add(&mut self, kind: StatsBucket, version: u16) -> Result<()> {
+        let (count, bucket) = self.bucket(kind, version);
+        *count = count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        if *count == 1 {
+            emit!(StatsBucketChanged { kind, bucket, count: 1 });
+        }
+        Ok(())
+    }
+
+    /// Remove one attestation from a bucket
+    pub fn remove(&mut self, kind: StatsBucket, version: u16) {
+        let (count, bucket) = self.bucket(kind, version);
+        if *count == 1 {
+            emit!(StatsBucketChanged { kind, bucket, count: 0 });
+        }
+        *count = count.saturating_sub(1);
+    }
+
+    /// Move one attestation from layout `version` to the current layout
+    ///
+    /// Records from before `STATS_CIRCUIT_LAYOUT_VERSION` were never
+    /// counted by circuit, so they enter circuit bucket 0 (unknown) here;
+    /// later ones already sit in their circuit's bucket.
+    pub fn upgrade(&mut self, version: u8) -> Result<()> {
+        self.remove(StatsBucket::Layout, u16::from(version));
+        self.add(StatsBucket::Layout, u16::from(ATTESTATION_LAYOUT_VERSION))?;
+        if version < STATS_CIRCUIT_LAYOUT_VERSION {
+            self.add(StatsBucket::Circuit, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the layout buckets from `STATS_CIRCUIT_LAYOUT_VERSION` on
+    /// add up to the circuit buckets' total
+    ///
+    /// Attestations are counted by circuit from that layout on and enter
+    /// and leave both families together; older records join the circuit
+    /// buckets when `upgrade_layout` moves them. Legacy layout buckets are
+    /// partial while a `scan_and_tag` pass is in progress, so this only
+    /// holds once the pass completes.
+    pub fn is_consistent(&self) -> bool {
+        let total = |counts: &[u64]| {
+            counts
+                .iter()
+                .try_fold(0u64, |total, count| total.checked_add(*count))
+        };
+        let counted = total(&self.by_layout[usize::from(STATS_CIRCUIT_LAYOUT_VERSION)..]);
+        counted.is_some() && counted == total(&self.by_circuit)
+    }
+
+    fn clear(&mut self, kind: StatsBucket, version: u16) {
+        let (count, bucket) = self.bucket(kind, version);
+        if *count > 0 {
+            *count = 0;
+            emit!(StatsBucketChanged { kind, bucket, count: 0 });
+        }
+    }
+}
+
+/// Progress of the attestation layout migration
+#[account]
+#[derive(InitSpace, Default)]
+pub struct MigrationStatus {
+    /// Layout version being migrated to (`ATTESTATION_LAYOUT_VERSION`)
+    pub target_version: u8,
+
+    /// Attestations counted by `scan_and_tag`
+    pub scanned: u64,
+
+    /// Scanned attestations found in an old layout
+    pub legacy_found: u64,
+
+    /// Attestations rewritten by `upgrade_layout`
+    pub migrated: u64,
+
+    /// Highest address scanned so far; the next batch starts above it
+    pub last_scanned: Pubkey,
+
+    /// Unix timestamp of the last scan or upgrade batch
+    pub updated_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl MigrationStatus {
+    /// Tagged legacy attestations not yet upgraded
+    pub fn remaining(&self) -> u64 {
+        self.legacy_found.saturating_sub(self.migrated)
+    }
+}
+
+#[event]
+pub struct ProofAttested {
+    pub claim_id: [u8; 32],
+    pub proof_hash: [u8; 32],
+    pub hash_algo: u8,
+    pub payout_amount: u64,
+    pub attested_at: i64,
+    pub sequence: u64,
+    pub evm_digest: [u8; 32],
+}
+
+/// Verbose attestation event carrying every public input by name
+///
+/// Only emitted when `Config::emit_evidence_events` is set.
+#[event]
+pub struct ProofAttestedV2 {
     pub claim_id: [u8; 32],
     pub proof_hash: [u8; 32],
+    pub hash_algo: u8,
+    pub merchant_id: [u8; 32],
+    pub fraud_detected: u64,
+    pub http_status: u64,
+    pub body_length: u64,
     pub payout_amount: u64,
     pub attested_at: i64,
 }
 
+#[event]
+pub struct AttestationArchived {
+    pub claim_id: [u8; 32],
+    pub period: u32,
+    pub count: u64,
+    pub digest: [u8; 32],
+}
+
+#[event]
+pub struct MerchantDashboardOpened {
+    pub merchant_id: [u8; 32],
+    pub opened_by: Pubkey,
+}
+
+#[event]
+pub struct MerchantDashboardClosed {
+    pub merchant_id: [u8; 32],
+}
+
+/// Channel registered or replaced
+#[event]
+pub struct NotificationChannelRegistered {
+    pub merchant_id: [u8; 32],
+    pub channel_hash: [u8; 32],
+}
+
+#[event]
+pub struct NotificationChannelRemoved {
+    pub merchant_id: [u8; 32],
+}
+
+#[event]
+pub struct AttestationAnnotated {
+    pub claim_id: [u8; 32],
+    pub note_hash: [u8; 32],
+    pub category: u8,
+    pub auditor: Pubkey,
+    pub annotated_at: i64,
+}
+
+#[event]
+pub struct ForeignAttestationLinked {
+    pub claim_id: [u8; 32],
+    pub foreign_chain_id: u64,
+    pub foreign_attestation_ref: [u8; 32],
+}
+
+#[event]
+pub struct SettlementBatchCommitted {
+    pub batch_index: u64,
+    pub root: [u8; 32],
+    pub first_sequence: u64,
+    pub last_sequence: u64,
+    /// `evm_digest` of each batched attestation, in leaf order
+    pub leaves: Vec<[u8; 32]>,
+    pub committed_at: i64,
+}
+
+#[event]
+pub struct WebhookSignerRotated {
+    pub key: Pubkey,
+    pub previous_key: Pubkey,
+    pub previous_valid_until: i64,
+}
+
+#[event]
+pub struct ClaimantUnlinked {
+    pub claim_id: [u8; 32],
+    /// SHA-256 of salt ‖ original proof URI
+    pub uri_commitment: [u8; 32],
+}
+
+#[event]
+pub struct MaintenanceDeclared {
+    pub merchant_id: [u8; 32],
+    pub start_slot: u64,
+    pub end_slot: u64,
+}
+
+#[event]
+pub struct ClaimBloomCreated {
+    pub merchant_id: [u8; 32],
+    pub size_bytes: u32,
+    pub hash_count: u8,
+}
+
+#[event]
+pub struct MerchantCategoryRegistered {
+    pub merchant_id: [u8; 32],
+    pub category: u8,
+}
+
+/// Verdict source registered or reweighted
+#[event]
+pub struct VerdictSourceRegistered {
+    pub source: Pubkey,
+    pub kind: u8,
+    pub weight: u64,
+}
+
+#[event]
+pub struct VerdictSourceRemoved {
+    pub source: Pubkey,
+}
+
+#[event]
+pub struct VerdictRecorded {
+    pub claim_id: [u8; 32],
+    pub source: Pubkey,
+    pub fraud_detected: bool,
+    pub weight: u64,
+    /// The claim's fraud weight after this verdict
+    pub fraud_weight: u64,
+}
+
+#[event]
+pub struct ReportHashAnchored {
+    pub kind: u8,
+    pub period: u32,
+    pub checksum: [u8; 32],
+    pub row_count: u64,
+}
+
+#[event]
+pub struct AttesterKeyRotated {
+    pub operator: Pubkey,
+    pub old: Pubkey,
+    pub new: Pubkey,
+    pub rotated_at: i64,
+}
+
+#[event]
+pub struct AttesterOffline {
+    pub attester: Pubkey,
+    pub last_heartbeat_slot: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AttesterOnline {
+    pub attester: Pubkey,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BuildHashRecorded {
+    pub commit_hash: [u8; 20],
+    pub verified_build_hash: [u8; 32],
+    pub deployed_slot: u64,
+}
+
+#[event]
+pub struct SchemaRegistered {
+    pub schema_id: u16,
+    pub field_count: u8,
+}
+
+/// A proving job ended with its attestation
+#[event]
+pub struct ProofJobCompleted {
+    pub claim_id: [u8; 32],
+    pub submitted_slot: u64,
+    pub attested_slot: u64,
+    pub proving_slots: u64,
+    pub duration_seconds: i64,
+}
+
+/// A response fingerprint reached `Config::fingerprint_threshold` claims
+#[event]
+pub struct RepeatFailureDetected {
+    pub fingerprint: [u8; 32],
+    pub count: u64,
+    pub first_claim_id: [u8; 32],
+    /// Merchant of the claim that reached the threshold
+    pub merchant_id: [u8; 32],
+}
+
+#[event]
+pub struct HistoricalAttestationImported {
+    pub claim_id: [u8; 32],
+    pub attested_at: i64,
+    pub payout_amount: u64,
+}
+
+#[event]
+pub struct AggregatedProofAttested {
+    pub batch_root: [u8; 32],
+    pub proof_hash: [u8; 32],
+    pub count: u32,
+    pub total_payout: u64,
+    pub attester: Pubkey,
+}
+
+#[event]
+pub struct ClaimMaterialized {
+    pub claim_id: [u8; 32],
+    pub batch_root: [u8; 32],
+    pub materialized: u32,
+}
+
+#[event]
+pub struct LegacyAttestationClaimed {
+    pub claim_id: [u8; 32],
+    pub memo_signature: [u8; 64],
+    pub attested_at: i64,
+    pub payout_amount: u64,
+}
+
+#[event]
+pub struct ProofUriRegistered {
+    pub claim_id: [u8; 32],
+    pub uri: String,
+    pub size: u64,
+    pub checksum: [u8; 32],
+}
+
+#[event]
+pub struct ReservesAttested {
+    pub auditor: Pubkey,
+    pub vault_balance: u64,
+    pub reserved_exposure: u64,
+    pub bank_reserves_hash: [u8; 32],
+    pub attested_at: i64,
+    pub count: u64,
+}
+
+#[event]
+pub struct ReserveAttestationMissed {
+    pub last_attested_at: i64,
+    pub missed_count: u64,
+}
+
+/// A `ProgramStats` bucket became occupied (`count` 1) or empty (`count` 0)
+#[event]
+pub struct StatsBucketChanged {
+    pub kind: StatsBucket,
+    pub bucket: u16,
+    pub count: u64,
+}
+
+#[event]
+pub struct LegacyAttestationTagged {
+    pub attestation: Pubkey,
+    pub layout_version: u8,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid proof hash")]
@@ -129,4 +6195,768 @@ pub enum ErrorCode {
 
     #[msg("Attestation already exists")]
     AttestationExists,
+
+    #[msg("Signer is not authorized for this instruction")]
+    Unauthorized,
+
+    #[msg("Invalid config parameter")]
+    InvalidConfig,
+
+    #[msg("Attestation is too recent to archive")]
+    AttestationTooRecent,
+
+    #[msg("Period does not match the attestation timestamp")]
+    PeriodMismatch,
+
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+
+    #[msg("Related attestation does not match related_claim_id")]
+    RelatedClaimMismatch,
+
+    #[msg("claim_id does not match its derivation")]
+    ClaimIdMismatch,
+
+    #[msg("Proof hash algorithm is not allowed by config")]
+    HashAlgoNotAllowed,
+
+    #[msg("Claim evidence is older than the allowed window")]
+    EvidenceTooOld,
+
+    #[msg("Evidence slot is in the future")]
+    InvalidEvidenceSlot,
+
+    #[msg("Instruction is disabled by config")]
+    InstructionDisabled,
+
+    #[msg("Payout requires a secondary proof hash")]
+    SecondaryProofRequired,
+
+    #[msg("Secondary proof must be co-signed by the configured prover")]
+    SecondaryProverRequired,
+
+    #[msg("Account is not an attestation in a known layout")]
+    UnknownAccountLayout,
+
+    #[msg("Migration batch must be sorted and start after the last scanned address")]
+    MigrationOutOfOrder,
+
+    #[msg("Account is not a registered, initialized nonce account")]
+    InvalidNonceAccount,
+
+    #[msg("Nonce registry is full")]
+    NonceRegistryFull,
+
+    #[msg("Reserve attestation is not overdue")]
+    ReservesNotOverdue,
+
+    #[msg("Proof URI is empty or too long")]
+    InvalidProofUri,
+
+    #[msg("Proof checksum does not match the attested proof hash")]
+    ProofChecksumMismatch,
+
+    #[msg("Account is not this program's ProgramData")]
+    InvalidProgramData,
+
+    #[msg("Attester heartbeat is within the allowed gap")]
+    AttesterNotStale,
+
+    #[msg("Attestation must be followed by the matching refund transfer")]
+    RefundTransferRequired,
+
+    #[msg("Schema has no fields, too many fields or an invalid field")]
+    InvalidSchema,
+
+    #[msg("Schema account does not match schema_id")]
+    SchemaMismatch,
+
+    #[msg("Historical import is closed")]
+    ImportClosed,
+
+    #[msg("Record is not in the published historical dataset")]
+    InvalidImportProof,
+
+    #[msg("Response fingerprint must be non-zero and recorded once per attestation")]
+    InvalidResponseFingerprint,
+
+    #[msg("merchant_category and category_stats must be passed together and match merchant_id")]
+    CategoryMismatch,
+
+    #[msg("Retention period has not elapsed since attestation")]
+    RetentionNotElapsed,
+
+    #[msg("Claimant has been unlinked from this attestation")]
+    AlreadyUnlinked,
+
+    #[msg("Claim filter size or hash count out of range")]
+    InvalidClaimBloom,
+
+    #[msg("claim_bloom does not belong to the claim's merchant")]
+    ClaimBloomMismatch,
+
+    #[msg("Webhook signing key must be set and differ from the current one")]
+    InvalidWebhookSigner,
+
+    #[msg("Region must be an uppercase ISO 3166-1 alpha-2 code")]
+    InvalidRegion,
+
+    #[msg("Dual-proof signers must be registered in two different regions")]
+    RegionDiversityRequired,
+
+    #[msg("Foreign chain id and attestation reference must be non-zero")]
+    InvalidForeignAttestation,
+
+    #[msg("Attestation already has the maximum number of annotations")]
+    AnnotationsFull,
+
+    #[msg("Maintenance window must start after the current slot and end at or after its start")]
+    InvalidMaintenanceWindow,
+
+    #[msg("Merchant already has the maximum number of pending maintenance windows")]
+    MaintenanceScheduleFull,
+
+    #[msg("Maintenance schedule does not belong to the attested merchant")]
+    MaintenanceScheduleMismatch,
+
+    #[msg("Evidence was captured during a declared maintenance window")]
+    MaintenanceWindowActive,
+
+    #[msg("Attester rotation must move to a different key, which must sign")]
+    InvalidAttesterRotation,
+
+    #[msg("Settlement batch must be the next one and hold attestations in sequence order after the last batch")]
+    InvalidSettlementBatch,
+
+    #[msg("Invariant violated: config fields are inconsistent")]
+    ConfigInvariantViolated,
+
+    #[msg("Invariant violated: layout and circuit statistics disagree")]
+    StatsInvariantViolated,
+
+    #[msg("Invariant violated: reported vault balance is below reserved exposure")]
+    ReservesNotCovered,
+
+    #[msg("Invariant violated: nonce registry entries are unset, duplicated or out of range")]
+    NonceRegistryInvariantViolated,
+
+    #[msg("Claim already has the maximum number of verdicts")]
+    ClaimVerdictsFull,
+
+    #[msg("Verdict source already recorded a verdict on this claim")]
+    VerdictAlreadyRecorded,
+
+    #[msg("Claim's fraud verdicts do not reach the configured threshold")]
+    VerdictThresholdNotMet,
+
+    #[msg("Inclusion proof does not lead to the batch root")]
+    InvalidInclusionProof,
+
+    #[msg("Aggregated proof's claim count or payout is used up")]
+    AggregateExhausted,
+
+    #[msg("Merchant dashboard belongs to a different merchant")]
+    MerchantDashboardMismatch,
+
+    #[msg("Transaction was signed for a different cluster")]
+    ClusterMismatch,
+
+    #[msg("Report period has not ended yet")]
+    ReportPeriodOpen,
+
+    #[msg("Too many attestations are awaiting settlement")]
+    SettlementBacklog,
+}
+
+/// Canonical claim_id: blake3(payment_signature ‖ endpoint_hash ‖ claimant)
+pub fn derive_claim_id(
+    payment_signature: &[u8; 64],
+    endpoint_hash: &[u8; 32],
+    claimant: &Pubkey,
+) -> [u8; 32] {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(payment_signature);
+    hasher.update(endpoint_hash);
+    hasher.update(claimant.as_ref());
+    *hasher.finalize().as_bytes()
+}
+
+/// Merkle leaf of a historical claim record
+///
+/// SHA-256 over `HISTORICAL_LEAF_DOMAIN` and the fields in argument
+/// order, integers little-endian.
+pub fn historical_leaf(
+    claim_id: &[u8; 32],
+    attested_at: i64,
+    proof_hash: &[u8; 32],
+    hash_algo: u8,
+    public_inputs: &[u64; 4],
+    refund_signature: &[u8; 64],
+    merchant_id: &[u8; 32],
+) -> [u8; 32] {
+    let mut inputs = [0u8; 32];
+    for (chunk, input) in inputs.chunks_exact_mut(8).zip(public_inputs) {
+        chunk.copy_from_slice(&input.to_le_bytes());
+    }
+    hashv(&[
+        HISTORICAL_LEAF_DOMAIN,
+        claim_id,
+        &attested_at.to_le_bytes(),
+        proof_hash,
+        &[hash_algo],
+        &inputs,
+        refund_signature,
+        merchant_id,
+    ])
+    .to_bytes()
+}
+
+/// Merkle leaf of a claim in an aggregated proof
+///
+/// SHA-256 over `AGGREGATE_LEAF_DOMAIN` and the fields in argument order,
+/// integers little-endian.
+pub fn aggregate_leaf(
+    claim_id: &[u8; 32],
+    public_inputs: &[u64; 4],
+    evidence_slot: u64,
+    refund_signature: &[u8; 64],
+    merchant_id: &[u8; 32],
+) -> [u8; 32] {
+    let mut inputs = [0u8; 32];
+    for (chunk, input) in inputs.chunks_exact_mut(8).zip(public_inputs) {
+        chunk.copy_from_slice(&input.to_le_bytes());
+    }
+    hashv(&[
+        AGGREGATE_LEAF_DOMAIN,
+        claim_id,
+        &inputs,
+        &evidence_slot.to_le_bytes(),
+        refund_signature,
+        merchant_id,
+    ])
+    .to_bytes()
+}
+
+/// Root reached from `leaf` along a Merkle path
+///
+/// Each node is SHA-256 of its two children in ascending byte order, so
+/// the path needs no left/right flags.
+pub fn merkle_root(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling {
+            (&node, sibling)
+        } else {
+            (sibling, &node)
+        };
+        hashv(&[first, second]).to_bytes()
+    })
+}
+
+/// Parent of two nodes in a settlement batch tree: keccak256(left ‖ right)
+pub fn settlement_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[left, right]).to_bytes()
+}
+
+/// Merkle root over ordered settlement leaves
+///
+/// Unlike `merkle_root`, pairs keep their leaf order, as the EVM notary
+/// expects. An odd node at the end of a level moves up unchanged. The
+/// root of a single leaf is the leaf itself; of no leaves, zero.
+pub fn settlement_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => settlement_node(left, right),
+                _ => pair[0],
+            })
+            .collect();
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+/// UTC day of a unix timestamp, as days since the epoch
+pub fn unix_day(unix_timestamp: i64) -> u32 {
+    unix_timestamp.div_euclid(86_400) as u32
+}
+
+/// Calendar month (UTC) of a unix timestamp, encoded as YYYYMM
+pub fn month_id(unix_timestamp: i64) -> u32 {
+    // Days-to-civil conversion (Howard Hinnant's algorithm)
+    let z = unix_timestamp.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year * 100 + month) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::system_instruction;
+    use sysvar_instructions::{
+        construct_instructions_data, store_current_index, BorrowedAccountMeta, BorrowedInstruction,
+    };
+
+    use super::*;
+
+    fn empty_stats() -> ProgramStats {
+        ProgramStats {
+            by_layout: [0; STATS_LAYOUT_BUCKETS],
+            by_circuit: [0; STATS_CIRCUIT_BUCKETS],
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn stats_stay_consistent_through_upgrades() {
+        let current = u16::from(ATTESTATION_LAYOUT_VERSION);
+        let mut stats = empty_stats();
+        assert!(stats.is_consistent());
+
+        // Live attestations enter both families
+        stats.add(StatsBucket::Layout, current).unwrap();
+        stats.add(StatsBucket::Circuit, 2).unwrap();
+        assert!(stats.is_consistent());
+        stats.add(StatsBucket::Layout, current).unwrap();
+        assert!(!stats.is_consistent());
+        stats.add(StatsBucket::Circuit, 40).unwrap();
+        assert_eq!(stats.by_circuit[STATS_CIRCUIT_BUCKETS - 1], 1);
+        assert!(stats.is_consistent());
+
+        // A layout 1 record is uncounted by circuit until upgraded
+        stats.add(StatsBucket::Layout, 1).unwrap();
+        assert!(stats.is_consistent());
+        stats.upgrade(1).unwrap();
+        assert_eq!(stats.by_layout[1], 0);
+        assert_eq!(stats.by_circuit[0], 1);
+        assert!(stats.is_consistent());
+
+        // A layout 5 record was counted by circuit when created
+        stats.add(StatsBucket::Layout, 5).unwrap();
+        stats.add(StatsBucket::Circuit, 1).unwrap();
+        assert!(stats.is_consistent());
+        stats.upgrade(5).unwrap();
+        assert_eq!(stats.by_circuit[0], 1);
+        assert_eq!(stats.by_layout[usize::from(current)], 4);
+        assert!(stats.is_consistent());
+
+        stats.remove(StatsBucket::Layout, current);
+        stats.remove(StatsBucket::Circuit, 1);
+        assert!(stats.is_consistent());
+    }
+
+    #[test]
+    fn v1_stats_keep_their_counts() {
+        let mut by_layout = [0; STATS_V1_LAYOUT_BUCKETS];
+        by_layout[STATS_V1_LAYOUT_BUCKETS - 1] = 3;
+        let mut by_circuit = [0; STATS_CIRCUIT_BUCKETS];
+        by_circuit[2] = 3;
+        let stats = ProgramStatsV1 {
+            by_layout,
+            by_circuit,
+            bump: 7,
+        }
+        .upgrade();
+        assert_eq!(stats.by_layout[STATS_V1_LAYOUT_BUCKETS - 1], 3);
+        assert_eq!(stats.by_layout[STATS_V1_LAYOUT_BUCKETS..], [0; 8]);
+        assert_eq!(stats.bump, 7);
+        assert!(stats.is_consistent());
+    }
+
+    #[test]
+    fn months_follow_utc() {
+        assert_eq!(month_id(0), 197001);
+        assert_eq!(month_id(-1), 196912);
+        assert_eq!(month_id(951_782_400), 200002); // 2000-02-29
+        assert_eq!(month_id(1_759_276_799), 202509);
+        assert_eq!(month_id(1_759_276_800), 202510); // 2025-10-01
+        assert_eq!(month_id(1_767_225_599), 202512);
+        assert_eq!(month_id(1_767_225_600), 202601);
+    }
+
+    #[test]
+    fn days_follow_utc() {
+        assert_eq!(unix_day(86_399), 0);
+        assert_eq!(unix_day(1_759_276_800), 20_362);
+    }
+
+    #[test]
+    fn historical_leaves_prove_into_sorted_pair_roots() {
+        let leaf = |attested_at: i64, payout: u64| {
+            historical_leaf(
+                &[1; 32],
+                attested_at,
+                &[2; 32],
+                HASH_ALGO_BLAKE3,
+                &[1, 503, 0, payout],
+                &[3; 64],
+                &[4; 32],
+            )
+        };
+        let leaves = [leaf(100, 5), leaf(101, 5), leaf(100, 6), leaf(102, 7)];
+        assert_ne!(leaves[0], leaves[1]);
+        assert_ne!(leaves[0], leaves[2]);
+        assert_ne!(
+            leaves[0],
+            aggregate_leaf(&[1; 32], &[1, 503, 0, 5], 0, &[3; 64], &[4; 32])
+        );
+
+        assert_eq!(merkle_root(leaves[0], &[]), leaves[0]);
+        let left = merkle_root(leaves[0], &[leaves[1]]);
+        assert_eq!(left, merkle_root(leaves[1], &[leaves[0]]));
+        let right = merkle_root(leaves[2], &[leaves[3]]);
+        let root = merkle_root(leaves[0], &[leaves[1], right]);
+        assert_eq!(root, merkle_root(leaves[3], &[leaves[2], left]));
+        assert_ne!(root, merkle_root(leaves[0], &[leaves[2], right]));
+    }
+
+    #[test]
+    fn settlement_roots_keep_leaf_order() {
+        let (a, b, c) = ([1; 32], [2; 32], [3; 32]);
+        assert_eq!(settlement_root(&[]), [0; 32]);
+        assert_eq!(settlement_root(&[a]), a);
+        assert_eq!(settlement_root(&[a, b]), settlement_node(&a, &b));
+        assert_ne!(settlement_root(&[a, b]), settlement_root(&[b, a]));
+        assert_eq!(
+            settlement_root(&[a, b, c]),
+            settlement_node(&settlement_node(&a, &b), &c)
+        );
+    }
+
+    #[test]
+    fn config_rules() {
+        let config = Config::new(Pubkey::new_unique(), 86_400, 255);
+        assert!(config.is_consistent());
+        assert!(!Config {
+            allowed_hash_algos: 0,
+            ..config.clone()
+        }
+        .is_consistent());
+        assert!(!Config {
+            archive_ttl_seconds: -1,
+            ..config.clone()
+        }
+        .is_consistent());
+        let mut refunds = Config {
+            require_refund_transfer: true,
+            ..config.clone()
+        };
+        assert!(!refunds.is_consistent());
+        refunds.payout_mint = Pubkey::new_unique();
+        assert!(!refunds.is_consistent());
+        refunds.refund_source = Pubkey::new_unique();
+        assert!(refunds.is_consistent());
+    }
+
+    #[test]
+    fn config_field_sizes_match_the_layout() {
+        let config = Config::new(Pubkey::new_unique(), 86_400, 255);
+
+        // Fields before `bump`, then `bump`
+        let fields: usize = CONFIG_FIELD_SIZES.iter().sum();
+        assert_eq!(fields + 1, Config::INIT_SPACE);
+        assert_eq!(config.try_to_vec().unwrap().len(), Config::INIT_SPACE);
+        assert!(is_config_prefix(32));
+        assert!(is_config_prefix(32 + 8));
+        assert!(is_config_prefix(fields));
+        assert!(!is_config_prefix(0));
+        assert!(!is_config_prefix(33));
+        assert!(!is_config_prefix(fields + 1));
+    }
+
+    #[test]
+    fn nonce_registry_entries_are_set_distinct_and_packed() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let registry = |count: u8, entries: &[Pubkey]| {
+            let mut accounts = [Pubkey::default(); MAX_NONCE_ACCOUNTS];
+            accounts[..entries.len()].copy_from_slice(entries);
+            NonceRegistry {
+                accounts,
+                count,
+                bump: 0,
+            }
+        };
+
+        assert!(registry(0, &[]).is_consistent());
+        assert!(registry(2, &[first, second]).is_consistent());
+        assert!(registry(2, &[first, second]).contains(&second));
+        assert!(!registry(2, &[first, first]).is_consistent());
+        assert!(!registry(2, &[first, Pubkey::default()]).is_consistent());
+        assert!(!registry(1, &[first, second]).is_consistent());
+        assert!(!registry(MAX_NONCE_ACCOUNTS as u8 + 1, &[]).is_consistent());
+    }
+
+    #[test]
+    fn layout_versions_by_data_length() {
+        let data = |space: usize| {
+            let mut data = ProofAttestation::DISCRIMINATOR.to_vec();
+            data.resize(8 + space, 0);
+            data
+        };
+
+        for (space, version) in [
+            (ProofAttestationV1::INIT_SPACE, 1),
+            (ATTESTATION_V2_SPACE, 2),
+            (ATTESTATION_V3_SPACE, 3),
+            (ATTESTATION_V4_SPACE, 4),
+            (ATTESTATION_V5_SPACE, 5),
+            (ATTESTATION_V6_SPACE, 6),
+            (ATTESTATION_V7_SPACE, 7),
+            (ProofAttestation::INIT_SPACE, ATTESTATION_LAYOUT_VERSION),
+        ] {
+            assert_eq!(attestation_layout_version(&data(space)), Some(version));
+        }
+        assert_eq!(
+            attestation_layout_version(&data(ProofAttestation::INIT_SPACE + 1)),
+            None
+        );
+        assert_eq!(attestation_layout_version(&data(0)[..7]), None);
+
+        let mut other = data(ProofAttestation::INIT_SPACE);
+        other[0] ^= 1;
+        assert_eq!(attestation_layout_version(&other), None);
+    }
+
+    /// `read` on the instructions sysvar of `instructions`, at instruction `current`
+    fn with_instructions<T>(
+        instructions: &[Instruction],
+        current: u16,
+        read: impl FnOnce(&AccountInfo) -> T,
+    ) -> T {
+        let borrowed: Vec<BorrowedInstruction> = instructions
+            .iter()
+            .map(|instruction| BorrowedInstruction {
+                program_id: &instruction.program_id,
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| BorrowedAccountMeta {
+                        pubkey: &meta.pubkey,
+                        is_signer: meta.is_signer,
+                        is_writable: meta.is_writable,
+                    })
+                    .collect(),
+                data: &instruction.data,
+            })
+            .collect();
+        let mut data = construct_instructions_data(&borrowed);
+        store_current_index(&mut data, current);
+
+        let (key, owner, mut lamports) = (sysvar_instructions::ID, Pubkey::default(), 0);
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        read(&account)
+    }
+
+    /// `find_refund_transfer` at instruction `current` of `instructions`
+    fn refund_found(
+        instructions: &[Instruction],
+        current: u16,
+        config: &Config,
+        claimant: &Pubkey,
+        amount: u64,
+    ) -> Option<Pubkey> {
+        with_instructions(instructions, current, |account| {
+            find_refund_transfer(account, config, claimant, amount).unwrap()
+        })
+    }
+
+    #[test]
+    fn durable_nonce_is_read_from_the_first_instruction() {
+        let (nonce, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let advance = system_instruction::advance_nonce_account(&nonce, &authority);
+        let attest = Instruction::new_with_bytes(crate::ID, &[0; 8], vec![]);
+        let nonce_of = |instructions: &[Instruction], current: u16| {
+            with_instructions(instructions, current, |account| {
+                durable_nonce_account(account).unwrap()
+            })
+        };
+
+        assert_eq!(nonce_of(&[advance.clone(), attest.clone()], 1), Some(nonce));
+        assert_eq!(nonce_of(&[attest.clone(), advance], 0), None);
+        let transfer = system_instruction::transfer(&nonce, &authority, 1);
+        assert_eq!(nonce_of(&[transfer, attest], 1), None);
+    }
+
+    #[test]
+    fn refund_must_reach_the_claimants_token_account() {
+        let config = Config {
+            require_refund_transfer: true,
+            payout_mint: Pubkey::new_unique(),
+            refund_source: Pubkey::new_unique(),
+            ..Config::new(Pubkey::new_unique(), 86_400, 255)
+        };
+        let claimant = Pubkey::new_unique();
+        let ata = associated_token_address(&claimant, &TOKEN_PROGRAM_ID, &config.payout_mint);
+        let transfer = |program_id: Pubkey, source: Pubkey, destination: Pubkey, amount: u64| {
+            let mut data = vec![TRANSFER_CHECKED_TAG];
+            data.extend_from_slice(&amount.to_le_bytes());
+            data.push(6);
+            Instruction::new_with_bytes(
+                program_id,
+                &data,
+                vec![
+                    AccountMeta::new(source, false),
+                    AccountMeta::new_readonly(config.payout_mint, false),
+                    AccountMeta::new(destination, false),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), true),
+                ],
+            )
+        };
+        let attest_for = |claimant: Pubkey| {
+            Instruction::new_with_bytes(
+                crate::ID,
+                &[0; 8],
+                vec![AccountMeta::new_readonly(claimant, false)],
+            )
+        };
+        let attest = attest_for(claimant);
+        let refund = transfer(TOKEN_PROGRAM_ID, config.refund_source, ata, 10_000);
+        let found = |instructions: &[Instruction], current: u16| {
+            refund_found(instructions, current, &config, &claimant, 10_000)
+        };
+
+        // Anywhere in the transaction, under either token program
+        assert_eq!(found(&[attest.clone(), refund.clone()], 0), Some(ata));
+        assert_eq!(found(&[refund.clone(), attest.clone()], 1), Some(ata));
+        let ata_2022 =
+            associated_token_address(&claimant, &TOKEN_2022_PROGRAM_ID, &config.payout_mint);
+        let token_2022 = transfer(
+            TOKEN_2022_PROGRAM_ID,
+            config.refund_source,
+            ata_2022,
+            10_000,
+        );
+        assert_eq!(found(&[attest.clone(), token_2022], 0), Some(ata_2022));
+
+        // Wrong source, destination, amount or program
+        let stranger = Pubkey::new_unique();
+        for wrong in [
+            transfer(TOKEN_PROGRAM_ID, stranger, ata, 10_000),
+            transfer(TOKEN_PROGRAM_ID, config.refund_source, stranger, 10_000),
+            transfer(TOKEN_PROGRAM_ID, config.refund_source, ata, 9_999),
+            transfer(stranger, config.refund_source, ata, 10_000),
+        ] {
+            assert_eq!(found(&[attest.clone(), wrong], 0), None);
+        }
+
+        // Not top-level, or the same transfer backing a second attestation
+        assert_eq!(found(&[refund.clone(), attest.clone()], 0), None);
+        let other = attest_for(Pubkey::new_unique());
+        let other_claimant = [attest.clone(), other, refund.clone()];
+        assert_eq!(found(&other_claimant, 0), Some(ata));
+        assert_eq!(found(&[attest.clone(), attest, refund], 0), None);
+    }
+
+    #[test]
+    fn batch_claims_meet_the_claim_rules() {
+        let merchant_id = [4; 32];
+        let (key, _) = Pubkey::find_program_address(&[MAINTENANCE_SEED, &merchant_id], &crate::ID);
+        let (owner, mut lamports, mut data) = (system_program::ID, 0, Vec::new());
+        let schedule = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let config = Config {
+            dual_proof_threshold: 1_000,
+            max_claim_age_slots: 50,
+            ..Config::new(Pubkey::new_unique(), 86_400, 255)
+        };
+        // As `materialize_from_batch` calls it: no secondary proof, no refund
+        let check = |config: &Config, payout: u64, evidence_slot: u64| {
+            check_claim_rules(
+                config,
+                ClaimRuleAccounts {
+                    attester: Pubkey::new_unique(),
+                    secondary_prover: None,
+                    attester_status: None,
+                    secondary_prover_status: None,
+                    schema: None,
+                    claim_verdicts: None,
+                    maintenance_schedule: &schedule,
+                    claimant: None,
+                    instructions: None,
+                },
+                &[1; 32],
+                HASH_ALGO_BLAKE3,
+                &[1, 503, 0, payout],
+                evidence_slot,
+                100,
+                &merchant_id,
+                false,
+                DEFAULT_SCHEMA_ID,
+                None,
+            )
+            .is_ok()
+        };
+
+        assert!(check(&config, 999, 60));
+        assert!(!check(&config, 1_000, 60));
+        assert!(!check(&config, 999, 49));
+        assert!(!check(&config, 999, 101));
+        let verdicts = Config {
+            verdict_threshold: 1,
+            ..config.clone()
+        };
+        assert!(!check(&verdicts, 999, 60));
+        let refunds = Config {
+            require_refund_transfer: true,
+            payout_mint: Pubkey::new_unique(),
+            refund_source: Pubkey::new_unique(),
+            ..config
+        };
+        assert!(!check(&refunds, 999, 60));
+    }
+
+    #[test]
+    fn maintenance_schedule_must_be_the_merchant_pda() {
+        let merchant_id = [4; 32];
+        let (key, _) = Pubkey::find_program_address(&[MAINTENANCE_SEED, &merchant_id], &crate::ID);
+        let check = |key: &Pubkey, owner: &Pubkey, data: &mut Vec<u8>, slot: u64| {
+            let mut lamports = 0;
+            let account = AccountInfo::new(key, false, false, &mut lamports, data, owner, false, 0);
+            check_maintenance(&account, &merchant_id, slot).is_ok()
+        };
+
+        // Never declared: no windows, but only at the merchant's address
+        assert!(check(&key, &system_program::ID, &mut Vec::new(), 10));
+        let elsewhere = Pubkey::new_unique();
+        assert!(!check(&elsewhere, &system_program::ID, &mut Vec::new(), 10));
+
+        let mut data = Vec::new();
+        MaintenanceSchedule {
+            merchant_id,
+            windows: vec![MaintenanceWindow {
+                start_slot: 10,
+                end_slot: 20,
+                declared_slot: 5,
+            }],
+            bump: 0,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        assert!(!check(&key, &crate::ID, &mut data.clone(), 10));
+        assert!(check(&key, &crate::ID, &mut data.clone(), 21));
+        assert!(!check(&key, &Pubkey::new_unique(), &mut data, 21));
+    }
 }